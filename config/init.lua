@@ -4,9 +4,18 @@
 -- Background
 --
 --
--- A background can either be a 6 digit hex value or an image path
+-- A background can either be a 6 digit hex value or the path to a PNG image
 local background = 0x5E4055
 
+-- Backgrounds Way Cooler paints beneath the windows. They can be set for
+-- every output ("default"), for an output by its name, or for a workspace
+-- ("workspace:<name>"), which takes the place of the one of its output.
+way_cooler.backgrounds = {
+  default = background,
+  -- ["DP-1"] = { image = "/path/to/image.png", mode = "fill" }, -- fill, fit, center or tile
+  -- ["workspace:2"] = { from = 0x5E4055, to = 0x222222, direction = "vertical" }
+}
+
 -- Programs that Way Cooler can run
 way_cooler.programs = {
   -- Name of the window that will be the bar window.
@@ -18,31 +27,143 @@ way_cooler.programs = {
 
 -- Registering programs to run at startup
 -- These programs are only ran once util.program.spawn_programs is called.
-util.program.spawn_at_startup("way-cooler-bg", "--color " .. background)
+-- util.program.spawn_at_startup("firefox")
 
 -- These options are applied to all windows.
 way_cooler.windows = {
+  -- Whether new windows, and windows that ask to be activated, get the focus:
+  -- "always", "smart" (only if you just used the application that opened it,
+  -- so not windows started from a keybinding or a launcher), "urgent" (never,
+  -- but mark them as wanting attention) or "never".
+  -- Rules can override this with their own focus_on_activation option.
+  focus_on_activation = "always",
   gaps = { -- Options for gaps
     size = 0, -- The width of gaps between windows in pixels
   },
   borders = { -- Options for borders
     size = 20, -- The width of the borders between windows in pixels
     inactive_color = 0x386890, -- Color of the borders for inactive containers
-    active_color = 0x57beb9 -- Color of active container borders
+    active_color = 0x57beb9, -- Color of active container borders
+    urgent_color = 0xd64937, -- Color of borders for windows that want attention
+    -- Hides the borders of a window when it's the only one tiled on its workspace
+    smart = false
   },
   title_bar = { -- Options for title bar above windows
     size = 20, -- Size of the title bar
     background_color = 0x386690, -- Color of inactive title bar
     active_background_color = 0x57beb9, -- Color of active title bar
     font_color = 0x0, -- Color of the font for an inactive title bar
-    active_font_color = 0xffffff -- Color of font for active title bar
+    active_font_color = 0xffffff, -- Color of font for active title bar
+    urgent_background_color = 0xd64937, -- Color of title bar for windows that want attention
+    urgent_font_color = 0xffffff, -- Color of font for windows that want attention
+    -- What is written in the title bar. The placeholders are {title}, {class},
+    -- {app_id}, {workspace}, {marks} (e.g "urgent") and {floating}.
+    format = "{title}",
+    font = "sans-serif",
+    font_size = 10,
+    font_weight = "normal", -- "normal" or "bold"
+    align = "left", -- "left", "center" or "right". Titles that don't fit are cut short.
+    -- Shows buttons to float, fullscreen and close windows. Dragging a title
+    -- moves its window, double-clicking it floats the window and
+    -- middle-clicking it closes the window.
+    buttons = true
+  }
+}
+
+-- Rules that are applied to the windows that match them.
+-- A rule matches on the class, app_id and/or (part of) the title of a window.
+way_cooler.rules = {
+  -- Keeps video players from being tiled or resized too small
+  -- video = { class = "mpv", min_width = 320, min_height = 240 },
+  -- Marks chat windows as wanting attention when their title changes
+  -- chat = { class = "irc", urgent_on_title_change = true },
+  -- Keeps a chat client from stealing the focus
+  -- chat_focus = { class = "irc", focus_on_activation = "urgent" }
+  -- Makes a dock a panel along the bottom of its output, windows aren't
+  -- tiled behind it. reserve_size defaults to the height (or width) of the dock.
+  -- dock = { class = "plank", reserve_edge = "bottom", reserve_size = 48 },
+  -- Writes the title bar of terminals differently, title_font, title_font_size
+  -- and title_font_weight can be set the same way.
+  -- terminal = { class = "termite", title_format = "{title} [{workspace}]", title_align = "center" },
+  -- Gives browsers borders without a title bar. border_style is "none", "pixel"
+  -- or "normal", and border_color, title_color and title_font_color replace
+  -- the inactive colors.
+  -- browser = { class = "firefox", border_style = "pixel", border_color = 0x555555 }
+  -- Windows that draw their own title bars, like most GTK applications, get no
+  -- borders. This gives them borders anyway.
+  -- files = { class = "org.gnome.Nautilus", border_style = "normal" }
+}
+
+-- Options that change how workspaces behave.
+way_cooler.workspaces = {
+  -- Passes over workspaces that have no windows on them
+  -- when switching to the next/previous workspace.
+  skip_empty = false,
+  -- Gives every output its own set of workspaces, instead of sharing them.
+  -- With this set, Mod+1 switches to workspace 1 of the focused output.
+  per_output = false,
+  -- Which output a workspace is made on, when workspaces are shared.
+  -- Workspaces that aren't listed are made on the focused output.
+  outputs = {
+    -- web = "DP-1"
   }
 }
 
 -- Options that change how the mouse behaves.
 way_cooler.mouse = {
   -- Locks the mouse to the corner of the window the user is resizing.
-  lock_to_corner_on_resize = false
+  lock_to_corner_on_resize = false,
+  -- Focuses the window under the mouse, without having to click on it.
+  focus_follows_mouse = false,
+  -- How long (in milliseconds) the mouse has to be over a window
+  -- before focus follows mouse focuses it.
+  focus_delay = 0,
+  -- Moves the mouse to the center of the focused window
+  -- when the focus is moved with the keyboard.
+  warp_to_focus = false
+}
+
+-- Options for each output, by the name of the output. They are applied
+-- when the output is plugged in. way_cooler.list_outputs() lists the outputs,
+-- way_cooler.configure_output(name, options) changes one right away.
+way_cooler.outputs = {
+  -- Draws everything on a HiDPI laptop panel twice as large.
+  -- ["eDP-1"] = { scale = 2 },
+  -- Picks the resolution of a monitor.
+  -- ["DP-1"] = { resolution = { w = 2560, h = 1440 } },
+  -- Turns off a monitor, its workspaces are moved to the other outputs.
+  -- ["HDMI-A-1"] = { enabled = false }
+}
+
+-- The status bar Way Cooler draws on every output. It shows the workspaces
+-- (click on one to switch to it), the title of the focused window and blocks
+-- of text. Blocks can also be set over D-Bus, with org.way_cooler.Bar.SetBlock.
+way_cooler.bar = {
+  enabled = true,
+  position = "top", -- "top" or "bottom"
+  height = 20,
+  background_color = 0x222222,
+  font_color = 0xffffff,
+  focused_color = 0x57beb9, -- Behind the workspace you are on
+  visible_color = 0x386890, -- Behind workspaces shown on other outputs
+  urgent_color = 0xd64937, -- Behind workspaces with windows that want attention
+  -- Text shown on the right of the bar, in the order of their names.
+  blocks = {
+    -- greeting = "Hello from Way Cooler"
+  }
+}
+
+-- Messages shown for a moment over the focused output: Lua errors,
+-- reloading this file and switching workspaces. Programs can show their own,
+-- with way_cooler.show_osd or org.way_cooler.Screen.ShowOsd over D-Bus.
+way_cooler.osd = {
+  enabled = true,
+  timeout = 2000, -- How long messages are shown for, in milliseconds
+  workspaces = true, -- Show the name of the workspace when switching to it
+  font_size = 16,
+  background_color = 0x222222,
+  font_color = 0xffffff,
+  error_color = 0xd64937 -- Behind errors
 }
 
 --
@@ -104,6 +225,33 @@ local keys = {
   key({ mod, "Shift" }, "q", "close_window"),
   key({ mod, "Shift" }, "space", "toggle_float_active"),
   key({ mod }, "space", "toggle_float_focus"),
+  key({ mod }, "minus", "minimize_active"),
+  key({ mod, "Shift" }, "minus", "restore_minimized"),
+  key({ mod }, "z", "undo"),
+  key({ mod, "Shift" }, "z", "redo"),
+
+  -- Workspace navigation
+  key({ mod }, "bracketright", "switch_workspace_next"),
+  key({ mod }, "bracketleft", "switch_workspace_prev"),
+  key({ mod, "Shift" }, "bracketright", "switch_workspace_next_on_output"),
+  key({ mod, "Shift" }, "bracketleft", "switch_workspace_prev_on_output"),
+  key({ mod }, "grave", "switch_workspace_back_and_forth"),
+  key({ mod, "Shift" }, "o", "move_workspace_to_next_output"),
+  key({ mod }, "u", "focus_urgent"),
+
+  -- Hint mode: label the windows, then type a label to focus on that window,
+  -- swap it with the focused window, or move the focused window next to it.
+  key({ mod }, "semicolon", "hint_focus"),
+  key({ mod, "Shift" }, "semicolon", "hint_swap"),
+  key({ mod, "Control" }, "semicolon", "hint_move"),
+
+  -- Window switcher: keep holding the modifier and press tab to pick a window,
+  -- type to search the windows of every workspace, let go to focus on it.
+  key({ mod }, "tab", "switcher_next"),
+  key({ mod, "Shift" }, "tab", "switcher_prev"),
+  -- Named workspaces can be reached with a function
+  -- key({ mod }, "w", function() way_cooler.switch_workspace("web") end),
+  -- key({ mod, "Shift" }, "w", function() way_cooler.move_to_workspace("web") end),
   key({ mod, "Shift" }, "r", "way_cooler_restart"),
 
   -- Quitting way-cooler is hardcoded to Alt+Shift+Esc.
@@ -129,6 +277,15 @@ way_cooler.register_mouse_modifier(mod)
 
 -- Execute some code after Way Cooler is finished initializing
 way_cooler.on_init = function()
+  -- Layout templates make placeholders in a workspace,
+  -- which are filled by the windows that match them when they open.
+  -- way_cooler.apply_layout("2", {
+  --   layout = "horizontal",
+  --   children = {
+  --     { class = "Firefox", size = 0.6 },
+  --     { layout = "vertical", children = { { title = "vim" }, { class = "weston-terminal" } } }
+  --   }
+  -- })
   util.program.spawn_startup_programs()
 end
 
@@ -88,6 +88,98 @@ commands.register_mouse_modifier = function(mod)
   rust.register_mouse_modifier(mod)
 end
 
+-- Apply a layout template to a workspace, making placeholders for windows
+commands.apply_layout = function(workspace, template)
+  assert(type(workspace) == 'string', "workspace: expected a string")
+  assert(type(template) == 'table', "template: expected a table")
+  rust.apply_layout(workspace, template)
+end
+
+-- Switch to a workspace by name, creating it if it doesn't exist
+commands.switch_workspace = function(workspace)
+  assert(type(workspace) == 'string', "workspace: expected a string")
+  rust.switch_workspace(workspace)
+end
+
+-- Move the active window to a workspace by name, creating it if it doesn't exist
+commands.move_to_workspace = function(workspace)
+  assert(type(workspace) == 'string', "workspace: expected a string")
+  rust.move_to_workspace(workspace)
+end
+
+-- Move a workspace, with everything on it, to the output with the name
+commands.move_workspace_to_output = function(workspace, output)
+  assert(type(workspace) == 'string', "workspace: expected a string")
+  assert(type(output) == 'string', "output: expected a string")
+  rust.move_workspace_to_output(workspace, output)
+end
+
+-- List the outputs by name, with their resolution, position, scale and modes
+commands.list_outputs = function()
+  return rust.list_outputs()
+end
+
+-- Change the resolution or scale of an output, or turn it off
+-- e.g way_cooler.configure_output("eDP-1", { enabled = false })
+commands.configure_output = function(output, settings)
+  assert(type(output) == 'string', "output: expected a string")
+  assert(type(settings) == 'table', "settings: expected a table")
+  rust.configure_output(output, settings)
+end
+
+-- Reserve space along an edge of an output for a panel, windows aren't
+-- tiled in it. Returns an id to release the space with.
+-- e.g local id = way_cooler.reserve_edge("DP-1", "bottom", 30)
+commands.reserve_edge = function(output, edge, size)
+  assert(type(output) == 'string', "output: expected a string")
+  assert(type(edge) == 'string', "edge: expected a string")
+  assert(type(size) == 'number', "size: expected a number")
+  return rust.reserve_edge(output, edge, size)
+end
+
+-- Release space reserved with reserve_edge
+commands.release_edge = function(id)
+  assert(type(id) == 'string', "id: expected a string")
+  rust.release_edge(id)
+end
+
+-- Change the background of "default", an output or "workspace:<name>",
+-- in place of the one in way_cooler.backgrounds
+-- e.g way_cooler.set_background("workspace:2", { image = "/path/to/image.png", mode = "fit" })
+commands.set_background = function(target, background)
+  assert(type(target) == 'string', "target: expected a string")
+  rust.set_background(target, background)
+end
+
+-- Go back to the background in way_cooler.backgrounds
+commands.clear_background = function(target)
+  assert(type(target) == 'string', "target: expected a string")
+  rust.clear_background(target)
+end
+
+-- Show a message on the focused output for a moment
+-- e.g way_cooler.show_osd("Volume", { value = 40, tag = "volume" })
+-- The options are all optional: value (a percentage shown as a bar),
+-- tag (messages with the same tag replace each other),
+-- timeout (in seconds) and error (shows it as an error).
+commands.show_osd = function(text, options)
+  assert(type(text) == 'string', "text: expected a string")
+  options = options or {}
+  assert(type(options) == 'table', "options: expected a table")
+  rust.show_osd(text, options)
+end
+
+-- Sets the border style ("none", "pixel" or "normal") and colors of a view,
+-- or of the active view if the id is empty, instead of what the rules say.
+-- e.g way_cooler.set_borders("", { border_style = "pixel", border_color = 0xff0000 })
+-- The settings are all optional: border_style, border_color,
+-- title_color and title_font_color.
+commands.set_borders = function(id, settings)
+  assert(type(id) == 'string', "id: expected a string")
+  assert(type(settings) == 'table', "settings: expected a table")
+  rust.set_borders(id, settings)
+end
+
 way_cooler_mt.__index = function(_table, key)
     if commands[key] then
       return commands[key]
@@ -12,11 +12,18 @@ use rustwlc::render::{read_pixels, wlc_pixel_format};
 use uuid::Uuid;
 
 use super::keys::{self, KeyPress, KeyEvent};
-use super::layout::{lock_tree, try_lock_tree, try_lock_action, Action, ContainerType,
-                    MovementError, TreeError, FocusError};
-use super::layout::commands::set_performing_action;
+use super::layout::{lock_tree, try_lock_tree, try_lock_action, try_lock_hints, try_lock_switcher,
+                    try_lock_status_bars, try_lock_backgrounds, lock_rendered_borders,
+                    Action, ContainerType,
+                    MovementError, TreeError, note_interaction, configured_scale,
+                    bar_workspace_at, show_osd, render_osd, OsdMessage,
+                    TitleBarClick, title_bar_click, focus_follows_mouse,
+                    bar_outdated, mark_bars_outdated, background_outdated,
+                    mark_backgrounds_outdated};
+use super::layout::commands::{set_performing_action, hint_key, switcher_key};
 use super::lua::{self, LuaQuery};
 
+use ::render::{Renderable, output_scale, logical_resolution};
 use ::render::screen_scrape::{read_screen_scrape_lock, scraped_pixels_lock,
                               sync_scrape};
 
@@ -30,6 +37,7 @@ const EVENT_PASS_THROUGH: bool = false;
 
 const LEFT_CLICK: u32 = 0x110;
 const RIGHT_CLICK: u32 = 0x111;
+const MIDDLE_CLICK: u32 = 0x112;
 
 pub extern fn output_created(output: WlcOutput) -> bool {
     trace!("output_created: {:?}: {}", output, output.get_name());
@@ -37,7 +45,7 @@ pub extern fn output_created(output: WlcOutput) -> bool {
         let result = tree.add_output(output).and_then(|_|{
             tree.switch_to_workspace(&"1")
                 .map(|_| tree.layout_active_of(ContainerType::Output))
-        });
+        }).and_then(|_| tree.apply_output_settings());
         match result {
             // If the output exists, we just couldn't add it to the tree because
             // it's already there. That's OK
@@ -62,15 +70,55 @@ pub extern fn output_resolution(output: WlcOutput,
     trace!("output_resolution: {:?} from  {:?} to {:?}",
            output, *old_size_ptr, *new_size_ptr);
     // Update the resolution of the output and its children
-    let scale = 1;
+    let scale = configured_scale(output).unwrap_or_else(|| output_scale(output));
     output.set_resolution(*new_size_ptr, scale);
+    mark_backgrounds_outdated();
     if let Ok(mut tree) = try_lock_tree() {
         tree.layout_active_of(ContainerType::Output)
             .expect("Could not layout active output");
     }
 }
 
+pub extern fn pre_render(output: WlcOutput) {
+    if let Ok(mut backgrounds) = try_lock_backgrounds() {
+        if background_outdated(output) {
+            match try_lock_tree() {
+                Ok(tree) => tree.update_background(&mut *backgrounds, output),
+                // Try again on the next frame
+                Err(_) => output.schedule_render()
+            }
+        }
+        for background in backgrounds.iter_mut()
+            .filter(|background| background.get_output() == output) {
+            background.render();
+        }
+    }
+}
+
 pub extern fn post_render(output: WlcOutput) {
+    if let Ok(mut bars) = try_lock_status_bars() {
+        if bar_outdated(output) {
+            match try_lock_tree() {
+                Ok(tree) => tree.update_status_bar(&mut *bars, output),
+                // Try again on the next frame
+                Err(_) => output.schedule_render()
+            }
+        }
+        for bar in bars.iter_mut().filter(|bar| bar.get_output() == output) {
+            bar.render();
+        }
+    }
+    if let Ok(mut hints) = try_lock_hints() {
+        if let Some(ref mut hints) = *hints {
+            hints.render(output);
+        }
+    }
+    if let Ok(mut switcher) = try_lock_switcher() {
+        if let Some(ref mut switcher) = *switcher {
+            switcher.render(output);
+        }
+    }
+    render_osd(output);
     let need_to_fetch = read_screen_scrape_lock();
     if *need_to_fetch {
         if let Ok(mut scraped_pixels) = scraped_pixels_lock() {
@@ -111,13 +159,25 @@ pub extern fn view_created(view: WlcView) -> bool {
             }
         }
     }
+    // Views that space is reserved for by a rule are panels, which aren't tiled
+    if let Ok(mut tree) = lock_tree() {
+        match tree.add_panel(view) {
+            Ok(true) => {
+                view.set_mask(1);
+                view.bring_to_front();
+                return true
+            },
+            Ok(false) => {},
+            Err(err) => warn!("Could not reserve space for {:?}: {:?}", view, err)
+        }
+    }
     // TODO Remove this hack
     if view.get_class().as_str() == "Background" {
         debug!("Setting background: {}", view.get_title());
         view.send_to_back();
         view.set_mask(1);
         let output = view.get_output();
-        let resolution = output.get_resolution()
+        let resolution = logical_resolution(output)
             .expect("Couldn't get output resolution");
         let fullscreen = Geometry {
             origin: Point { x: 0, y: 0 },
@@ -137,13 +197,15 @@ pub extern fn view_created(view: WlcView) -> bool {
         return false
     }
     if let Ok(mut tree) = lock_tree() {
+        let previous = tree.active_id();
         let result = tree.add_view(view).and_then(|_| {
             view.set_state(VIEW_MAXIMIZED, true);
-            match tree.set_active_view(view) {
-                // If blocked by fullscreen, we don't focus on purpose
-                Err(TreeError::Focus(FocusError::BlockedByFullscreen(_, _))) => Ok(()),
-                result => result
+            // Views that filled a placeholder in another workspace don't take focus
+            let id = try!(tree.lookup_view(view));
+            if !try!(tree.container_in_active_workspace(id)) {
+                return Ok(())
             }
+            tree.focus_requested(view, previous)
         });
         if result.is_err() {
             warn!("Could not add {:?}. Reason: {:?}", view, result);
@@ -156,8 +218,14 @@ pub extern fn view_created(view: WlcView) -> bool {
 
 pub extern fn view_destroyed(view: WlcView) {
     trace!("view_destroyed: {:?}", view);
+    if let Ok(mut rendered) = lock_rendered_borders() {
+        rendered.remove(&view);
+    }
     match try_lock_tree() {
         Ok(mut tree) => {
+            if tree.release_view_reservations(view) {
+                return
+            }
             tree.remove_view(view).unwrap_or_else(|err| {
                 match err {
                     TreeError::ViewNotFound(_) => {},
@@ -173,6 +241,7 @@ pub extern fn view_destroyed(view: WlcView) {
 pub extern fn view_focus(current: WlcView, focused: bool) {
     trace!("view_focus: {:?} {}", current, focused);
     current.set_state(VIEW_ACTIVATED, focused);
+    mark_bars_outdated();
     if let Ok(mut tree) = try_lock_tree() {
         match tree.set_active_view(current) {
             Ok(_) => {},
@@ -185,6 +254,7 @@ pub extern fn view_focus(current: WlcView, focused: bool) {
 
 pub extern fn view_props_changed(view: WlcView, prop: ViewPropertyType) {
     if prop.contains(PROPERTY_TITLE) {
+        mark_bars_outdated();
         if let Ok(mut tree) = try_lock_tree() {
             match tree.update_title(view) {
                 Ok(_) => {},
@@ -224,12 +294,21 @@ pub extern fn view_request_state(view: WlcView, state: ViewState, toggle: bool)
                 warn!("Could not find view {:?} in tree", view);
             }
         }
+    } else if state == VIEW_ACTIVATED && toggle {
+        // The view wants attention, the focus policy decides if it gets it
+        if let Ok(mut tree) = try_lock_tree() {
+            if tree.lookup_view(view).is_ok() {
+                tree.focus_requested(view, None).unwrap_or_else(|err| {
+                    warn!("Could not activate {:?}: {:?}", view, err);
+                });
+            }
+        }
     }
 }
 
 pub extern fn view_request_move(view: WlcView, _dest: &Point) {
     if let Ok(mut tree) = try_lock_tree() {
-        if let Err(err) = tree.set_active_view(view) {
+        if let Err(err) = tree.focus_requested(view, None) {
             error!("view_request_move error: {:?}", err);
         }
     }
@@ -252,13 +331,27 @@ pub extern fn view_request_resize(view: WlcView, edge: ResizeEdge, point: &Point
     }
 }
 
-pub extern fn keyboard_key(_view: WlcView, _time: u32, mods: &KeyboardModifiers,
+pub extern fn keyboard_key(view: WlcView, _time: u32, mods: &KeyboardModifiers,
                            key: u32, state: KeyState) -> bool {
+    if state == KeyState::Pressed {
+        note_interaction(view);
+    }
     let empty_mods: KeyboardModifiers = KeyboardModifiers {
             mods: MOD_NONE,
             leds: KeyboardLed::empty()
     };
     let sym = keyboard::get_keysym_for_key(key, empty_mods);
+
+    // The window switcher grabs the keyboard until the modifier is let go
+    if switcher_key(&sym, mods.mods, state) {
+        return EVENT_BLOCKED
+    }
+
+    // Hint mode grabs the keyboard until a label is typed
+    if state == KeyState::Pressed && hint_key(&sym) {
+        return EVENT_BLOCKED
+    }
+
     let press = KeyPress::new(mods.mods, sym);
 
     if state == KeyState::Pressed {
@@ -272,12 +365,11 @@ pub extern fn keyboard_key(_view: WlcView, _time: u32, mods: &KeyboardModifiers,
                     match lua::send(LuaQuery::HandleKey(press)) {
                         Ok(_) => {},
                         Err(err) => {
-                            // We may want to wait for Lua's reply from
-                            // keypresses; for example if the table is tampered
-                            // with or Lua is restarted or Lua has an error.
-                            // ATM Lua asynchronously logs this but in the future
-                            // an error popup/etc is a good idea.
+                            // Errors from running the keybinding are shown
+                            // by the Lua thread, which replies asynchronously.
                             error!("Error sending keypress: {:?}", err);
+                            show_osd(OsdMessage::error(
+                                format!("Could not run the keybinding: {:?}", err)));
                         }
                     }
                 }
@@ -291,6 +383,10 @@ pub extern fn keyboard_key(_view: WlcView, _time: u32, mods: &KeyboardModifiers,
 
 pub extern fn view_request_geometry(view: WlcView, geometry: &Geometry) {
     if let Ok(mut tree) = try_lock_tree() {
+        // Panels are placed in their reserved space, which fits their size
+        if tree.resize_panel(view, *geometry) {
+            return
+        }
         tree.update_floating_geometry(view, *geometry).unwrap_or_else(|_| {
             warn!("Could not find view {:#?} \
                    in order to update geometry w/ {:#?}",
@@ -299,10 +395,53 @@ pub extern fn view_request_geometry(view: WlcView, geometry: &Geometry) {
     }
 }
 
-pub extern fn pointer_button(view: WlcView, _time: u32,
+pub extern fn pointer_button(view: WlcView, time: u32,
                          mods: &KeyboardModifiers, button: u32,
                              state: ButtonState, point: &Point) -> bool {
     if state == ButtonState::Pressed {
+        note_interaction(view);
+        if button == LEFT_CLICK {
+            let workspace = try_lock_status_bars().ok()
+                .and_then(|bars| bar_workspace_at(&*bars, WlcOutput::focused(), *point));
+            if let Some(name) = workspace {
+                if let Ok(mut tree) = try_lock_tree() {
+                    tree.switch_to_workspace(name.as_str())
+                        .unwrap_or_else(|err| {
+                            warn!("Could not switch to workspace {}: {:?}", name, err);
+                        });
+                }
+                return EVENT_BLOCKED
+            }
+        }
+        if button == LEFT_CLICK || button == MIDDLE_CLICK {
+            // The tree is unlocked before closing the view
+            let clicked = try_lock_tree().ok().and_then(|mut tree| {
+                tree.title_bar_at(*point).map(|(view, part)| {
+                    let click = if button == MIDDLE_CLICK {
+                        TitleBarClick::Close
+                    } else {
+                        title_bar_click(view, part, time)
+                    };
+                    tree.click_title_bar(view, click).unwrap_or_else(|err| {
+                        warn!("Could not handle click on title bar of {:?}: {:?}", view, err);
+                    });
+                    (view, click)
+                })
+            });
+            if let Some((view, click)) = clicked {
+                match click {
+                    TitleBarClick::Close => view.close(),
+                    TitleBarClick::Drag => set_performing_action(Some(Action {
+                        view: view,
+                        grab: *point,
+                        edges: ResizeEdge::empty(),
+                        title_bar: true
+                    })),
+                    TitleBarClick::ToggleFloating | TitleBarClick::ToggleFullscreen => {}
+                }
+                return EVENT_BLOCKED
+            }
+        }
         let mouse_mod = keys::mouse_modifier();
         if button == LEFT_CLICK && !view.is_root() {
             if let Ok(mut tree) = try_lock_tree() {
@@ -314,7 +453,8 @@ pub extern fn pointer_button(view: WlcView, _time: u32,
                     let action = Action {
                         view: view,
                         grab: *point,
-                        edges: ResizeEdge::empty()
+                        edges: ResizeEdge::empty(),
+                        title_bar: false
                     };
                     set_performing_action(Some(action));
                 }
@@ -328,7 +468,8 @@ pub extern fn pointer_button(view: WlcView, _time: u32,
                 let action = Action {
                     view: view,
                     grab: *point,
-                    edges: ResizeEdge::empty()
+                    edges: ResizeEdge::empty(),
+                    title_bar: false
                 };
                 set_performing_action(Some(action));
                 let geometry = view.get_geometry()
@@ -341,7 +482,8 @@ pub extern fn pointer_button(view: WlcView, _time: u32,
                         .unwrap_or(Action {
                             view: view,
                             grab: *point,
-                            edges: ResizeEdge::empty()
+                            edges: ResizeEdge::empty(),
+                            title_bar: false
                         });
                     let flag_x = if point.x < halfw {
                         RESIZE_LEFT
@@ -367,6 +509,7 @@ pub extern fn pointer_button(view: WlcView, _time: u32,
             }
         }
     } else {
+        let mut docked_view = None;
         if let Ok(lock) = try_lock_action() {
             let unknown = format!("unknown ({})", button);
             info!("User released {:?} mouse button",
@@ -381,11 +524,21 @@ pub extern fn pointer_button(view: WlcView, _time: u32,
                     if view.get_state().contains(VIEW_RESIZING) {
                         view.set_state(VIEW_RESIZING, false);
                     }
+                    if action.title_bar {
+                        docked_view = Some(view);
+                    }
                 },
                 _ => {}
             }
         }
         set_performing_action(None);
+        if let Some(view) = docked_view {
+            if let Ok(mut tree) = try_lock_tree() {
+                tree.dock_view(view, *point).unwrap_or_else(|err| {
+                    warn!("Could not dock {:?}: {:?}", view, err);
+                });
+            }
+        }
     }
     EVENT_PASS_THROUGH
 }
@@ -405,7 +558,16 @@ pub extern fn pointer_motion(view: WlcView, _time: u32, point: &Point) -> bool {
         }
     }
     match maybe_action {
-        None => result = EVENT_PASS_THROUGH,
+        None => {
+            if focus_follows_mouse() {
+                if let Ok(mut tree) = try_lock_tree() {
+                    tree.pointer_moved(view, *point).unwrap_or_else(|err| {
+                        warn!("Could not focus on {:?} under the pointer: {:?}", view, err);
+                    });
+                }
+            }
+            result = EVENT_PASS_THROUGH
+        },
         Some(action) => {
             if action.edges.bits() != 0 {
                 if let Ok(mut tree) = try_lock_tree() {
@@ -456,14 +618,12 @@ pub extern fn compositor_terminating() {
 }
 
 pub extern fn view_pre_render(view: WlcView) {
-    if let Ok(mut tree) = lock_tree() {
-        tree.render_borders(view).unwrap_or_else(|err| {
-            match err {
-                // TODO Only filter if background or bar
-                TreeError::ViewNotFound(_) => {},
-                err => warn!("Error while rendering borders: {:?}", err)
-            }
-        })
+    // The borders are drawn when they change, which holds the tree lock.
+    // Rendering them only copies the last drawn surface.
+    if let Ok(mut rendered) = lock_rendered_borders() {
+        if let Some(borders) = rendered.get_mut(&view) {
+            borders.render();
+        }
     }
 }
 
@@ -475,6 +635,7 @@ pub fn init() {
     callback::output_destroyed(output_destroyed);
     callback::output_focus(output_focus);
     callback::output_resolution(output_resolution);
+    callback::output_render_pre(pre_render);
     callback::output_render_post(post_render);
     callback::view_created(view_created);
     callback::view_destroyed(view_destroyed);
@@ -84,6 +84,17 @@ pub fn register_defaults() {
                            move_to_workspace_9, "9";
                            move_to_workspace_0, "0");
 
+    register("switch_workspace_next", Arc::new(layout_cmds::switch_workspace_next));
+    register("switch_workspace_prev", Arc::new(layout_cmds::switch_workspace_prev));
+    register("switch_workspace_next_on_output",
+             Arc::new(layout_cmds::switch_workspace_next_on_output));
+    register("switch_workspace_prev_on_output",
+             Arc::new(layout_cmds::switch_workspace_prev_on_output));
+    register("switch_workspace_back_and_forth",
+             Arc::new(layout_cmds::switch_workspace_back_and_forth));
+    register("move_workspace_to_next_output",
+             Arc::new(layout_cmds::move_workspace_to_next_output));
+
     register("horizontal_vertical_switch", Arc::new(layout_cmds::tile_switch));
     register("split_vertical", Arc::new(layout_cmds::split_vertical));
     register("split_horizontal", Arc::new(layout_cmds::split_horizontal));
@@ -99,6 +110,16 @@ pub fn register_defaults() {
     register("close_window", Arc::new(layout_cmds::remove_active));
     register("toggle_float_active", Arc::new(layout_cmds::toggle_float));
     register("toggle_float_focus", Arc::new(layout_cmds::toggle_float_focus));
+    register("minimize_active", Arc::new(layout_cmds::minimize_active));
+    register("restore_minimized", Arc::new(layout_cmds::restore_minimized));
+    register("focus_urgent", Arc::new(layout_cmds::focus_urgent));
+    register("hint_focus", Arc::new(layout_cmds::hint_focus));
+    register("hint_swap", Arc::new(layout_cmds::hint_swap));
+    register("hint_move", Arc::new(layout_cmds::hint_move));
+    register("switcher_next", Arc::new(layout_cmds::switcher_next));
+    register("switcher_prev", Arc::new(layout_cmds::switcher_prev));
+    register("undo", Arc::new(layout_cmds::undo));
+    register("redo", Arc::new(layout_cmds::redo));
 }
 
 // All of the methods defined should be registered.
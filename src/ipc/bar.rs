@@ -0,0 +1,19 @@
+#![allow(unused_variables, dead_code)] // macros
+
+/// Dbus macro for the status bar drawn by Way Cooler
+
+use super::super::layout::{set_bar_block, remove_bar_block};
+
+dbus_interface! {
+    path: "/org/way_cooler/Bar";
+    name: "org.way_cooler.Bar";
+
+    fn SetBlock(name: String, text: String) -> success: DBusResult<bool> {
+        set_bar_block(name, text);
+        Ok(true)
+    }
+
+    fn RemoveBlock(name: String) -> success: DBusResult<bool> {
+        Ok(remove_bar_block(name.as_str()))
+    }
+}
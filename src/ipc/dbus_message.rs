@@ -1,5 +1,28 @@
 //! Message sent to dbus instances
 
-pub struct DBusMessage {
-    
+use dbus::Message;
+use rustc_serialize::json::ToJson;
+
+/// Something that happened in way-cooler that dbus clients are told about,
+/// as a signal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DBusMessage {
+    /// The workspaces with views that want attention changed,
+    /// holds the names of the workspaces that want it now.
+    UrgentWorkspaces(Vec<String>)
+}
+
+impl DBusMessage {
+    /// Makes the signal for this message.
+    pub fn into_signal(self) -> Message {
+        match self {
+            DBusMessage::UrgentWorkspaces(names) => {
+                Message::new_signal("/org/way_cooler/Layout",
+                                    "org.way_cooler.Layout",
+                                    "UrgentWorkspaces")
+                    .expect("Could not make UrgentWorkspaces signal")
+                    .append(format!("{}", names.to_json()))
+            }
+        }
+    }
 }
@@ -2,17 +2,24 @@
 
 /// Dbus macro for Layout code
 
-use super::utils::{parse_edge, parse_uuid, parse_direction, parse_axis, lock_tree_dbus};
+use super::utils::{parse_edge, parse_uuid, parse_direction, parse_axis,
+                   parse_workspace_scope, lock_tree_dbus};
 
 use dbus::tree::MethodErr;
 
-use super::super::layout::{Layout, commands as layout_cmd};
+use super::super::layout::{BorderSettings, Layout, commands as layout_cmd};
 use rustwlc::{ResizeEdge, Point};
+use rustc_serialize::json::{Json, ToJson};
 
 dbus_interface! {
     path: "/org/way_cooler/Layout";
     name: "org.way_cooler.Layout";
 
+    property UrgentWorkspaceNames: String {
+        let tree = try!(lock_tree_dbus());
+        Ok(format!("{}", tree.urgent_workspaces().to_json()))
+    }
+
     fn ActiveContainerId() -> container_id: DBusResult<String> {
         let tree = try!(lock_tree_dbus());
         match tree.active_id() {
@@ -77,6 +84,64 @@ dbus_interface! {
             .map_err(|err| MethodErr::failed(&format!("{:?}", err)))
     }
 
+    fn NextWorkspace(scope: String, skip_empty: bool) -> success: DBusResult<bool> {
+        let scope = try!(parse_workspace_scope("scope", scope.as_str()));
+        let mut tree = try!(lock_tree_dbus());
+        tree.switch_to_neighbouring_workspace(scope, true, skip_empty)
+            .and(Ok(true))
+            .map_err(|err| MethodErr::failed(&format!("{:?}", err)))
+    }
+
+    fn PrevWorkspace(scope: String, skip_empty: bool) -> success: DBusResult<bool> {
+        let scope = try!(parse_workspace_scope("scope", scope.as_str()));
+        let mut tree = try!(lock_tree_dbus());
+        tree.switch_to_neighbouring_workspace(scope, false, skip_empty)
+            .and(Ok(true))
+            .map_err(|err| MethodErr::failed(&format!("{:?}", err)))
+    }
+
+    fn SwitchWorkspaceBackAndForth() -> success: DBusResult<bool> {
+        let mut tree = try!(lock_tree_dbus());
+        tree.switch_workspace_back_and_forth()
+            .and(Ok(true))
+            .map_err(|err| MethodErr::failed(&format!("{:?}", err)))
+    }
+
+    fn RenameWorkspace(w_name: String, new_name: String) -> success: DBusResult<bool> {
+        let mut tree = try!(lock_tree_dbus());
+        tree.rename_workspace(w_name.as_str(), new_name.as_str())
+            .and(Ok(true))
+            .map_err(|err| MethodErr::failed(&format!("{:?}", err)))
+    }
+
+    fn ReorderWorkspace(w_name: String, position: u32) -> success: DBusResult<bool> {
+        let mut tree = try!(lock_tree_dbus());
+        tree.reorder_workspace(w_name.as_str(), position as usize)
+            .and(Ok(true))
+            .map_err(|err| MethodErr::failed(&format!("{:?}", err)))
+    }
+
+    fn MoveWorkspaceToOutput(w_name: String, output_id: String) -> success: DBusResult<bool> {
+        let uuid = try!(try!(parse_uuid("output_id", &output_id))
+                        .ok_or(MethodErr::invalid_arg(&"output_id: No output given")));
+        let mut tree = try!(lock_tree_dbus());
+        tree.move_workspace_to_output(w_name.as_str(), uuid)
+            .and(Ok(true))
+            .map_err(|err| MethodErr::failed(&format!("{:?}", err)))
+    }
+
+    fn DeleteWorkspace(w_name: String) -> success: DBusResult<bool> {
+        let mut tree = try!(lock_tree_dbus());
+        tree.delete_workspace(w_name.as_str())
+            .and(Ok(true))
+            .map_err(|err| MethodErr::failed(&format!("{:?}", err)))
+    }
+
+    fn Workspaces() -> workspaces: DBusResult<String> {
+        let tree = try!(lock_tree_dbus());
+        Ok(format!("{}", tree.workspaces()))
+    }
+
     fn CloseView(view_id: String) -> success: DBusResult<bool> {
         let mut tree = try!(lock_tree_dbus());
         let uuid = try!(try!(parse_uuid("view_id", &view_id))
@@ -116,6 +181,88 @@ dbus_interface! {
             .map_err(|err| MethodErr::failed(&format!("{:?}", err)))
     }
 
+    fn Minimize(view_id: String) -> success: DBusResult<bool> {
+        let mut tree = try!(lock_tree_dbus());
+        let uuid = try!(try!(parse_uuid("view_id", &view_id))
+                        .or_else(|| tree.active_id())
+                        .ok_or(MethodErr::failed(&"No active container")));
+        tree.minimize_view(uuid)
+            .and(Ok(true))
+            .map_err(|err| MethodErr::failed(&format!("{:?}", err)))
+    }
+
+    fn Restore(view_id: String) -> success: DBusResult<bool> {
+        let mut tree = try!(lock_tree_dbus());
+        let uuid = try!(try!(parse_uuid("view_id", &view_id))
+                        .ok_or(MethodErr::invalid_arg(&"view_id: expected a UUID")));
+        tree.restore_view(uuid)
+            .and(Ok(true))
+            .map_err(|err| MethodErr::failed(&format!("{:?}", err)))
+    }
+
+    fn MinimizedViews() -> views: DBusResult<String> {
+        let tree = try!(lock_tree_dbus());
+        Ok(format!("{}", tree.minimized_views()))
+    }
+
+    fn SetUrgent(view_id: String, urgent: bool) -> success: DBusResult<bool> {
+        let mut tree = try!(lock_tree_dbus());
+        let uuid = try!(try!(parse_uuid("view_id", &view_id))
+                        .ok_or(MethodErr::invalid_arg(&"view_id: expected a UUID")));
+        tree.set_urgent(uuid, urgent)
+            .and(Ok(true))
+            .map_err(|err| MethodErr::failed(&format!("{:?}", err)))
+    }
+
+    fn SetBorders(view_id: String, settings: String) -> success: DBusResult<bool> {
+        let settings = try!(Json::from_str(&settings)
+                            .map_err(|err| format!("{}", err))
+                            .and_then(|settings| BorderSettings::from_json(&settings))
+                            .map_err(|err| MethodErr::invalid_arg(&format!("settings: {}", err))));
+        let mut tree = try!(lock_tree_dbus());
+        let uuid = try!(try!(parse_uuid("view_id", &view_id))
+                        .or_else(|| tree.active_id())
+                        .ok_or(MethodErr::failed(&"No active container")));
+        tree.set_border_settings(uuid, settings)
+            .and(Ok(true))
+            .map_err(|err| MethodErr::failed(&format!("{:?}", err)))
+    }
+
+    fn FocusUrgent() -> success: DBusResult<bool> {
+        let mut tree = try!(lock_tree_dbus());
+        tree.focus_urgent()
+            .and(Ok(true))
+            .map_err(|err| MethodErr::failed(&format!("{:?}", err)))
+    }
+
+    fn UrgentWorkspaces() -> workspaces: DBusResult<String> {
+        let tree = try!(lock_tree_dbus());
+        Ok(format!("{}", tree.urgent_workspaces().to_json()))
+    }
+
+    fn ApplyLayout(w_name: String, template: String) -> success: DBusResult<bool> {
+        let template = try!(Json::from_str(&template)
+                            .map_err(|err| MethodErr::invalid_arg(&format!("template: {}", err))));
+        let mut tree = try!(lock_tree_dbus());
+        tree.apply_template(w_name.as_str(), &template)
+            .and(Ok(true))
+            .map_err(|err| MethodErr::failed(&format!("{:?}", err)))
+    }
+
+    fn Undo() -> success: DBusResult<bool> {
+        let mut tree = try!(lock_tree_dbus());
+        tree.undo()
+            .and(Ok(true))
+            .map_err(|err| MethodErr::failed(&format!("{:?}", err)))
+    }
+
+    fn Redo() -> success: DBusResult<bool> {
+        let mut tree = try!(lock_tree_dbus());
+        tree.redo()
+            .and(Ok(true))
+            .map_err(|err| MethodErr::failed(&format!("{:?}", err)))
+    }
+
     fn Debug() -> success: DBusResult<String> {
         Ok(format!("{}", layout_cmd::tree_as_json()))
     }
@@ -15,6 +15,7 @@ pub use self::session::DBusSession;
 
 mod layout;
 mod screen;
+mod bar;
 
 pub const VERSION: u32 = 1;
 
@@ -28,6 +29,19 @@ lazy_static! {
 }
 
 
+/// Sends the message as a signal to the dbus clients.
+///
+/// Does nothing if the dbus session isn't running.
+pub fn send(message: DBusMessage) {
+    if let Ok(sender) = SENDER.lock() {
+        if let Some(ref sender) = *sender {
+            if let Err(err) = sender.send(message) {
+                warn!("Could not send {:?} to the dbus session", err.0);
+            }
+        }
+    }
+}
+
 pub fn init() {
     let (send, recv) = mpsc::channel();
     let _join = thread::spawn( move || {
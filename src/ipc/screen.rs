@@ -1,9 +1,13 @@
 use dbus::arg::{Array};
+use rustwlc::{Point, Size};
 use dbus::tree::MethodErr;
 use dbus::MessageItem;
+use rustc_serialize::json::Json;
+use std::time::Duration;
 
-use super::utils::{parse_uuid, lock_tree_dbus};
+use super::utils::{parse_output, parse_output_edge, parse_uuid, lock_tree_dbus};
 use super::{DBusFactory, DBusObjPath};
+use ::layout::{set_background, clear_background, show_osd, OsdMessage};
 use ::render::screen_scrape::{write_screen_scrape_lock, read_screen_scrape_lock,
                               scraped_pixels_lock, sync_scrape};
 
@@ -46,19 +50,306 @@ pub fn setup(f: &mut DBusFactory) -> DBusObjPath {
                     let mut args_iter = m.msg.iter_init();
                     let output_id = args_iter.read::<String>()?;
                     let tree = lock_tree_dbus()?;
-                    let uuid = parse_uuid("container_id", &output_id)?
-                        .or_else(|| tree.active_id())
-                        .ok_or(MethodErr::failed(&"No active container"))?;
-                    let size = tree.output_resolution(uuid)
+                    let uuid = parse_output(&tree, "output_id", &output_id)?;
+                    let (physical, _) = tree.output_sizes(uuid)
                         .map_err(|err| {
                             MethodErr::failed(&format!("{:?}", err))
                         })?;
                     Ok(vec![m.msg.method_return()
-                            .append1((size.w, size.h))
+                            .append1((physical.w, physical.h))
                     ])
                 })
                     .outarg::<(u32, u32), _>("success")
                     .inarg::<String, _>("output_id")
             )
+            .add_m(
+                // The resolution divided by the scale of the output,
+                // which is what the layout and the views are sized in.
+                f.method("LogicalResolution", (), |m| {
+                    let mut args_iter = m.msg.iter_init();
+                    let output_id = args_iter.read::<String>()?;
+                    let tree = lock_tree_dbus()?;
+                    let uuid = parse_output(&tree, "output_id", &output_id)?;
+                    let (_, logical) = tree.output_sizes(uuid)
+                        .map_err(|err| {
+                            MethodErr::failed(&format!("{:?}", err))
+                        })?;
+                    Ok(vec![m.msg.method_return()
+                            .append1((logical.w, logical.h))
+                    ])
+                })
+                    .outarg::<(u32, u32), _>("success")
+                    .inarg::<String, _>("output_id")
+            )
+            .add_m(
+                f.method("Scale", (), |m| {
+                    let mut args_iter = m.msg.iter_init();
+                    let output_id = args_iter.read::<String>()?;
+                    let tree = lock_tree_dbus()?;
+                    let uuid = parse_output(&tree, "output_id", &output_id)?;
+                    let scale = tree.output_scale(uuid)
+                        .map_err(|err| {
+                            MethodErr::failed(&format!("{:?}", err))
+                        })?;
+                    Ok(vec![m.msg.method_return().append1(scale)])
+                })
+                    .outarg::<u32, _>("success")
+                    .inarg::<String, _>("output_id")
+            )
+            .add_m(
+                f.method("SetScale", (), |m| {
+                    let mut args_iter = m.msg.iter_init();
+                    let output_id = args_iter.read::<String>()?;
+                    let scale = args_iter.read::<u32>()?;
+                    let mut tree = lock_tree_dbus()?;
+                    let uuid = parse_output(&tree, "output_id", &output_id)?;
+                    tree.set_output_scale(uuid, scale)
+                        .map_err(|err| {
+                            MethodErr::failed(&format!("{:?}", err))
+                        })?;
+                    Ok(vec![m.msg.method_return().append1(true)])
+                })
+                    .outarg::<bool, _>("success")
+                    .inarg::<String, _>("output_id")
+                    .inarg::<u32, _>("scale")
+            )
+            .add_m(
+                f.method("Outputs", (), |m| {
+                    let tree = lock_tree_dbus()?;
+                    Ok(vec![m.msg.method_return()
+                            .append1(tree.outputs_info().to_string())])
+                }).outarg::<String, _>("success")
+            )
+            .add_m(
+                f.method("Info", (), |m| {
+                    let mut args_iter = m.msg.iter_init();
+                    let output_id = args_iter.read::<String>()?;
+                    let tree = lock_tree_dbus()?;
+                    let uuid = parse_output(&tree, "output_id", &output_id)?;
+                    let info = tree.output_info(uuid)
+                        .map_err(|err| {
+                            MethodErr::failed(&format!("{:?}", err))
+                        })?;
+                    Ok(vec![m.msg.method_return().append1(info.to_string())])
+                })
+                    .outarg::<String, _>("success")
+                    .inarg::<String, _>("output_id")
+            )
+            .add_m(
+                f.method("Name", (), |m| {
+                    let mut args_iter = m.msg.iter_init();
+                    let output_id = args_iter.read::<String>()?;
+                    let tree = lock_tree_dbus()?;
+                    let uuid = parse_output(&tree, "output_id", &output_id)?;
+                    let name = tree.output_name(uuid)
+                        .map_err(|err| {
+                            MethodErr::failed(&format!("{:?}", err))
+                        })?;
+                    Ok(vec![m.msg.method_return().append1(name)])
+                })
+                    .outarg::<String, _>("success")
+                    .inarg::<String, _>("output_id")
+            )
+            .add_m(
+                f.method("Position", (), |m| {
+                    let mut args_iter = m.msg.iter_init();
+                    let output_id = args_iter.read::<String>()?;
+                    let tree = lock_tree_dbus()?;
+                    let uuid = parse_output(&tree, "output_id", &output_id)?;
+                    let position = tree.output_position(uuid)
+                        .map_err(|err| {
+                            MethodErr::failed(&format!("{:?}", err))
+                        })?;
+                    Ok(vec![m.msg.method_return().append1((position.x, position.y))])
+                })
+                    .outarg::<(i32, i32), _>("success")
+                    .inarg::<String, _>("output_id")
+            )
+            .add_m(
+                f.method("Workspaces", (), |m| {
+                    let mut args_iter = m.msg.iter_init();
+                    let output_id = args_iter.read::<String>()?;
+                    let tree = lock_tree_dbus()?;
+                    let uuid = parse_output(&tree, "output_id", &output_id)?;
+                    let (active, workspaces) = tree.output_workspaces(uuid)
+                        .map_err(|err| {
+                            MethodErr::failed(&format!("{:?}", err))
+                        })?;
+                    let workspaces = workspaces.into_iter()
+                        .map(MessageItem::Str)
+                        .collect();
+                    Ok(vec![m.msg.method_return()
+                            .append2(active.unwrap_or_default(),
+                                     MessageItem::Array(workspaces, "s".into()))])
+                })
+                    .outarg::<String, _>("active")
+                    .outarg::<Array<String, Vec<String>>, _>("workspaces")
+                    .inarg::<String, _>("output_id")
+            )
+            .add_m(
+                f.method("Focused", (), |m| {
+                    let mut args_iter = m.msg.iter_init();
+                    let output_id = args_iter.read::<String>()?;
+                    let tree = lock_tree_dbus()?;
+                    let uuid = parse_output(&tree, "output_id", &output_id)?;
+                    let focused = tree.output_focused(uuid)
+                        .map_err(|err| {
+                            MethodErr::failed(&format!("{:?}", err))
+                        })?;
+                    Ok(vec![m.msg.method_return().append1(focused)])
+                })
+                    .outarg::<bool, _>("success")
+                    .inarg::<String, _>("output_id")
+            )
+            .add_m(
+                f.method("SetResolution", (), |m| {
+                    let mut args_iter = m.msg.iter_init();
+                    let output_id = args_iter.read::<String>()?;
+                    let w = args_iter.read::<u32>()?;
+                    let h = args_iter.read::<u32>()?;
+                    let mut tree = lock_tree_dbus()?;
+                    let uuid = parse_output(&tree, "output_id", &output_id)?;
+                    tree.set_output_resolution(uuid, Size { w: w, h: h })
+                        .map_err(|err| {
+                            MethodErr::failed(&format!("{:?}", err))
+                        })?;
+                    Ok(vec![m.msg.method_return().append1(true)])
+                })
+                    .outarg::<bool, _>("success")
+                    .inarg::<String, _>("output_id")
+                    .inarg::<u32, _>("width")
+                    .inarg::<u32, _>("height")
+            )
+            .add_m(
+                f.method("SetPosition", (), |m| {
+                    let mut args_iter = m.msg.iter_init();
+                    let output_id = args_iter.read::<String>()?;
+                    let x = args_iter.read::<i32>()?;
+                    let y = args_iter.read::<i32>()?;
+                    let mut tree = lock_tree_dbus()?;
+                    let uuid = parse_output(&tree, "output_id", &output_id)?;
+                    tree.set_output_position(uuid, Point { x: x, y: y })
+                        .map_err(|err| {
+                            MethodErr::failed(&format!("{:?}", err))
+                        })?;
+                    Ok(vec![m.msg.method_return().append1(true)])
+                })
+                    .outarg::<bool, _>("success")
+                    .inarg::<String, _>("output_id")
+                    .inarg::<i32, _>("x")
+                    .inarg::<i32, _>("y")
+            )
+            .add_m(
+                f.method("SetEnabled", (), |m| {
+                    let mut args_iter = m.msg.iter_init();
+                    let output_id = args_iter.read::<String>()?;
+                    let enabled = args_iter.read::<bool>()?;
+                    let mut tree = lock_tree_dbus()?;
+                    let uuid = parse_output(&tree, "output_id", &output_id)?;
+                    tree.set_output_enabled(uuid, enabled)
+                        .map_err(|err| {
+                            MethodErr::failed(&format!("{:?}", err))
+                        })?;
+                    Ok(vec![m.msg.method_return().append1(true)])
+                })
+                    .outarg::<bool, _>("success")
+                    .inarg::<String, _>("output_id")
+                    .inarg::<bool, _>("enabled")
+            )
+            .add_m(
+                f.method("ReserveEdge", (), |m| {
+                    let mut args_iter = m.msg.iter_init();
+                    let output_id = args_iter.read::<String>()?;
+                    let edge = args_iter.read::<String>()?;
+                    let size = args_iter.read::<u32>()?;
+                    let edge = parse_output_edge("edge", &edge)?;
+                    let mut tree = lock_tree_dbus()?;
+                    let uuid = parse_output(&tree, "output_id", &output_id)?;
+                    let id = tree.reserve_edge(uuid, edge, size)
+                        .map_err(|err| {
+                            MethodErr::failed(&format!("{:?}", err))
+                        })?;
+                    Ok(vec![m.msg.method_return().append1(id.to_string())])
+                })
+                    .outarg::<String, _>("reservation_id")
+                    .inarg::<String, _>("output_id")
+                    .inarg::<String, _>("edge")
+                    .inarg::<u32, _>("size")
+            )
+            .add_m(
+                f.method("ReleaseEdge", (), |m| {
+                    let mut args_iter = m.msg.iter_init();
+                    let reservation_id = args_iter.read::<String>()?;
+                    let uuid = parse_uuid("reservation_id", &reservation_id)?
+                        .ok_or(MethodErr::invalid_arg(&"reservation_id: expected a UUID"))?;
+                    let mut tree = lock_tree_dbus()?;
+                    tree.release_reservation(uuid)
+                        .map_err(|err| {
+                            MethodErr::failed(&format!("{:?}", err))
+                        })?;
+                    Ok(vec![m.msg.method_return().append1(true)])
+                })
+                    .outarg::<bool, _>("success")
+                    .inarg::<String, _>("reservation_id")
+            )
+            .add_m(
+                // The target is "default", the name of an output or
+                // "workspace:<name>". The background is JSON, as it is in
+                // the backgrounds category of the registry.
+                f.method("SetBackground", (), |m| {
+                    let mut args_iter = m.msg.iter_init();
+                    let target = args_iter.read::<String>()?;
+                    let background = args_iter.read::<String>()?;
+                    let background = Json::from_str(&background)
+                        .map_err(|err| MethodErr::invalid_arg(
+                            &format!("background: not valid JSON: {:?}", err)))?;
+                    set_background(target, &background)
+                        .map_err(|err| {
+                            MethodErr::invalid_arg(&format!("{:?}", err))
+                        })?;
+                    Ok(vec![m.msg.method_return().append1(true)])
+                })
+                    .outarg::<bool, _>("success")
+                    .inarg::<String, _>("target")
+                    .inarg::<String, _>("background")
+            )
+            .add_m(
+                f.method("ClearBackground", (), |m| {
+                    let mut args_iter = m.msg.iter_init();
+                    let target = args_iter.read::<String>()?;
+                    Ok(vec![m.msg.method_return().append1(clear_background(&target))])
+                })
+                    .outarg::<bool, _>("success")
+                    .inarg::<String, _>("target")
+            )
+            .add_m(
+                // Shows a message on the focused output for a moment.
+                // An empty tag, a negative value and a timeout of 0 mean
+                // no tag, no bar and the configured timeout.
+                f.method("ShowOsd", (), |m| {
+                    let mut args_iter = m.msg.iter_init();
+                    let text = args_iter.read::<String>()?;
+                    let tag = args_iter.read::<String>()?;
+                    let value = args_iter.read::<i32>()?;
+                    let timeout = args_iter.read::<u32>()?;
+                    let mut message = OsdMessage::info(text);
+                    if !tag.is_empty() {
+                        message.tag = Some(tag);
+                    }
+                    if value >= 0 {
+                        message.value = Some(value as u32);
+                    }
+                    if timeout > 0 {
+                        message.timeout = Some(Duration::from_millis(timeout as u64));
+                    }
+                    show_osd(message);
+                    Ok(vec![m.msg.method_return().append1(true)])
+                })
+                    .outarg::<bool, _>("success")
+                    .inarg::<String, _>("text")
+                    .inarg::<String, _>("tag")
+                    .inarg::<i32, _>("value")
+                    .inarg::<u32, _>("timeout_ms")
+            )
     )
 }
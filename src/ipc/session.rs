@@ -14,7 +14,6 @@ use super::dbus_message::DBusMessage;
 /// Contains all of the horrors of the dbus library within.
 /// Way Cooler's IPC is split up into files for different interfaces
 /// which all come together
-#[allow(dead_code)]
 pub struct DBusSession {
     tree: DBusTree,
     connection: Connection,
@@ -32,7 +31,8 @@ impl DBusSession {
 
         let tree = factory.tree()
             .add(super::layout::setup(&mut factory))
-            .add(super::screen::setup(&mut factory));
+            .add(super::screen::setup(&mut factory))
+            .add(super::bar::setup(&mut factory));
 
         //super::keybindings::setup(&mut factory);
 
@@ -49,6 +49,16 @@ impl DBusSession {
 
         for _ in self.tree.run(&self.connection,
                                self.connection.iter(1000)) {
+            self.send_signals();
+        }
+    }
+
+    /// Sends the messages that way-cooler queued up as signals.
+    fn send_signals(&self) {
+        while let Ok(message) = self.receiver.try_recv() {
+            if self.connection.send(message.into_signal()).is_err() {
+                warn!("Could not send dbus signal");
+            }
         }
     }
 }
@@ -7,7 +7,7 @@ use dbus::tree::MethodErr;
 
 use super::{DBusResult};
 
-use layout::{Direction, Layout, Tree, lock_tree};
+use layout::{Direction, Edge, Layout, Tree, WorkspaceScope, lock_tree};
 
 use rustwlc::{ResizeEdge, RESIZE_TOP, RESIZE_BOTTOM,
               RESIZE_LEFT, RESIZE_RIGHT};
@@ -33,6 +33,19 @@ pub fn parse_uuid(arg: &'static str, text: &str) -> DBusResult<Option<Uuid>> {
     }
 }
 
+/// Finds the output the string refers to, either by its UUID or by its name
+/// (e.g "DP-1"). The empty string refers to the active output.
+pub fn parse_output(tree: &Tree, arg: &'static str, text: &str) -> DBusResult<Uuid> {
+    if text == "" {
+        return tree.active_output_id()
+            .ok_or(MethodErr::failed(&"No active output"))
+    }
+    if let Some(id) = tree.output_by_name(text) {
+        return Ok(id)
+    }
+    parse_uuid(arg, text).map(|id| id.expect("Text was not empty"))
+}
+
 /// Parses a `Direction` from a string, returning `MethodErr::invalid_arg`
 /// if the string is invalid.
 pub fn parse_direction(arg: &'static str, text: &str) -> DBusResult<Direction> {
@@ -70,3 +83,22 @@ pub fn parse_edge(dir: &str) -> DBusResult<ResizeEdge> {
     result
 }
 
+/// Parses a `WorkspaceScope` from a string, returning `MethodErr::invalid_arg`
+/// if the string is invalid.
+pub fn parse_workspace_scope(arg: &'static str, text: &str) -> DBusResult<WorkspaceScope> {
+    match text.to_lowercase().as_str() {
+        "global" | "" => Ok(WorkspaceScope::Global),
+        "output" => Ok(WorkspaceScope::Output),
+        _ => Err(MethodErr::invalid_arg(
+            &format!("{}: {} is not a valid workspace scope. \
+                      May be either 'global' or 'output'", arg, text)))
+    }
+}
+
+/// Parses an `Edge` of an output from a string, returning
+/// `MethodErr::invalid_arg` if the string is invalid.
+pub fn parse_output_edge(arg: &'static str, text: &str) -> DBusResult<Edge> {
+    Edge::from_name(text).ok_or_else(|| MethodErr::invalid_arg(
+        &format!("{}: {} is not a valid edge. \
+                  May be one of 'top', 'bottom', 'left', 'right'.", arg, text)))
+}
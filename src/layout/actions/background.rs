@@ -1,11 +1,110 @@
 //! A collection of methods that modify the background of the outputs.
+//!
+//! Way Cooler paints the backgrounds itself, as configured in the
+//! backgrounds category of the registry. Each key is what the background is
+//! for: "workspace:<name>" for a workspace, the name of an output, or
+//! "default". The background of the workspace shown on an output is used,
+//! otherwise that of the output, otherwise the default.
+//!
+//! Backgrounds are only looked up again after they are marked outdated with
+//! `mark_backgrounds_outdated`, e.g when a background is set or another
+//! workspace is shown, until then rendering doesn't look at the tree.
+//!
+//! A program can also be the background, by having the "Background" class.
 use super::super::{Container, ContainerType, LayoutTree, TreeError};
 use super::super::commands::CommandResult;
 
+use petgraph::graph::NodeIndex;
+use rustc_serialize::json::Json;
 use uuid::Uuid;
-use rustwlc::{WlcView};
+use rustwlc::{Geometry, Point, WlcOutput, WlcView};
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Mutex;
+
+use ::registry;
+use ::render::{Background, BackgroundStyle, Renderable, logical_resolution};
+
+lazy_static! {
+    /// Backgrounds set at runtime, which take the place of the configured ones.
+    static ref OVERRIDES: Mutex<BTreeMap<String, BackgroundStyle>> = Mutex::new(BTreeMap::new());
+    /// The outputs whose backgrounds are the ones they should have.
+    static ref UP_TO_DATE: Mutex<Vec<WlcOutput>> = Mutex::new(Vec::new());
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BackgroundErr {
+    /// The value didn't describe a background.
+    Invalid(Json)
+}
+
+/// Sets the background for the workspace ("workspace:<name>"),
+/// output or "default", in place of the configured one.
+pub fn set_background(target: String, background: &Json) -> Result<(), BackgroundErr> {
+    let style = try!(BackgroundStyle::from_json(background)
+                     .ok_or_else(|| BackgroundErr::Invalid(background.clone())));
+    OVERRIDES.lock().expect("Could not lock the backgrounds").insert(target, style);
+    mark_backgrounds_outdated();
+    Ok(())
+}
+
+/// Goes back to the configured background for the target,
+/// returning whether a background had been set for it.
+pub fn clear_background(target: &str) -> bool {
+    let removed = OVERRIDES.lock().expect("Could not lock the backgrounds")
+        .remove(target).is_some();
+    if removed {
+        mark_backgrounds_outdated();
+    }
+    removed
+}
+
+/// Makes every output look up its background again when it is next drawn.
+pub fn mark_backgrounds_outdated() {
+    let outputs: Vec<WlcOutput> = match UP_TO_DATE.lock() {
+        Ok(mut up_to_date) => up_to_date.drain(..).collect(),
+        Err(_) => return
+    };
+    for output in outputs {
+        output.schedule_render();
+    }
+}
+
+/// Determines if the background of the output has to be looked up again.
+pub fn background_outdated(output: WlcOutput) -> bool {
+    UP_TO_DATE.lock()
+        .map(|up_to_date| !up_to_date.contains(&output))
+        .unwrap_or(true)
+}
+
+/// Notes that the output has the background it should.
+fn mark_background_up_to_date(output: WlcOutput) {
+    if let Ok(mut up_to_date) = UP_TO_DATE.lock() {
+        if !up_to_date.contains(&output) {
+            up_to_date.push(output);
+        }
+    }
+}
+
+/// Gets the background for the target, set at runtime or in the registry.
+fn background_for(target: &str) -> Option<BackgroundStyle> {
+    if let Some(style) = OVERRIDES.lock().ok().and_then(|set| set.get(target).cloned()) {
+        return Some(style)
+    }
+    let lock = registry::clients_read();
+    let client = lock.client(Uuid::nil()).unwrap();
+    let handle = registry::ReadHandle::new(&client);
+    handle.read("backgrounds".into()).ok()
+        .and_then(|backgrounds| backgrounds.get(target).cloned())
+        .and_then(|background| {
+            let style = BackgroundStyle::from_json(&background);
+            if style.is_none() {
+                warn!("Ignoring the background of {}, {} is not a background",
+                      target, background);
+            }
+            style
+        })
+}
 
 impl LayoutTree {
     /// Attempts to attach the `bg` to the `outputs`.
@@ -43,3 +142,100 @@ impl LayoutTree {
         Ok(())
     }
 }
+
+impl LayoutTree {
+    /// Gets the background of the output: that of the workspace shown on it,
+    /// of the output itself, or the default one.
+    pub fn background_style(&self, output_ix: NodeIndex) -> Option<BackgroundStyle> {
+        self.output_active_workspace(output_ix)
+            .and_then(|name| background_for(format!("workspace:{}", name).as_str()))
+            .or_else(|| background_for(self.tree[output_ix].name().as_str()))
+            .or_else(|| background_for("default"))
+    }
+
+    /// Paints the background of the output again if it changed,
+    /// or removes it if the output has none.
+    pub fn update_background(&self, backgrounds: &mut Vec<Background>, output: WlcOutput) {
+        // Before reading anything, so changes made meanwhile aren't lost
+        mark_background_up_to_date(output);
+        let output_ix = self.tree.children_of(self.tree.root_ix()).into_iter()
+            .find(|output_ix| match self.tree[*output_ix] {
+                Container::Output { handle, .. } => handle == output,
+                _ => false
+            });
+        let style = output_ix.and_then(|output_ix| self.background_style(output_ix));
+        let (style, size) = match (style, logical_resolution(output)) {
+            (Some(style), Some(size)) => (style, size),
+            _ => {
+                backgrounds.retain(|background| background.get_output() != output);
+                return
+            }
+        };
+        let geometry = Geometry {
+            origin: Point { x: 0, y: 0 },
+            size: size
+        };
+        let index = backgrounds.iter().position(|background| background.get_output() == output);
+        if let Some(index) = index {
+            let background = &backgrounds[index];
+            if background.style == style && background.get_geometry() == geometry {
+                return
+            }
+        }
+        let old = index.map(|index| backgrounds.remove(index));
+        let background = match old {
+            Some(background) => background.reallocate_buffer(geometry),
+            None => Background::new(geometry, output)
+        };
+        let background = background.and_then(|mut background| {
+            background.set_style(style);
+            background.draw()
+        });
+        if let Some(background) = background {
+            backgrounds.push(background);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustc_serialize::json::Json;
+    use ::layout::core::tree::tests::basic_tree;
+    use ::render::{BackgroundStyle, Color};
+    use super::{BackgroundErr, clear_background, set_background};
+
+    #[test]
+    fn choosing_backgrounds() {
+        let tree = basic_tree();
+        let output_ix = tree.tree.children_of(tree.tree.root_ix())[0];
+        let shown = tree.output_active_workspace(output_ix).unwrap();
+        let workspace = format!("workspace:{}", shown);
+        assert_eq!(tree.background_style(output_ix), None);
+        set_background("default".into(), &Json::U64(0x5E4055)).unwrap();
+        assert_eq!(tree.background_style(output_ix),
+                   Some(BackgroundStyle::Color(Color::from(0x5E4055))));
+        let gradient = Json::from_str(r#"{"from": 0, "to": 16777215}"#).unwrap();
+        set_background(workspace.clone(), &gradient).unwrap();
+        assert_eq!(tree.background_style(output_ix),
+                   Some(BackgroundStyle::Gradient {
+                       from: Color::from(0),
+                       to: Color::from(0xffffff),
+                       vertical: true
+                   }));
+        assert_eq!(set_background(workspace.clone(), &Json::Boolean(true)),
+                   Err(BackgroundErr::Invalid(Json::Boolean(true))));
+        assert!(clear_background(workspace.as_str()));
+        assert!(clear_background("default"));
+        assert!(!clear_background("default"));
+        assert_eq!(tree.background_style(output_ix), None);
+
+        let image = Json::from_str(r#"{"image": "/bg.png", "mode": "tile"}"#).unwrap();
+        let filled = Json::from_str(r#"{"image": "/bg.png"}"#).unwrap();
+        assert!(BackgroundStyle::from_json(&image).is_some());
+        assert!(BackgroundStyle::from_json(&image) != BackgroundStyle::from_json(&filled));
+        assert_eq!(BackgroundStyle::from_json(&filled),
+                   BackgroundStyle::from_json(&Json::String("/bg.png".into())));
+        let bad_mode = Json::from_str(r#"{"image": "/bg.png", "mode": "stretch"}"#).unwrap();
+        assert_eq!(BackgroundStyle::from_json(&bad_mode), None);
+    }
+}
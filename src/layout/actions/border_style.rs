@@ -0,0 +1,154 @@
+//! Gives views borders in the style set for them.
+//!
+//! See `core::borders::style` for the styles, and where they are set.
+
+use petgraph::graph::NodeIndex;
+use rustwlc::WlcView;
+use uuid::Uuid;
+
+use super::super::LayoutTree;
+use super::super::commands::CommandResult;
+use super::super::core::borders::Borders;
+use super::super::core::borders::style::{self, BorderSettings, BorderStyle};
+use super::super::core::container::{Container, ContainerType};
+use super::super::core::rules;
+use super::super::core::tree::TreeError;
+use ::render::Renderable;
+use ::wayland::server_decoration;
+
+/// Makes the borders of a view that is being added to the tree.
+///
+/// Views that draw their own decorations go without, unless their rules
+/// set a border style for them.
+pub fn new_view_borders(view: WlcView) -> Option<Borders> {
+    let style = BorderSettings::from_options(&rules::options_for(view)).ok()
+        .and_then(|settings| settings.style);
+    if BorderStyle::effective(style, server_decoration::client_decorated(view))
+        == BorderStyle::None {
+        return None
+    }
+    let geometry = view.get_geometry()
+        .expect("View had no geometry");
+    Borders::new(geometry, view.get_output())
+        .map(|mut borders| {
+            borders.title = Container::get_title(view);
+            borders
+        })
+}
+
+impl LayoutTree {
+    /// Sets the style and colors of the borders of the view behind the id,
+    /// instead of what its rules say. What is unset is left to the rules.
+    pub fn set_border_settings(&mut self, id: Uuid, settings: BorderSettings)
+                               -> CommandResult {
+        let node_ix = try!(self.tree.lookup_id(id).ok_or(TreeError::NodeNotFound(id)));
+        if self.tree[node_ix].get_type() != ContainerType::View {
+            return Err(TreeError::UuidWrongType(id, vec!(ContainerType::View)))
+        }
+        try!(self.tree[node_ix].set_border_settings(settings)
+             .map_err(|err| TreeError::Container(err)));
+        let workspace_ix = try!(self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)
+                                .map_err(|err| TreeError::PetGraph(err)));
+        self.layout(workspace_ix);
+        Ok(())
+    }
+
+    /// Lays out the workspace of the view again, after it changed whether
+    /// it draws its own decorations.
+    pub fn update_border_style(&mut self, view: WlcView) -> CommandResult {
+        let node_ix = try!(self.tree.lookup_view(view).ok_or(TreeError::ViewNotFound(view)));
+        let workspace_ix = try!(self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)
+                                .map_err(|err| TreeError::PetGraph(err)));
+        self.layout(workspace_ix);
+        Ok(())
+    }
+
+    /// Gets the style the borders of the view are drawn in.
+    ///
+    /// This is no borders for views that draw their own decorations, unless
+    /// a style is set for them. When `lone_borderless` is set, tiled views
+    /// go without borders too, see `lone_view_borderless`.
+    pub fn border_style_of(&self, node_ix: NodeIndex, lone_borderless: bool) -> BorderStyle {
+        let container = &self.tree[node_ix];
+        let client_decorated = match *container {
+            Container::View { handle, .. } => server_decoration::client_decorated(handle),
+            _ => false
+        };
+        let style = BorderStyle::effective(container.border_settings().style, client_decorated);
+        if style != BorderStyle::None && !container.floating() && lone_borderless {
+            return BorderStyle::None
+        }
+        style
+    }
+
+    /// Determines if smart borders are on and the workspace (or the workspace
+    /// the node is on) has only one tiled view, which then goes without borders.
+    ///
+    /// This is meant to be read once for each workspace that is laid out.
+    pub fn lone_view_borderless(&self, node_ix: NodeIndex) -> bool {
+        if !style::smart_borders() {
+            return false
+        }
+        let workspace_ix = if self.tree[node_ix].get_type() == ContainerType::Workspace {
+            node_ix
+        } else {
+            match self.tree.ancestor_of_type(node_ix, ContainerType::Workspace) {
+                Ok(workspace_ix) => workspace_ix,
+                Err(_) => return false
+            }
+        };
+        self.tree.all_descendants_of(workspace_ix).into_iter()
+            .filter(|ix| {
+                let container = &self.tree[*ix];
+                container.get_type() == ContainerType::View
+                    && !container.floating() && !container.minimized()
+            })
+            .count() == 1
+    }
+
+    /// Gives the view borders in its border style, or takes them away.
+    ///
+    /// This needs to be done before the view is placed within its borders.
+    pub fn apply_border_style(&mut self, node_ix: NodeIndex, lone_borderless: bool) {
+        let style = self.border_style_of(node_ix, lone_borderless);
+        if let Container::View { handle, ref mut borders, .. } = self.tree[node_ix] {
+            if style == BorderStyle::None {
+                *borders = None;
+                return
+            }
+            let has_title = borders.as_ref().map(Borders::has_title);
+            if has_title != Some(style.has_title()) {
+                if let Some(geometry) = handle.get_geometry() {
+                    *borders = Borders::with_title(geometry, handle.get_output(),
+                                                   style.has_title());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::layout::core::tree::tests::basic_tree;
+    use ::layout::core::container::ContainerType;
+    use ::layout::core::borders::style::{BorderSettings, BorderStyle};
+
+    #[test]
+    fn setting_border_styles() {
+        let mut tree = basic_tree();
+        let view_ix = tree.active_ix_of(ContainerType::View).unwrap();
+        let id = tree.tree[view_ix].get_id();
+        assert_eq!(tree.border_style_of(view_ix, false), BorderStyle::Normal);
+        let settings = BorderSettings {
+            style: Some(BorderStyle::Pixel),
+            color: Some(0xff0000.into()),
+            .. BorderSettings::default()
+        };
+        tree.set_border_settings(id, settings).unwrap();
+        assert_eq!(tree.border_style_of(view_ix, false), BorderStyle::Pixel);
+        assert_eq!(tree.tree[view_ix].border_settings().color, Some(0xff0000.into()));
+        let workspace_ix = tree.active_ix_of(ContainerType::Workspace).unwrap();
+        let workspace_id = tree.tree[workspace_ix].get_id();
+        assert!(tree.set_border_settings(workspace_id, settings).is_err());
+    }
+}
@@ -3,10 +3,27 @@ use super::super::{LayoutTree, TreeError};
 use super::super::core::Direction;
 use super::super::core::container::{Container, ContainerType, Layout};
 
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use nix::libc::pid_t;
 use petgraph::graph::NodeIndex;
+use rustc_serialize::json::Json;
 use rustwlc::WlcView;
 use uuid::Uuid;
 
+use super::super::core::rules;
+use ::registry;
+
+/// How long after the user last used an application its new windows
+/// may still take the focus, with the smart focus policy.
+const RECENT_INTERACTION_SECS: u64 = 3;
+
+lazy_static! {
+    /// The process the user last typed into or clicked on, and when.
+    static ref LAST_INTERACTION: Mutex<Option<(pid_t, Instant)>> = Mutex::new(None);
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum FocusError {
     /// Reached a container where we can keep climbing the tree no longer.
@@ -21,7 +38,165 @@ pub enum FocusError {
     BlockedByFullscreen(Uuid, Uuid)
 }
 
+/// Whether a view gets the focus when it asks for it,
+/// by being made or by asking to be activated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusPolicy {
+    /// The view is always focused.
+    Always,
+    /// The view is only focused if the user recently used the application
+    /// it belongs to, otherwise it is marked as urgent.
+    Smart,
+    /// The view is never focused, but it is marked as urgent.
+    UrgentOnly,
+    /// The view is never focused.
+    Never
+}
+
+impl FocusPolicy {
+    /// Parses a policy from its name in the configuration.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "always" => Some(FocusPolicy::Always),
+            "smart" => Some(FocusPolicy::Smart),
+            "urgent" => Some(FocusPolicy::UrgentOnly),
+            "never" => Some(FocusPolicy::Never),
+            _ => None
+        }
+    }
+
+    /// Gets the policy for the view, from the `focus_on_activation` option of
+    /// the rules that match it, or else of the windows category.
+    ///
+    /// Defaults to always focusing the view.
+    pub fn for_view(view: WlcView) -> Self {
+        let policy = |value: &Json| value.as_string().and_then(|name| {
+            let policy = FocusPolicy::from_name(name);
+            if policy.is_none() {
+                warn!("Unknown focus_on_activation policy {:?}", name);
+            }
+            policy
+        });
+        if let Some(policy) = rules::options_for(view).get("focus_on_activation")
+            .and_then(|value| policy(value)) {
+            return policy
+        }
+        let lock = registry::clients_read();
+        let client = lock.client(Uuid::nil()).unwrap();
+        let handle = registry::ReadHandle::new(&client);
+        handle.read("windows".into()).ok()
+            .and_then(|windows| windows.get("focus_on_activation")
+                      .and_then(|value| policy(value)))
+            .unwrap_or(FocusPolicy::Always)
+    }
+}
+
+/// Remembers that the user just used the application of the view,
+/// by typing into it or clicking on it.
+pub fn note_interaction(view: WlcView) {
+    if view.is_root() {
+        return
+    }
+    if let Ok(mut last) = LAST_INTERACTION.lock() {
+        *last = Some((view.get_pid(), Instant::now()));
+    }
+}
+
+/// Determines if the user recently used the application of the view,
+/// or the application of its parent view.
+fn recently_interacted(view: WlcView) -> bool {
+    let last = match LAST_INTERACTION.lock() {
+        Ok(last) => *last,
+        Err(_) => return false
+    };
+    match last {
+        Some((pid, time)) => {
+            let parent = view.get_parent();
+            time.elapsed() < Duration::from_secs(RECENT_INTERACTION_SECS)
+                && (view.get_pid() == pid
+                    || (!parent.is_root() && parent.get_pid() == pid))
+        },
+        None => false
+    }
+}
+
 impl LayoutTree {
+    /// Handles the view asking for focus, by being made or by asking to be
+    /// activated, according to the focus policy for the view.
+    ///
+    /// If the view is not allowed to take the focus, the previously active
+    /// container (if any) is focused again, as adding a view focuses it.
+    pub fn focus_requested(&mut self, view: WlcView, previous: Option<Uuid>)
+                           -> CommandResult {
+        let id = try!(self.lookup_view(view)).get_id();
+        let policy = FocusPolicy::for_view(view);
+        let allowed = match policy {
+            FocusPolicy::Always => true,
+            FocusPolicy::Smart => recently_interacted(view),
+            FocusPolicy::UrgentOnly | FocusPolicy::Never => false
+        };
+        if allowed {
+            return match self.set_active_view(view) {
+                // If blocked by fullscreen, we don't focus on purpose
+                Err(TreeError::Focus(FocusError::BlockedByFullscreen(_, _))) => Ok(()),
+                result => result
+            }
+        }
+        debug!("Not focusing on {:?}, focus policy is {:?}", view, policy);
+        if let Some(previous_id) = previous {
+            if previous_id != id && self.tree.lookup_id(previous_id).is_some() {
+                match self.set_active_container(previous_id) {
+                    Ok(_) | Err(TreeError::Focus(FocusError::BlockedByFullscreen(_, _))) => {},
+                    Err(err) => return Err(err)
+                }
+            }
+        }
+        if policy != FocusPolicy::Never {
+            try!(self.set_urgent(id, true));
+        }
+        Ok(())
+    }
+
+    /// Focuses on the view behind the id, wherever it is: if it's on a
+    /// workspace that isn't shown that workspace is switched to, and if it's
+    /// minimized it is restored.
+    pub fn focus_anywhere(&mut self, id: Uuid) -> CommandResult {
+        let node_ix = try!(self.tree.lookup_id(id).ok_or(TreeError::NodeNotFound(id)));
+        if self.tree[node_ix].get_type() != ContainerType::View {
+            return Err(TreeError::Focus(FocusError::NotAView(id)))
+        }
+        if self.tree[node_ix].minimized() {
+            return self.restore_view(id)
+        }
+        let workspace_ix = try!(self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)
+                                .map_err(|err| TreeError::PetGraph(err)));
+        if !self.workspace_visible(workspace_ix) {
+            if let Some(old_worksp_ix) = self.switching_from() {
+                self.switch_between_workspaces(old_worksp_ix, workspace_ix);
+            }
+        }
+        try!(self.set_active_node(node_ix));
+        self.layout(workspace_ix);
+        Ok(())
+    }
+
+    /// Gets the views, with the most recently focused ones first.
+    ///
+    /// Views that were never focused come last, in the order they are in the tree.
+    pub fn views_by_recent_focus(&self) -> Vec<NodeIndex> {
+        let mut views: Vec<NodeIndex> = self.focus_order.iter()
+            .filter_map(|id| self.tree.lookup_id(*id))
+            .filter(|ix| self.tree[*ix].get_type() == ContainerType::View)
+            .collect();
+        let never_focused: Vec<NodeIndex> = self.tree.all_descendants_of(self.tree.root_ix())
+            .into_iter()
+            .filter(|ix| self.tree[*ix].get_type() == ContainerType::View)
+            .filter(|ix| !views.contains(ix))
+            .collect();
+        views.extend(never_focused);
+        views
+    }
+
     /// Focuses on the container by the uuid, if it points to a View.
     /// Otherwise, an error is returned.
     pub fn focus_on(&mut self, uuid: Uuid) -> CommandResult {
@@ -39,6 +214,14 @@ impl LayoutTree {
             },
             _ => return Err(TreeError::Focus(FocusError::NotAView(uuid)))
         }
+        self.focus_order.retain(|id| *id != uuid);
+        self.focus_order.insert(0, uuid);
+        // It has the attention it wanted
+        if self.tree[node_ix].urgent() {
+            self.tree[node_ix].set_urgent(false)
+                .expect("Could not clear urgent on a view");
+            self.urgent_changed();
+        }
         if !self.tree[node_ix].floating() {
             self.tree.set_ancestor_paths_active(node_ix);
         }
@@ -89,7 +272,9 @@ impl LayoutTree {
                     (Layout::Horizontal, Direction::Right) |
                     (Layout::Vertical, Direction::Up) |
                     (Layout::Vertical, Direction::Down) => {
-                        let siblings = self.tree.children_of(parent_ix);
+                        let siblings: Vec<_> = self.tree.children_of(parent_ix).into_iter()
+                            .filter(|sibling_ix| !self.tree[*sibling_ix].minimized())
+                            .collect();
                         let cur_index = siblings.iter().position(|node| {
                             *node == node_ix
                         }).expect("Could not find self in parent");
@@ -110,7 +295,7 @@ impl LayoutTree {
                                     ContainerType::Container => {
                                         let path_ix = self.tree.follow_path(new_active_ix);
                                         // If the path wasn't complete, find the first view and focus on that
-                                        let node_ix = try!(self.tree.descendant_of_type(path_ix, ContainerType::View)
+                                        let node_ix = try!(self.tree.focusable_descendant_of_type(path_ix, ContainerType::View)
                                                            .map_err(|err| TreeError::PetGraph(err)));
                                         let parent_ix = try!(self.tree.parent_of(node_ix)
                                                              .map_err(|err| TreeError::PetGraph(err)));
@@ -218,8 +403,8 @@ impl LayoutTree {
         let root_c_children = self.tree.grounded_children(container_ix);
         if root_c_children.len() > 0 {
             // Only searches first child of root container, can't be floating view.
-            let new_active_ix = self.tree.descendant_of_type(root_c_children[0],
-                                                                ContainerType::View)
+            let new_active_ix = self.tree.focusable_descendant_of_type(root_c_children[0],
+                                                                       ContainerType::View)
                 .unwrap_or(root_c_children[0]);
             let id = self.tree[new_active_ix].get_id();
             self.set_active_container(id)
@@ -239,8 +424,8 @@ impl LayoutTree {
         } else {
             let floating_children = self.tree.floating_children(container_ix);
             for child_ix in floating_children {
-                if let Ok(view_ix) = self.tree.descendant_of_type(child_ix,
-                                                                    ContainerType::View) {
+                if let Ok(view_ix) = self.tree.focusable_descendant_of_type(child_ix,
+                                                                            ContainerType::View) {
                     match self.tree[view_ix] {
                         Container::View { handle, id, .. } => {
                             debug!("Floating view found, focusing on {:#?}", handle);
@@ -313,8 +498,18 @@ impl LayoutTree {
 #[cfg(test)]
 mod tests {
     use super::super::super::core::tree::tests::basic_tree;
+    use super::FocusPolicy;
     use rustwlc::*;
 
+    #[test]
+    fn focus_policy_names() {
+        assert_eq!(FocusPolicy::from_name("always"), Some(FocusPolicy::Always));
+        assert_eq!(FocusPolicy::from_name("smart"), Some(FocusPolicy::Smart));
+        assert_eq!(FocusPolicy::from_name("urgent"), Some(FocusPolicy::UrgentOnly));
+        assert_eq!(FocusPolicy::from_name("never"), Some(FocusPolicy::Never));
+        assert_eq!(FocusPolicy::from_name("sometimes"), None);
+    }
+
     /// Tests the new algorithm, the one that i3 uses, to determine which
     /// sibling to focus on when the active one is closed.
     #[test]
@@ -0,0 +1,273 @@
+//! Hint mode, for getting to a view without using the mouse.
+//!
+//! Every visible view gets a short label drawn on top of it. Typing the
+//! label of a view focuses on it, swaps the active view with it, or moves
+//! the active view next to it. Escape leaves hint mode without doing anything.
+
+use petgraph::graph::NodeIndex;
+use rustwlc::{Point, WlcOutput};
+use uuid::Uuid;
+
+use super::super::{LayoutTree, TreeError};
+use super::super::commands::CommandResult;
+use super::super::core::container::{ContainerType, Handle};
+use super::super::core::ShiftDirection;
+use super::focus::FocusError;
+use ::render::{Color, Label, Renderable};
+
+/// The letters labels are made of, the easiest to reach come first.
+pub const HINT_ALPHABET: &'static str = "asdfghjkl";
+
+/// The size of the font of the labels, in pixels.
+const HINT_FONT_SIZE: u32 = 24;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum HintsErr {
+    /// There were no visible views to label.
+    NoViews,
+    /// The view behind the UUID is floating, so it can't be swapped or moved next to.
+    Floating(Uuid)
+}
+
+/// What is done to the view whose label was typed.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum HintAction {
+    /// Focuses on the view
+    Focus,
+    /// Swaps the active view with the view
+    Swap,
+    /// Moves the active view next to the view
+    Move
+}
+
+/// What happened after typing a letter in hint mode.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum HintInput {
+    /// The letters typed so far start some labels, keep typing.
+    Pending,
+    /// The letters typed are the label of the view behind the UUID.
+    Chosen(Uuid),
+    /// The letters typed don't start any label.
+    NoMatch
+}
+
+/// A label, and the view it is drawn on.
+#[derive(Debug)]
+struct Hint {
+    text: String,
+    id: Uuid,
+    label: Option<Label>
+}
+
+/// The state of hint mode, while it is active.
+#[derive(Debug)]
+pub struct Hints {
+    action: HintAction,
+    typed: String,
+    hints: Vec<Hint>
+}
+
+/// Makes a label for each of the views, all of the same length so that
+/// no label is the start of another one.
+pub fn hint_labels(count: usize, alphabet: &str) -> Vec<String> {
+    let letters: Vec<char> = alphabet.chars().collect();
+    if count == 0 || letters.is_empty() {
+        return vec![]
+    }
+    let mut length = 1;
+    while letters.len().pow(length as u32) < count {
+        length += 1;
+    }
+    (0..count).map(|mut index| {
+        let mut label = vec![letters[0]; length];
+        for slot in label.iter_mut().rev() {
+            *slot = letters[index % letters.len()];
+            index /= letters.len();
+        }
+        label.into_iter().collect()
+    }).collect()
+}
+
+impl Hints {
+    /// Gets what is done to the view that is chosen.
+    pub fn action(&self) -> HintAction {
+        self.action
+    }
+
+    /// Types a letter of a label.
+    ///
+    /// Labels that don't start with the letters typed so far are no
+    /// longer drawn.
+    pub fn type_char(&mut self, letter: char) -> HintInput {
+        self.typed.push(letter);
+        let typed = self.typed.as_str();
+        self.hints.retain(|hint| hint.text.starts_with(typed));
+        match self.hints.iter().find(|hint| hint.text == typed) {
+            Some(hint) => HintInput::Chosen(hint.id),
+            None if self.hints.is_empty() => HintInput::NoMatch,
+            None => HintInput::Pending
+        }
+    }
+
+    /// Renders the labels that are on the output.
+    pub fn render(&mut self, output: WlcOutput) {
+        for hint in &mut self.hints {
+            if let Some(ref mut label) = hint.label {
+                if label.get_output() == output {
+                    label.render();
+                }
+            }
+        }
+    }
+}
+
+impl LayoutTree {
+    /// Labels every visible view, to start hint mode.
+    pub fn start_hints(&self, action: HintAction) -> Result<Hints, TreeError> {
+        let views: Vec<NodeIndex> = self.tree.all_descendants_of(self.tree.root_ix())
+            .into_iter()
+            .filter(|ix| self.tree[*ix].get_type() == ContainerType::View)
+            .filter(|ix| !self.tree[*ix].minimized())
+            .filter(|ix| self.tree.ancestor_of_type(*ix, ContainerType::Workspace).ok()
+                    .map_or(false, |workspace_ix| self.workspace_visible(workspace_ix)))
+            .collect();
+        if views.is_empty() {
+            return Err(TreeError::Hints(HintsErr::NoViews))
+        }
+        let labels = hint_labels(views.len(), HINT_ALPHABET);
+        let hints = views.into_iter().zip(labels.into_iter()).map(|(view_ix, text)| {
+            let label = match self.tree[view_ix].get_handle() {
+                Some(Handle::View(view)) => view.get_geometry().and_then(|geometry| {
+                    let center = Point {
+                        x: geometry.origin.x + geometry.size.w as i32 / 2,
+                        y: geometry.origin.y + geometry.size.h as i32 / 2
+                    };
+                    Label::centered(&text.to_uppercase(), center,
+                                    HINT_FONT_SIZE, view.get_output())
+                }),
+                _ => None
+            }.and_then(|mut label| {
+                label.background_color = Color::solid_color(0xf0, 0xc6, 0x74);
+                label.font_color = Color::solid_color(0, 0, 0);
+                label.draw()
+            });
+            Hint {
+                text: text,
+                id: self.tree[view_ix].get_id(),
+                label: label
+            }
+        }).collect();
+        Ok(Hints {
+            action: action,
+            typed: String::new(),
+            hints: hints
+        })
+    }
+
+    /// Does the action of hint mode to the view behind the id,
+    /// after its label was typed.
+    pub fn hint_chosen(&mut self, action: HintAction, id: Uuid) -> CommandResult {
+        let node_ix = try!(self.tree.lookup_id(id).ok_or(TreeError::NodeNotFound(id)));
+        let active_ix = match action {
+            HintAction::Focus => None,
+            HintAction::Swap | HintAction::Move => {
+                match self.active_ix_of(ContainerType::View) {
+                    Some(active_ix) if active_ix != node_ix => Some(active_ix),
+                    // Nothing to swap or move, just focus
+                    _ => None
+                }
+            }
+        };
+        if let Some(active_ix) = active_ix {
+            for ix in &[active_ix, node_ix] {
+                if self.tree[*ix].floating() {
+                    let id = self.tree[*ix].get_id();
+                    return Err(TreeError::Hints(HintsErr::Floating(id)))
+                }
+            }
+            match action {
+                HintAction::Swap => try!(self.swap_views(active_ix, node_ix)),
+                _ => try!(self.move_view_next_to(active_ix, node_ix))
+            }
+            let root_ix = self.tree.root_ix();
+            self.layout(root_ix);
+            self.validate();
+            return match self.set_active_node(active_ix) {
+                Err(TreeError::Focus(FocusError::BlockedByFullscreen(_, _))) => Ok(()),
+                result => result
+            }
+        }
+        match self.set_active_node(node_ix) {
+            Err(TreeError::Focus(FocusError::BlockedByFullscreen(_, _))) => Ok(()),
+            result => result
+        }
+    }
+
+    /// Swaps where two tiled views are in the tree.
+    fn swap_views(&mut self, first_ix: NodeIndex, second_ix: NodeIndex) -> CommandResult {
+        let first_parent = try!(self.tree.parent_of(first_ix)
+                                .map_err(|err| TreeError::PetGraph(err)));
+        let second_parent = try!(self.tree.parent_of(second_ix)
+                                 .map_err(|err| TreeError::PetGraph(err)));
+        if first_parent == second_parent {
+            return self.tree.swap_node_order(first_ix, second_ix)
+                .map_err(|err| TreeError::PetGraph(err))
+        }
+        let first_pos = **self.tree.get_edge_weight_between(first_parent, first_ix)
+            .expect("View was not linked to its parent");
+        let second_pos = **self.tree.get_edge_weight_between(second_parent, second_ix)
+            .expect("View was not linked to its parent");
+        try!(self.tree.move_into(first_ix, second_parent)
+             .map_err(|err| TreeError::PetGraph(err)));
+        self.tree.set_child_pos(first_ix, second_pos);
+        try!(self.tree.move_into(second_ix, first_parent)
+             .map_err(|err| TreeError::PetGraph(err)));
+        self.tree.set_child_pos(second_ix, first_pos);
+        self.bind_views_to_output(first_ix);
+        self.bind_views_to_output(second_ix);
+        Ok(())
+    }
+
+    /// Moves the tiled view so that it's right after the target view.
+    fn move_view_next_to(&mut self, node_ix: NodeIndex, target_ix: NodeIndex) -> CommandResult {
+        let old_parent_ix = try!(self.tree.parent_of(node_ix)
+                                 .map_err(|err| TreeError::PetGraph(err)));
+        try!(self.tree.place_node_at(node_ix, target_ix, ShiftDirection::Right)
+             .map_err(|err| TreeError::PetGraph(err)));
+        self.bind_views_to_output(node_ix);
+        if self.tree.can_remove_empty_parent(old_parent_ix) {
+            try!(self.remove_container(old_parent_ix));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::layout::core::tree::tests::basic_tree;
+
+    #[test]
+    fn labels_are_prefix_free() {
+        assert!(hint_labels(0, "ab").is_empty());
+        assert_eq!(hint_labels(2, "ab"), vec!["a", "b"]);
+        assert_eq!(hint_labels(3, "ab"), vec!["aa", "ab", "ba"]);
+        let labels = hint_labels(20, HINT_ALPHABET);
+        for label in &labels {
+            assert_eq!(label.len(), 2);
+            assert_eq!(labels.iter().filter(|other| other.starts_with(label.as_str())).count(), 1);
+        }
+    }
+
+    #[test]
+    fn typing_labels() {
+        let tree = basic_tree();
+        let active_id = tree.tree[tree.active_container.unwrap()].get_id();
+        // Only workspace 1 is visible, which has one view
+        let mut hints = tree.start_hints(HintAction::Focus).unwrap();
+        assert_eq!(hints.action(), HintAction::Focus);
+        assert_eq!(hints.type_char('a'), HintInput::Chosen(active_id));
+        let mut hints = tree.start_hints(HintAction::Focus).unwrap();
+        assert_eq!(hints.type_char('s'), HintInput::NoMatch);
+    }
+}
@@ -0,0 +1,560 @@
+//! Undo and redo of structural changes to the tree.
+//!
+//! Before a structural change (moving, splitting, floating, sending to a
+//! workspace...) is made, a snapshot of the layout of every workspace is
+//! taken. Undoing a change rebuilds the layout from the snapshot, moving the
+//! views that are still around back to where they were.
+
+use std::collections::VecDeque;
+
+use petgraph::graph::NodeIndex;
+use rustwlc::{Geometry, ResizeEdge};
+use uuid::Uuid;
+
+use super::super::{LayoutTree, TreeError};
+use super::super::commands::CommandResult;
+use super::super::core::container::{Container, ContainerType, Layout};
+use super::super::core::InnerTree;
+use super::focus::FocusError;
+
+/// How many changes are remembered before the oldest ones are forgotten.
+const HISTORY_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum HistoryErr {
+    /// There were no changes left to undo.
+    NothingToUndo,
+    /// There were no undone changes left to redo.
+    NothingToRedo
+}
+
+/// A snapshot of a view or container, and all of its children.
+#[derive(Debug, Clone, PartialEq)]
+enum NodeSnapshot {
+    View {
+        id: Uuid,
+        floating: bool,
+        geometry: Geometry
+    },
+    Container {
+        id: Uuid,
+        layout: Layout,
+        geometry: Geometry,
+        children: Vec<NodeSnapshot>
+    }
+}
+
+impl NodeSnapshot {
+    /// Determines if the two snapshots describe the same layout.
+    ///
+    /// Geometries and the ids of containers are ignored, because they
+    /// change without the layout changing.
+    fn same_layout(&self, other: &NodeSnapshot) -> bool {
+        match (self, other) {
+            (&NodeSnapshot::View { id: id1, floating: floating1, .. },
+             &NodeSnapshot::View { id: id2, floating: floating2, .. }) => {
+                id1 == id2 && floating1 == floating2
+            },
+            (&NodeSnapshot::Container { layout: layout1, children: ref children1, .. },
+             &NodeSnapshot::Container { layout: layout2, children: ref children2, .. }) => {
+                layout1 == layout2 && children1.len() == children2.len()
+                    && children1.iter().zip(children2).all(|(c1, c2)| c1.same_layout(c2))
+            },
+            _ => false
+        }
+    }
+
+    /// Removes the views that are no longer in the tree (or are minimized),
+    /// and the containers that were left empty because of it.
+    fn prune(self, tree: &InnerTree) -> Option<NodeSnapshot> {
+        match self {
+            NodeSnapshot::View { id, .. } => {
+                tree.lookup_id(id)
+                    .and_then(|node_ix| if tree[node_ix].minimized() { None } else { Some(self) })
+            },
+            NodeSnapshot::Container { id, layout, geometry, children } => {
+                let children: Vec<_> = children.into_iter()
+                    .filter_map(|child| child.prune(tree))
+                    .collect();
+                if children.is_empty() {
+                    return None
+                }
+                Some(NodeSnapshot::Container {
+                    id: id,
+                    layout: layout,
+                    geometry: geometry,
+                    children: children
+                })
+            }
+        }
+    }
+
+    fn has_children(&self) -> bool {
+        match *self {
+            NodeSnapshot::Container { ref children, .. } => !children.is_empty(),
+            NodeSnapshot::View { .. } => false
+        }
+    }
+}
+
+/// A snapshot of the layout of a workspace.
+#[derive(Debug, Clone, PartialEq)]
+struct WorkspaceSnapshot {
+    name: String,
+    /// The output the workspace was on
+    output: Uuid,
+    /// The root container of the workspace
+    root: NodeSnapshot
+}
+
+/// A snapshot of the layout of every workspace in the tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutSnapshot {
+    workspaces: Vec<WorkspaceSnapshot>,
+    /// The id of the active container
+    active: Option<Uuid>
+}
+
+impl LayoutSnapshot {
+    /// Determines if the two snapshots describe the same layout.
+    ///
+    /// Empty workspaces are ignored, as they come and go when switching.
+    fn same_layout(&self, other: &LayoutSnapshot) -> bool {
+        let non_empty = |snapshot: &LayoutSnapshot| snapshot.workspaces.iter()
+            .filter(|workspace| workspace.root.has_children())
+            .count();
+        non_empty(self) == non_empty(other) && self.workspaces.iter()
+            .filter(|workspace| workspace.root.has_children())
+            .all(|workspace| other.workspaces.iter().any(|other_workspace| {
+                workspace.name == other_workspace.name
+                    && workspace.root.same_layout(&other_workspace.root)
+            }))
+    }
+
+    /// Removes the views that are no longer in the tree.
+    fn prune(self, tree: &InnerTree) -> LayoutSnapshot {
+        let workspaces = self.workspaces.into_iter()
+            .map(|workspace| {
+                let root = match workspace.root {
+                    NodeSnapshot::Container { id, layout, geometry, children } => {
+                        // The root container stays, even if it's empty
+                        NodeSnapshot::Container {
+                            id: id,
+                            layout: layout,
+                            geometry: geometry,
+                            children: children.into_iter()
+                                .filter_map(|child| child.prune(tree))
+                                .collect()
+                        }
+                    },
+                    view => view
+                };
+                WorkspaceSnapshot { root: root, ..workspace }
+            }).collect();
+        LayoutSnapshot { workspaces: workspaces, active: self.active }
+    }
+}
+
+/// The changes that can be undone and redone.
+#[derive(Debug)]
+pub struct History {
+    undo: VecDeque<LayoutSnapshot>,
+    redo: Vec<LayoutSnapshot>
+}
+
+impl History {
+    pub fn new() -> Self {
+        History {
+            undo: VecDeque::new(),
+            redo: Vec::new()
+        }
+    }
+
+    /// Remembers the layout as it was before a change,
+    /// forgetting the oldest change if there are too many.
+    fn push_undo(&mut self, snapshot: LayoutSnapshot) {
+        self.undo.push_back(snapshot);
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Renames a workspace in every remembered layout,
+    /// so undoing a change doesn't bring back the old name.
+    pub fn rename_workspace(&mut self, name: &str, new_name: &str) {
+        for snapshot in self.undo.iter_mut().chain(self.redo.iter_mut()) {
+            for workspace in &mut snapshot.workspaces {
+                if workspace.name == name {
+                    workspace.name = new_name.into();
+                }
+            }
+        }
+    }
+}
+
+impl LayoutTree {
+    /// Makes a structural change to the tree, remembering the layout from
+    /// before the change so that it can be undone.
+    ///
+    /// If the change did not modify the layout, nothing is remembered.
+    /// Making a change forgets all of the changes that could be redone.
+    pub fn record_change<F>(&mut self, change: F) -> CommandResult
+        where F: FnOnce(&mut LayoutTree) -> CommandResult
+    {
+        let before = self.snapshot();
+        let result = change(self);
+        if !before.same_layout(&self.snapshot()) {
+            self.history.push_undo(before);
+            self.history.redo.clear();
+        }
+        result
+    }
+
+    /// Undoes the last structural change to the tree.
+    ///
+    /// Changes that can no longer be undone, because all of the views
+    /// that were changed are gone, are skipped.
+    pub fn undo(&mut self) -> CommandResult {
+        let current = self.snapshot();
+        while let Some(snapshot) = self.history.undo.pop_back() {
+            let snapshot = snapshot.prune(&self.tree);
+            if snapshot.same_layout(&current) {
+                continue
+            }
+            self.history.redo.push(current);
+            return self.restore_snapshot(snapshot)
+        }
+        Err(TreeError::History(HistoryErr::NothingToUndo))
+    }
+
+    /// Redoes the last structural change to the tree that was undone.
+    ///
+    /// Changes that can no longer be redone, because all of the views
+    /// that were changed are gone, are skipped.
+    pub fn redo(&mut self) -> CommandResult {
+        let current = self.snapshot();
+        while let Some(snapshot) = self.history.redo.pop() {
+            let snapshot = snapshot.prune(&self.tree);
+            if snapshot.same_layout(&current) {
+                continue
+            }
+            self.history.push_undo(current);
+            return self.restore_snapshot(snapshot)
+        }
+        Err(TreeError::History(HistoryErr::NothingToRedo))
+    }
+
+    /// Takes a snapshot of the layout of every workspace in the tree.
+    ///
+    /// Minimized views are not a part of the layout, so they are left out.
+    pub fn snapshot(&self) -> LayoutSnapshot {
+        let mut workspaces = vec![];
+        for output_ix in self.tree.children_of(self.tree.root_ix()) {
+            for workspace_ix in self.tree.children_of(output_ix) {
+                let name = self.tree[workspace_ix].get_name()
+                    .expect("Workspace had no name").to_string();
+                let root_c_ix = self.tree.children_of(workspace_ix)[0];
+                let root = self.snapshot_node(root_c_ix)
+                    .expect("Root container could not be snapshotted");
+                workspaces.push(WorkspaceSnapshot {
+                    name: name,
+                    output: self.tree[output_ix].get_id(),
+                    root: root
+                });
+            }
+        }
+        LayoutSnapshot {
+            workspaces: workspaces,
+            active: self.get_active_container().map(Container::get_id)
+        }
+    }
+
+    fn snapshot_node(&self, node_ix: NodeIndex) -> Option<NodeSnapshot> {
+        let container = &self.tree[node_ix];
+        let geometry = match container.get_type() {
+            ContainerType::View | ContainerType::Container => {
+                container.get_geometry().expect("Container had no geometry")
+            },
+            _ => return None
+        };
+        match *container {
+            Container::View { id, floating, .. } => {
+                if container.minimized() {
+                    return None
+                }
+                Some(NodeSnapshot::View {
+                    id: id,
+                    floating: floating,
+                    geometry: geometry
+                })
+            },
+            Container::Container { id, layout, .. } => {
+                let children = self.tree.children_of(node_ix).into_iter()
+                    .filter_map(|child_ix| self.snapshot_node(child_ix))
+                    .collect();
+                Some(NodeSnapshot::Container {
+                    id: id,
+                    layout: layout,
+                    geometry: geometry,
+                    children: children
+                })
+            },
+            _ => None
+        }
+    }
+
+    /// Rebuilds the layout of the tree from the snapshot.
+    ///
+    /// Views in the snapshot that are no longer in the tree are skipped,
+    /// and views that are not in the snapshot are left where they are.
+    /// The visible workspaces stay the same.
+    fn restore_snapshot(&mut self, snapshot: LayoutSnapshot) -> CommandResult {
+        let root_ix = self.tree.root_ix();
+        let outputs = self.tree.children_of(root_ix);
+        let visible: Vec<NodeIndex> = outputs.iter()
+            .filter_map(|output_ix| {
+                self.tree.follow_path_until(*output_ix, ContainerType::Workspace).ok()
+            }).collect();
+        let active_workspace_ix = self.active_ix_of(ContainerType::Workspace)
+            .or_else(|| visible.get(0).cloned());
+        for workspace in snapshot.workspaces {
+            // Prefer the workspace on the same output, names can repeat across outputs
+            let existing_ix = self.tree.lookup_id(workspace.output)
+                .and_then(|output_ix| self.workspace_ix_on_output(output_ix, &workspace.name))
+                .or_else(|| self.tree.workspace_ix_by_name(&workspace.name));
+            let workspace_ix = match existing_ix {
+                Some(workspace_ix) => workspace_ix,
+                None => {
+                    // Don't bring back workspaces just for them to be empty
+                    if !workspace.root.has_children() {
+                        continue
+                    }
+                    let output_ix = self.tree.lookup_id(workspace.output)
+                        .or_else(|| outputs.get(0).cloned())
+                        .expect("There were no outputs to restore a workspace on");
+                    let root_c_ix = self.init_workspace(workspace.name, output_ix);
+                    try!(self.tree.parent_of(root_c_ix)
+                         .map_err(|err| TreeError::PetGraph(err)))
+                }
+            };
+            let root_c_ix = self.tree.children_of(workspace_ix)[0];
+            match workspace.root {
+                NodeSnapshot::Container { layout, children, .. } => {
+                    self.set_layout(root_c_ix, layout);
+                    for child in children {
+                        try!(self.restore_node(child, root_c_ix, workspace_ix));
+                    }
+                },
+                NodeSnapshot::View { .. } => unreachable!()
+            }
+        }
+        // Clean up what the views left behind
+        let mut workspaces = vec![];
+        for output_ix in outputs {
+            workspaces.extend(self.tree.children_of(output_ix));
+        }
+        for workspace_ix in workspaces.clone() {
+            let root_c_ix = self.tree.children_of(workspace_ix)[0];
+            self.remove_empty_containers(root_c_ix);
+            if !visible.contains(&workspace_ix)
+                && self.tree.descendant_of_type(workspace_ix, ContainerType::View).is_err()
+                && self.placeholders_of(workspace_ix).is_empty() {
+                try!(self.remove_workspace(workspace_ix));
+                workspaces.retain(|ix| *ix != workspace_ix);
+            }
+        }
+        // Moving nodes changes the active path,
+        // so point it back at the workspaces that were visible.
+        workspaces.sort_by_key(|workspace_ix| {
+            (visible.contains(workspace_ix), Some(*workspace_ix) == active_workspace_ix)
+        });
+        for workspace_ix in workspaces.iter().cloned() {
+            let root_c_ix = self.tree.children_of(workspace_ix)[0];
+            let path_end_ix = self.tree.lowest_active_view(root_c_ix)
+                .into_iter()
+                .find(|view_ix| !self.tree[*view_ix].floating())
+                .unwrap_or(root_c_ix);
+            self.tree.set_ancestor_paths_active(path_end_ix);
+            self.tree.set_family_visible(workspace_ix, visible.contains(&workspace_ix));
+        }
+        let maybe_active_ix = snapshot.active
+            .and_then(|id| self.tree.lookup_id(id))
+            .into_iter()
+            .find(|node_ix| {
+                let container = &self.tree[*node_ix];
+                !container.floating() && !container.minimized()
+                    && self.tree.ancestor_of_type(*node_ix, ContainerType::Workspace).ok()
+                    == active_workspace_ix
+            })
+            .or_else(|| {
+                let root_ix = self.tree.root_ix();
+                match self.tree.follow_path(root_ix) {
+                    node_ix if self.tree[node_ix].get_type() == ContainerType::View
+                        || self.tree[node_ix].get_type() == ContainerType::Container => {
+                            Some(node_ix)
+                        },
+                    _ => None
+                }
+            });
+        self.active_container = None;
+        if let Some(active_ix) = maybe_active_ix {
+            match self.set_active_node(active_ix) {
+                Ok(_) => {},
+                Err(TreeError::Focus(FocusError::BlockedByFullscreen(_, _))) => {
+                    debug!("Blocked focus by fullscreen");
+                },
+                Err(err) => return Err(err)
+            }
+        }
+        self.layout(root_ix);
+        self.validate();
+        self.validate_path();
+        Ok(())
+    }
+
+    /// Puts the node described by the snapshot into the parent.
+    ///
+    /// Containers that no longer exist are recreated,
+    /// views that no longer exist are skipped.
+    fn restore_node(&mut self, snapshot: NodeSnapshot, parent_ix: NodeIndex,
+                    workspace_ix: NodeIndex) -> CommandResult {
+        match snapshot {
+            NodeSnapshot::View { id, floating, geometry } => {
+                let node_ix = match self.tree.lookup_id(id) {
+                    Some(node_ix) => node_ix,
+                    None => return Ok(())
+                };
+                if self.tree[node_ix].minimized() {
+                    return Ok(())
+                }
+                if let Ok(cur_workspace_ix) = self.tree.ancestor_of_type(
+                    node_ix, ContainerType::Workspace) {
+                    if cur_workspace_ix != workspace_ix {
+                        self.transfer_fullscreen(cur_workspace_ix, workspace_ix, id);
+                    }
+                }
+                let old_output_ix = self.tree.ancestor_of_type(node_ix, ContainerType::Output)
+                    .ok();
+                try!(self.tree.move_into(node_ix, parent_ix)
+                     .map_err(|err| TreeError::PetGraph(err)));
+                // wlc has to draw the view on the output it is moved back to
+                let output_ix = self.tree.ancestor_of_type(node_ix, ContainerType::Output).ok();
+                if output_ix != old_output_ix {
+                    self.bind_views_to_output(node_ix);
+                }
+                let container = &mut self.tree[node_ix];
+                if container.floating() != floating {
+                    try!(container.set_floating(floating)
+                         .map_err(|_| TreeError::UuidWrongType(id, vec!(ContainerType::View))));
+                }
+                container.set_geometry(ResizeEdge::empty(), geometry);
+            },
+            NodeSnapshot::Container { id, layout, geometry, children } => {
+                let node_ix = match self.tree.lookup_id(id) {
+                    Some(node_ix) if self.tree[node_ix].get_type() == ContainerType::Container
+                        && !self.tree.is_root_container(node_ix) => {
+                            try!(self.tree.move_into(node_ix, parent_ix)
+                                 .map_err(|err| TreeError::PetGraph(err)));
+                            node_ix
+                        },
+                    _ => self.tree.add_child(parent_ix, Container::new_container(geometry), false)
+                };
+                self.set_layout(node_ix, layout);
+                self.tree[node_ix].set_geometry(ResizeEdge::empty(), geometry);
+                for child in children {
+                    try!(self.restore_node(child, node_ix, workspace_ix));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes every non-root container beneath the node that has no children.
+    fn remove_empty_containers(&mut self, node_ix: NodeIndex) {
+        for child_ix in self.tree.children_of(node_ix) {
+            if self.tree[child_ix].get_type() != ContainerType::Container {
+                continue
+            }
+            self.remove_empty_containers(child_ix);
+            if self.tree.can_remove_empty_parent(child_ix) {
+                if self.active_container == Some(child_ix) {
+                    self.active_container = None;
+                }
+                self.tree.remove(child_ix);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::TreeError;
+    use super::super::super::core::container::{Container, ContainerType, Layout};
+    use super::super::super::core::tree::Direction;
+    use super::HistoryErr;
+    use ::layout::core::tree::tests::{active_view_id, basic_tree};
+
+    #[test]
+    fn nothing_to_undo() {
+        let mut tree = basic_tree();
+        assert_eq!(tree.undo(), Err(TreeError::History(HistoryErr::NothingToUndo)));
+        assert_eq!(tree.redo(), Err(TreeError::History(HistoryErr::NothingToRedo)));
+        // Changes that don't change anything aren't remembered
+        tree.record_change(|_| Ok(())).unwrap();
+        assert!(tree.undo().is_err());
+    }
+
+    #[test]
+    fn undo_redo_move() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        let id = active_view_id(&tree);
+        let parent_id = tree.parent_of(id).unwrap().get_id();
+        let before = tree.snapshot();
+        tree.record_change(|tree| tree.move_container(id, Direction::Left)).unwrap();
+        let after = tree.snapshot();
+        assert!(!before.same_layout(&after));
+        tree.undo().unwrap();
+        assert!(tree.snapshot().same_layout(&before));
+        assert_eq!(tree.parent_of(id).unwrap().get_id(), parent_id);
+        assert_eq!(active_view_id(&tree), id);
+        tree.redo().unwrap();
+        assert!(tree.snapshot().same_layout(&after));
+        assert!(tree.redo().is_err());
+    }
+
+    #[test]
+    fn undo_layout_toggle() {
+        let mut tree = basic_tree();
+        let root_c_ix = tree.root_container_ix().unwrap();
+        tree.record_change(|tree| {
+            tree.set_layout(root_c_ix, Layout::Vertical);
+            Ok(())
+        }).unwrap();
+        tree.undo().unwrap();
+        let root_c_ix = tree.root_container_ix().unwrap();
+        match tree.tree[root_c_ix] {
+            Container::Container { layout, .. } => assert_eq!(layout, Layout::Horizontal),
+            _ => panic!("Root container was not a container")
+        }
+    }
+
+    #[test]
+    fn undo_send_to_workspace() {
+        let mut tree = basic_tree();
+        let id = active_view_id(&tree);
+        tree.record_change(|tree| {
+            tree.send_to_workspace(id, "3");
+            Ok(())
+        }).unwrap();
+        let workspace_ix = tree.tree.ancestor_of_type(tree.tree.lookup_id(id).unwrap(),
+                                                      ContainerType::Workspace).unwrap();
+        assert_eq!(tree.tree[workspace_ix].get_name(), Some("3"));
+        tree.undo().unwrap();
+        let workspace_ix = tree.tree.ancestor_of_type(tree.tree.lookup_id(id).unwrap(),
+                                                      ContainerType::Workspace).unwrap();
+        assert_eq!(tree.tree[workspace_ix].get_name(), Some("1"));
+        // Workspace 3 is empty and not visible, so it's cleaned up
+        assert!(tree.tree.workspace_ix_by_name("3").is_none());
+    }
+}
@@ -5,9 +5,11 @@ use rustwlc::{WlcView, Geometry, Point, Size, ResizeEdge};
 
 use super::super::{LayoutTree, TreeError};
 use super::super::commands::CommandResult;
-use super::super::core::container::{Container, ContainerType, Layout};
+use super::super::core::container::{Container, ContainerType, Layout, SizeLimits};
+use super::status_bar::mark_bars_outdated;
 use ::layout::core::borders::Borders;
 use ::debug_enabled;
+use ::render::logical_resolution;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -23,6 +25,7 @@ impl LayoutTree {
     /// that container based on what type of container it is and how big of an
     /// area is allocated for it and its children.
     pub fn layout(&mut self, node_ix: NodeIndex) {
+        mark_bars_outdated();
         match self.tree[node_ix].get_type() {
             ContainerType::Root => {
                 for output_ix in self.tree.children_of(node_ix) {
@@ -49,9 +52,12 @@ impl LayoutTree {
                 }
                 let mut fullscreen_apps = Vec::new();
                 for workspace_ix in self.tree.children_of(node_ix) {
-                    self.layout_helper(workspace_ix, geometry, &mut fullscreen_apps);
+                    let lone_borderless = self.lone_view_borderless(workspace_ix);
+                    self.layout_helper(workspace_ix, geometry, &mut fullscreen_apps,
+                                       lone_borderless);
                 }
                 self.layout_fullscreen_apps(fullscreen_apps);
+                self.place_panels(node_ix);
             }
             ContainerType::Workspace => {
                 // get geometry from the parent output
@@ -60,7 +66,9 @@ impl LayoutTree {
                 let output_geometry = self.tree[output_ix].get_geometry()
                     .expect("Could not get output geometry");
                 let mut fullscreen_apps = Vec::new();
-                self.layout_helper(node_ix, output_geometry, &mut fullscreen_apps);
+                let lone_borderless = self.lone_view_borderless(node_ix);
+                self.layout_helper(node_ix, output_geometry, &mut fullscreen_apps,
+                                   lone_borderless);
                 self.layout_fullscreen_apps(fullscreen_apps)
             }
             ContainerType::Container => {
@@ -70,7 +78,8 @@ impl LayoutTree {
                 };
                 // TODO Fake vector that doesn't allocate for this case?
                 let mut fullscreen_apps = Vec::new();
-                self.layout_helper(node_ix, geometry, &mut fullscreen_apps);
+                let lone_borderless = self.lone_view_borderless(node_ix);
+                self.layout_helper(node_ix, geometry, &mut fullscreen_apps, lone_borderless);
             }
             ContainerType::View => {
                 let parent_ix = self.tree.parent_of(node_ix)
@@ -84,8 +93,11 @@ impl LayoutTree {
     /// Helper function to layout a container. The geometry is the constraint geometry,
     /// the container tries to lay itself out within the confines defined by the constraint.
     /// Generally, this should not be used directly and layout should be used.
+    ///
+    /// `lone_borderless` is whether the only tiled view of the workspace
+    /// goes without borders, see `lone_view_borderless`.
     fn layout_helper(&mut self, node_ix: NodeIndex, geometry: Geometry,
-                     fullscreen_apps: &mut Vec<NodeIndex>) {
+                     fullscreen_apps: &mut Vec<NodeIndex>, lone_borderless: bool) {
         if self.tree[node_ix].fullscreen() {
             fullscreen_apps.push(node_ix);
         }
@@ -101,13 +113,14 @@ impl LayoutTree {
             ContainerType::Output => {
                 self.tree[node_ix].set_geometry(ResizeEdge::empty(), geometry);
                 for child_ix in self.tree.children_of(node_ix) {
-                    self.layout_helper(child_ix, geometry, fullscreen_apps);
+                    let lone_borderless = self.lone_view_borderless(child_ix);
+                    self.layout_helper(child_ix, geometry, fullscreen_apps, lone_borderless);
                 }
             }
             ContainerType::Workspace => {
                 self.tree[node_ix].set_geometry(ResizeEdge::empty(), geometry);
                 for child_ix in self.tree.grounded_children(node_ix) {
-                    self.layout_helper(child_ix, geometry, fullscreen_apps);
+                    self.layout_helper(child_ix, geometry, fullscreen_apps, lone_borderless);
                 }
                 // place floating children above everything else
                 let root_ix = self.tree.children_of(node_ix)[0];
@@ -141,7 +154,9 @@ impl LayoutTree {
 
                         if scale > 0.1 {
                             scale = geometry.size.w as f32 / scale;
-                            let new_size_f = |child_size: Size, sub_geometry: Geometry| {
+                            let widths = children.iter().map(|child_ix| {
+                                let child_size = self.tree[*child_ix].get_geometry()
+                                    .expect("Child had no geometry").size;
                                 let width = if child_size.w > 0 {
                                     child_size.w as f32
                                 } else {
@@ -149,8 +164,17 @@ impl LayoutTree {
                                     // e.g, if container was width 500 w/ 2 children, this view would have a width of 250
                                     geometry.size.w as f32 / children_len.checked_sub(1).unwrap_or(1) as f32
                                 };
+                                (width * scale) as u32
+                            }).collect();
+                            let limits: Vec<(u32, u32)> = children.iter().map(|child_ix| {
+                                let limits = self.size_limits_of(*child_ix);
+                                (limits.min.w, limits.max.w)
+                            }).collect();
+                            let widths = LayoutTree::constrain_sizes(widths, limits.as_slice(),
+                                                                     geometry.size.w);
+                            let new_size_f = |index: usize, sub_geometry: Geometry| {
                                 Size {
-                                    w: ((width) * scale) as u32,
+                                    w: widths[index],
                                     h: sub_geometry.size.h
                                 }
                             };
@@ -172,8 +196,8 @@ impl LayoutTree {
                             };
                             self.generic_tile(node_ix, geometry, children.as_slice(),
                                               new_size_f, remaining_size_f, new_point_f,
-                                              fullscreen_apps);
-                            self.add_borders(node_ix)
+                                              fullscreen_apps, lone_borderless);
+                            self.add_borders(node_ix, lone_borderless)
                                 .expect("Couldn't add border gaps to horizontal container");
                             self.add_gaps(node_ix)
                                 .expect("Couldn't add gaps to horizontal container");
@@ -191,17 +215,28 @@ impl LayoutTree {
 
                         if scale > 0.1 {
                             scale = geometry.size.h as f32 / scale;
-                            let new_size_f = |child_size: Size, sub_geometry: Geometry| {
+                            let heights = children.iter().map(|child_ix| {
+                                let child_size = self.tree[*child_ix].get_geometry()
+                                    .expect("Child had no geometry").size;
                                 let height = if child_size.h > 0 {
                                     child_size.h as f32
                                 } else {
                                     // If the height would become zero, just make it the average size of the container.
                                     // e.g, if container was height 500 w/ 2 children, this view would have a height of 250
                                     geometry.size.h as f32 / children_len.checked_sub(1).unwrap_or(1) as f32
-                                 };
+                                };
+                                (height * scale) as u32
+                            }).collect();
+                            let limits: Vec<(u32, u32)> = children.iter().map(|child_ix| {
+                                let limits = self.size_limits_of(*child_ix);
+                                (limits.min.h, limits.max.h)
+                            }).collect();
+                            let heights = LayoutTree::constrain_sizes(heights, limits.as_slice(),
+                                                                      geometry.size.h);
+                            let new_size_f = |index: usize, sub_geometry: Geometry| {
                                 Size {
                                     w: sub_geometry.size.w,
-                                    h: ((height) * scale) as u32
+                                    h: heights[index]
                                 }
                             };
                             let remaining_size_f = |sub_geometry: Geometry,
@@ -222,8 +257,8 @@ impl LayoutTree {
                             };
                             self.generic_tile(node_ix, geometry, children.as_slice(),
                                               new_size_f, remaining_size_f, new_point_f,
-                                              fullscreen_apps);
-                            self.add_borders(node_ix)
+                                              fullscreen_apps, lone_borderless);
+                            self.add_borders(node_ix, lone_borderless)
                                 .expect("Couldn't add border gaps to horizontal container");
                             self.add_gaps(node_ix)
                                 .expect("Couldn't add gaps to vertical container");
@@ -235,7 +270,7 @@ impl LayoutTree {
 
             ContainerType::View => {
                 self.tree[node_ix].set_geometry(ResizeEdge::empty(), geometry);
-                self.add_borders(node_ix)
+                self.add_borders(node_ix, lone_borderless)
                     .expect("Couldn't add border gaps to horizontal container");
             }
         }
@@ -265,7 +300,7 @@ impl LayoutTree {
                              .map_err(|err| TreeError::PetGraph(err)));
         let output_size = match self.tree[output_ix] {
             Container::Output { handle, .. } => {
-                handle.get_resolution().expect("Output had no resolution")
+                logical_resolution(handle).expect("Output had no resolution")
             },
             _ => unreachable!()
         };
@@ -353,6 +388,9 @@ impl LayoutTree {
             error!("Tried to absolutely place a non-floating view!");
             return
         }
+        // Floating views aren't tiled, so smart borders don't matter
+        self.apply_border_style(node_ix, false);
+        self.update_title_bar(node_ix);
         {
             let container = &mut self.tree[node_ix];
             match *container {
@@ -413,7 +451,9 @@ impl LayoutTree {
                     let mut fullscreen_apps = Vec::new();
                     let geometry = self.tree[container_ix].get_geometry()
                         .expect("Container didn't have a geometry");
-                    self.layout_helper(container_ix, geometry, &mut fullscreen_apps);
+                    let lone_borderless = self.lone_view_borderless(container_ix);
+                    self.layout_helper(container_ix, geometry, &mut fullscreen_apps,
+                                       lone_borderless);
                 },
                 ContainerType::View => {
                     warn!("Cannot simply update a view's geometry without {}",
@@ -481,24 +521,100 @@ impl LayoutTree {
         return scale;
     }
 
+    /// Clamps each size to be within its (minimum, maximum) limits.
+    /// The space that clamping frees up (or takes away) is shared between the
+    /// sizes that are still within their limits, in proportion to their size.
+    ///
+    /// If the minimum sizes can not all fit in the total,
+    /// the sizes are returned unchanged.
+    fn constrain_sizes(mut sizes: Vec<u32>, limits: &[(u32, u32)], total: u32) -> Vec<u32> {
+        let min_total = limits.iter()
+            .fold(0u32, |acc, &(min, _)| acc.saturating_add(min));
+        if min_total > total {
+            return sizes
+        }
+        let mut clamped = vec![false; sizes.len()];
+        loop {
+            let mut changed = false;
+            for (index, size) in sizes.iter_mut().enumerate() {
+                let (min, max) = limits[index];
+                if clamped[index] || (*size >= min && *size <= max) {
+                    continue
+                }
+                *size = cmp::max(cmp::min(*size, max), min);
+                clamped[index] = true;
+                changed = true;
+            }
+            let free: Vec<usize> = (0..sizes.len())
+                .filter(|index| !clamped[*index]).collect();
+            if !changed || free.is_empty() {
+                return sizes
+            }
+            let clamped_total = (0..sizes.len())
+                .filter(|index| clamped[*index])
+                .fold(0u32, |acc, index| acc.saturating_add(sizes[index]));
+            let free_total = free.iter()
+                .fold(0u64, |acc, index| acc + sizes[*index] as u64);
+            let remaining = total.saturating_sub(clamped_total) as u64;
+            for index in free.iter().cloned() {
+                sizes[index] = if free_total > 0 {
+                    (sizes[index] as u64 * remaining / free_total) as u32
+                } else {
+                    (remaining / free.len() as u64) as u32
+                };
+            }
+        }
+    }
+
+    /// Gets how small and large the node may be made when it is tiled.
+    ///
+    /// For views, this is the size limits of the view plus the space its
+    /// borders and gaps take up. A container can not be made smaller than
+    /// its children allow, but it has no maximum size.
+    pub fn size_limits_of(&self, node_ix: NodeIndex) -> SizeLimits {
+        match self.tree[node_ix] {
+            Container::View { size_limits, ref borders, .. } => {
+                let (thickness, title_size) = match *borders {
+                    Some(ref borders) => (Borders::thickness(), borders.title_size()),
+                    None => (0, 0)
+                };
+                let border_size = thickness + Borders::gap_size();
+                size_limits.grow(border_size, border_size + title_size)
+            },
+            Container::Container { layout, .. } => {
+                let mut limits = SizeLimits::new();
+                limits.min = Size { w: 0, h: 0 };
+                for child_ix in self.tree.grounded_children(node_ix) {
+                    let child_min = self.size_limits_of(child_ix).min;
+                    match layout {
+                        Layout::Horizontal => {
+                            limits.min.w = limits.min.w.saturating_add(child_min.w);
+                            limits.min.h = cmp::max(limits.min.h, child_min.h);
+                        },
+                        Layout::Vertical => {
+                            limits.min.w = cmp::max(limits.min.w, child_min.w);
+                            limits.min.h = limits.min.h.saturating_add(child_min.h);
+                        }
+                    }
+                }
+                limits
+            },
+            _ => SizeLimits::new()
+        }
+    }
+
     fn generic_tile<SizeF, RemainF, PointF>
         (&mut self,
          node_ix: NodeIndex, geometry: Geometry, children: &[NodeIndex],
          new_size_f: SizeF, remaining_size_f: RemainF, new_point_f: PointF,
-         fullscreen_apps: &mut Vec<NodeIndex>)
-        where SizeF:   Fn(Size, Geometry) -> Size,
+         fullscreen_apps: &mut Vec<NodeIndex>, lone_borderless: bool)
+        where SizeF:   Fn(usize, Geometry) -> Size,
               RemainF: Fn(Geometry, Geometry) -> Size,
               PointF:  Fn(Size, Geometry) -> Point
     {
         let mut sub_geometry = geometry.clone();
         for (index, child_ix) in children.iter().enumerate() {
-            let child_size: Size;
-            {
-                let child = &self.tree[*child_ix];
-                child_size = child.get_geometry()
-                    .expect("Child had no geometry").size;
-            }
-            let new_size = new_size_f(child_size, sub_geometry.clone());
+            let new_size = new_size_f(index, sub_geometry.clone());
             sub_geometry = Geometry {
                 origin: sub_geometry.origin.clone(),
                 size: new_size.clone()
@@ -513,7 +629,8 @@ impl LayoutTree {
                     size: new_size
                 };
             }
-            self.layout_helper(*child_ix, sub_geometry.clone(), fullscreen_apps);
+            self.layout_helper(*child_ix, sub_geometry.clone(), fullscreen_apps,
+                               lone_borderless);
 
             // Next sub container needs to start where this one ends
             let new_point = new_point_f(new_size.clone(), sub_geometry.clone());
@@ -717,18 +834,24 @@ impl LayoutTree {
     }
 
     /// Adds spacing for borders between the windows.
-    fn add_borders(&mut self, node_ix: NodeIndex) -> CommandResult {
+    fn add_borders(&mut self, node_ix: NodeIndex, lone_borderless: bool) -> CommandResult {
+        if self.tree[node_ix].get_type() == ContainerType::View {
+            self.apply_border_style(node_ix, lone_borderless);
+        }
         {
             let container = &mut self.tree[node_ix];
             let mut geometry = container.get_geometry()
                 .expect("Container had no geometry");
             match *container {
-                Container::View { handle, .. } => {
+                Container::View { handle, ref borders, .. } => {
+                    let title_size = match *borders {
+                        Some(ref borders) => borders.title_size(),
+                        None => return Ok(())
+                    };
                     let borders = Borders::thickness();
                     if borders == 0 {
                         return Ok(())
                     }
-                    let title_size = Borders::title_bar_size();
                     geometry.origin.x += (borders / 2) as i32;
                     geometry.origin.y += (borders / 2) as i32;
                     geometry.origin.y += title_size as i32;
@@ -758,10 +881,16 @@ impl LayoutTree {
         while children.len() > 0 {
             let child_ix = children.pop().unwrap();
             children.extend(self.tree.grounded_children(child_ix));
+            self.update_title_bar(child_ix);
             let container = &mut self.tree[child_ix];
             if Some(child_ix) != self.active_container {
-                container.clear_border_color()
-                    .expect("Could not clear border color");
+                if container.urgent() {
+                    container.urgent_border_color()
+                        .expect("Could not set border color to be urgent");
+                } else {
+                    container.clear_border_color()
+                        .expect("Could not clear border color");
+                }
             } else {
                 container.active_border_color()
                     .expect("Could not set border color to be active");
@@ -783,4 +912,19 @@ mod test {
         assert_eq!(LayoutTree::calculate_scale(vec!(5.0, 5.0, 5.0, 5.0, 5.0, 5.0), 0.0), 30.0);
         assert_eq!(LayoutTree::calculate_scale(vec!(5.0, 5.0, 5.0, 5.0, -5.0, 0.0), 5.0), 22.0);
     }
+
+    #[test]
+    /// Ensure that constrain_sizes gives the space of clamped sizes to their siblings
+    fn constrain_sizes_test() {
+        let unbounded = ::std::u32::MAX;
+        assert_eq!(LayoutTree::constrain_sizes(vec!(50, 50), &[(0, unbounded), (0, unbounded)], 100),
+                   vec!(50, 50));
+        assert_eq!(LayoutTree::constrain_sizes(vec!(50, 50), &[(80, unbounded), (0, unbounded)], 100),
+                   vec!(80, 20));
+        assert_eq!(LayoutTree::constrain_sizes(vec!(100, 100, 100), &[(0, 60), (0, unbounded), (0, unbounded)], 300),
+                   vec!(60, 120, 120));
+        // Minimum sizes that don't fit are ignored
+        assert_eq!(LayoutTree::constrain_sizes(vec!(50, 50), &[(80, unbounded), (80, unbounded)], 100),
+                   vec!(50, 50));
+    }
 }
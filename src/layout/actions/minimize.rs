@@ -0,0 +1,191 @@
+use petgraph::graph::NodeIndex;
+use uuid::Uuid;
+
+use super::super::{LayoutTree, TreeError};
+use super::super::commands::CommandResult;
+use super::super::core::container::{Container, ContainerType, MinimizedFrom};
+use super::focus::FocusError;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MinimizeErr {
+    /// The view behind the UUID was asked to minimize when it already was.
+    AlreadyMinimized(Uuid),
+    /// The view behind the UUID was asked to restore, but it was not minimized.
+    NotMinimized(Uuid)
+}
+
+impl LayoutTree {
+    /// Minimizes the view behind the id.
+    ///
+    /// The view is hidden and taken out of the tiling, but it stays in the
+    /// tree as a child of the root container of its workspace. Where it
+    /// came from is remembered, so that `restore_view` can put it back.
+    ///
+    /// If the view was the active container, the next container is focused.
+    pub fn minimize_view(&mut self, id: Uuid) -> CommandResult {
+        let node_ix = try!(self.tree.lookup_id(id).ok_or(TreeError::NodeNotFound(id)));
+        match self.tree[node_ix] {
+            Container::View { .. } => {},
+            _ => return Err(TreeError::UuidWrongType(id, vec!(ContainerType::View)))
+        }
+        if self.tree[node_ix].minimized() {
+            return Err(TreeError::Minimize(MinimizeErr::AlreadyMinimized(id)))
+        }
+        let parent_ix = try!(self.tree.parent_of(node_ix)
+                             .map_err(|err| TreeError::PetGraph(err)));
+        let workspace_ix = try!(self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)
+                                .map_err(|err| TreeError::PetGraph(err)));
+        let root_c_ix = self.tree.children_of(workspace_ix)[0];
+        let position = **self.tree.get_edge_weight_between(parent_ix, node_ix)
+            .expect("View was not linked to its parent");
+        let minimized_from = MinimizedFrom {
+            parent: self.tree[parent_ix].get_id(),
+            position: position
+        };
+        if self.tree[node_ix].fullscreen() {
+            self.tree[node_ix].set_fullscreen(false)
+                .expect("Could not unset fullscreen on a view");
+            self.tree[workspace_ix].update_fullscreen_c(id, false)
+                .expect("workspace_ix did not point to a workspace");
+        }
+        {
+            let container = &mut self.tree[node_ix];
+            try!(container.set_minimized(Some(minimized_from))
+                 .map_err(|err| TreeError::Container(err)));
+            container.set_visibility(false);
+        }
+        if self.active_container == Some(node_ix) {
+            self.active_container = None;
+        }
+        if parent_ix != root_c_ix {
+            try!(self.tree.move_into(node_ix, root_c_ix)
+                 .map_err(|err| TreeError::PetGraph(err)));
+            if self.tree.can_remove_empty_parent(parent_ix) {
+                try!(self.remove_view_or_container(parent_ix));
+            }
+        }
+        if self.active_container.is_none() {
+            self.focus_on_next_container(root_c_ix);
+        }
+        self.layout(workspace_ix);
+        self.validate();
+        Ok(())
+    }
+
+    /// Restores the minimized view behind the id.
+    ///
+    /// The view is put back in the container it was minimized from, at the
+    /// same position. If that container no longer exists, the view is put in
+    /// the root container of its workspace instead.
+    ///
+    /// The workspace of the view is switched to, and the view is focused.
+    pub fn restore_view(&mut self, id: Uuid) -> CommandResult {
+        let node_ix = try!(self.tree.lookup_id(id).ok_or(TreeError::NodeNotFound(id)));
+        let minimized_from = try!(self.tree[node_ix].minimized_from()
+                                  .ok_or(TreeError::Minimize(MinimizeErr::NotMinimized(id))));
+        let workspace_ix = try!(self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)
+                                .map_err(|err| TreeError::PetGraph(err)));
+        let root_c_ix = self.tree.children_of(workspace_ix)[0];
+        // Only put it back in the old container if it is still in this workspace
+        let parent_ix = match self.tree.lookup_id(minimized_from.parent) {
+            Some(parent_ix) if self.tree[parent_ix].get_type() == ContainerType::Container
+                && self.tree.ancestor_of_type(parent_ix, ContainerType::Workspace)
+                == Ok(workspace_ix) => parent_ix,
+            _ => root_c_ix
+        };
+        try!(self.tree[node_ix].set_minimized(None)
+             .map_err(|err| TreeError::Container(err)));
+        if !self.tree[node_ix].floating() {
+            try!(self.tree.move_into(node_ix, parent_ix)
+                 .map_err(|err| TreeError::PetGraph(err)));
+            self.tree.set_child_pos(node_ix, minimized_from.position);
+        }
+        let output_ix = try!(self.tree.parent_of(workspace_ix)
+                             .map_err(|err| TreeError::PetGraph(err)));
+        if self.tree.follow_path_until(output_ix, ContainerType::Workspace) == Ok(workspace_ix) {
+            self.tree[node_ix].set_visibility(true);
+        } else {
+            let name = self.tree[workspace_ix].get_name()
+                .expect("Workspace had no name").to_string();
+            self.switch_to_workspace(&name);
+        }
+        match self.set_active_node(node_ix) {
+            Ok(_) => {},
+            Err(TreeError::Focus(FocusError::BlockedByFullscreen(_, _))) => {
+                debug!("Blocked focus by fullscreen");
+            },
+            Err(err) => return Err(err)
+        }
+        self.layout(workspace_ix);
+        self.validate();
+        Ok(())
+    }
+
+    /// Gets the node indices of every minimized view beneath the node.
+    ///
+    /// The views are in the order they sit in their root containers,
+    /// so the most recently minimized view of a workspace comes last.
+    pub fn minimized_views_of(&self, node_ix: NodeIndex) -> Vec<NodeIndex> {
+        let mut result = vec![];
+        if self.tree[node_ix].minimized() {
+            result.push(node_ix);
+        }
+        for child_ix in self.tree.children_of(node_ix) {
+            result.extend(self.minimized_views_of(child_ix));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::core::container::ContainerType;
+    use ::layout::core::tree::tests::{active_view_id, basic_tree};
+
+    #[test]
+    fn minimize_and_restore() {
+        let mut tree = basic_tree();
+        let id = active_view_id(&tree);
+        let parent_id = tree.parent_of(id).unwrap().get_id();
+        let root_ix = tree.tree.root_ix();
+        assert!(tree.minimized_views_of(root_ix).is_empty());
+        tree.minimize_view(id).unwrap();
+        let node_ix = tree.tree.lookup_id(id).unwrap();
+        assert!(tree.tree[node_ix].minimized());
+        assert_eq!(tree.minimized_views_of(root_ix), vec![node_ix]);
+        assert!(tree.active_container != Some(node_ix));
+        assert_eq!(tree.tree.descendant_of_type(node_ix, ContainerType::View), Ok(node_ix));
+        assert!(tree.tree.focusable_descendant_of_type(node_ix, ContainerType::View).is_err());
+        assert!(tree.minimize_view(id).is_err());
+        tree.restore_view(id).unwrap();
+        assert!(!tree.tree[node_ix].minimized());
+        assert!(tree.minimized_views_of(root_ix).is_empty());
+        assert_eq!(tree.active_container, Some(node_ix));
+        assert_eq!(tree.parent_of(id).unwrap().get_id(), parent_id);
+        assert!(tree.restore_view(id).is_err());
+    }
+
+    #[test]
+    fn restore_into_old_container() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        let id = active_view_id(&tree);
+        let parent_id = tree.parent_of(id).unwrap().get_id();
+        tree.minimize_view(id).unwrap();
+        assert!(tree.parent_of(id).unwrap().get_id() != parent_id);
+        tree.restore_view(id).unwrap();
+        assert_eq!(tree.parent_of(id).unwrap().get_id(), parent_id);
+    }
+
+    #[test]
+    fn minimized_view_keeps_workspace() {
+        let mut tree = basic_tree();
+        let id = active_view_id(&tree);
+        tree.minimize_view(id).unwrap();
+        tree.switch_to_workspace("2");
+        assert!(tree.tree.workspace_ix_by_name("1").is_some());
+        tree.restore_view(id).unwrap();
+        let workspace_ix = tree.active_ix_of(ContainerType::Workspace).unwrap();
+        assert_eq!(tree.tree[workspace_ix].get_name(), Some("1"));
+    }
+}
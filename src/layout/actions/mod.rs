@@ -5,3 +5,16 @@ pub mod workspace;
 pub mod resize;
 pub mod pointer;
 pub mod background;
+pub mod minimize;
+pub mod history;
+pub mod template;
+pub mod urgent;
+pub mod hints;
+pub mod switcher;
+pub mod output;
+pub mod status_bar;
+pub mod reservation;
+pub mod osd;
+pub mod title;
+pub mod border_style;
+pub mod title_bar;
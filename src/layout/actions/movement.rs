@@ -112,7 +112,9 @@ impl LayoutTree {
         let parent_ix = try!(self.tree.parent_of(node_ix)
                              .map_err(|err| MovementError::Tree(
                                  Box::new(TreeError::PetGraph(err)))));
-        let siblings_and_self = self.tree.children_of(parent_ix);
+        let siblings_and_self: Vec<_> = self.tree.children_of(parent_ix).into_iter()
+            .filter(|sibling_ix| !self.tree[*sibling_ix].minimized())
+            .collect();
         let cur_index = try!(siblings_and_self.iter().position(|node| {
             *node == node_ix
         }).ok_or(MovementError::Tree(
@@ -171,7 +173,9 @@ impl LayoutTree {
         let cur_parent_ix = try!(self.tree.parent_of(move_ancestor)
                                  .map_err(|err| MovementError::Tree(
                                      Box::new(TreeError::PetGraph(err)))));
-        let siblings_and_self = self.tree.children_of(cur_parent_ix);
+        let siblings_and_self: Vec<_> = self.tree.children_of(cur_parent_ix).into_iter()
+            .filter(|sibling_ix| !self.tree[*sibling_ix].minimized())
+            .collect();
         let cur_index = try!(siblings_and_self.iter().position(|node| {
             *node == move_ancestor
         }).ok_or(MovementError::Tree(
@@ -416,4 +420,20 @@ mod tests {
         assert_eq!(tree.tree[children[0]].get_type(), ContainerType::View);
         assert_eq!(tree.tree[children[1]].get_type(), ContainerType::View);
     }
+    #[test]
+    fn move_past_minimized_views() {
+        let mut tree = basic_tree();
+        tree.add_view(WlcView::root()).unwrap();
+        let hidden_id = tree.get_active_container().unwrap().get_id();
+        tree.add_view(WlcView::root()).unwrap();
+        let active_ix = tree.active_container.unwrap();
+        let active_uuid = tree.tree[active_ix].get_id();
+        let active_parent = tree.tree.parent_of(active_ix).unwrap();
+        tree.minimize_view(hidden_id).unwrap();
+        // Swaps with the first view, not the minimized one next to it
+        tree.move_container(active_uuid, Direction::Left).unwrap();
+        let children = tree.tree.children_of(active_parent);
+        assert_eq!(children[0], tree.tree.lookup_id(active_uuid).unwrap());
+        assert_eq!(children[1], tree.tree.lookup_id(hidden_id).unwrap());
+    }
 }
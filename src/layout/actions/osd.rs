@@ -0,0 +1,321 @@
+//! Short messages shown over the focused output, which go away by themselves.
+//!
+//! Messages are shown for Lua errors, when the configuration is reloaded and
+//! when switching workspaces. Clients can show their own over D-Bus or from
+//! Lua, e.g the volume. The options are read from the osd category of the
+//! registry.
+//!
+//! A timer takes messages away when their time is up, the outputs are only
+//! rendered again when a message is shown or taken away. Messages can be shown
+//! from any thread, but the timer is only made and armed on the main thread,
+//! from `init_osd_timer` and `render_osd`.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use petgraph::graph::NodeIndex;
+use rustc_serialize::json::Json;
+use rustwlc::{Geometry, Point, Size, WlcOutput};
+use uuid::Uuid;
+
+use super::super::LayoutTree;
+use super::super::core::container::ContainerType;
+use ::registry;
+use ::render::{Osd, Renderable, logical_resolution};
+use ::wayland::timer::Timer;
+
+/// Space between messages shown at the same time, in pixels.
+const SPACING: u32 = 8;
+
+lazy_static! {
+    /// The messages being shown, the oldest first.
+    static ref SHOWN: Mutex<Vec<Shown>> = Mutex::new(Vec::new());
+    /// Fires when the time of the first message to go is up.
+    static ref EXPIRY_TIMER: Mutex<Option<Timer>> = Mutex::new(None);
+    /// When the expiry timer is armed to fire, if it is.
+    static ref ARMED_UNTIL: Mutex<Option<Instant>> = Mutex::new(None);
+}
+
+/// What the message is about, which decides its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OsdKind {
+    Info,
+    Error
+}
+
+/// A message to show on the OSD.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OsdMessage {
+    /// The text, which can be several lines long.
+    pub text: String,
+    pub kind: OsdKind,
+    /// A percentage, shown as a bar under the text.
+    pub value: Option<u32>,
+    /// Messages with the same tag replace each other, e.g "volume".
+    pub tag: Option<String>,
+    /// How long it's shown for, instead of the configured timeout.
+    pub timeout: Option<Duration>
+}
+
+impl OsdMessage {
+    pub fn info(text: String) -> Self {
+        OsdMessage {
+            text: text,
+            kind: OsdKind::Info,
+            value: None,
+            tag: None,
+            timeout: None
+        }
+    }
+
+    pub fn error(text: String) -> Self {
+        OsdMessage {
+            text: text,
+            kind: OsdKind::Error,
+            value: None,
+            tag: Some("error".into()),
+            timeout: None
+        }
+    }
+}
+
+/// A message being shown, and until when.
+#[derive(Debug)]
+struct Shown {
+    message: OsdMessage,
+    until: Instant,
+    /// The box, once it has been drawn.
+    osd: Option<Osd>
+}
+
+/// Reads an option from the osd category of the registry.
+fn osd_option(key: &str) -> Option<Json> {
+    let lock = registry::clients_read();
+    let client = lock.client(Uuid::nil()).unwrap();
+    let handle = registry::ReadHandle::new(&client);
+    handle.read("osd".into()).ok()
+        .and_then(|osd| osd.get(key).cloned())
+}
+
+/// Reads a number option from the osd category, or the default if unset.
+fn number_option(key: &str, default: u32) -> u32 {
+    osd_option(key)
+        .and_then(|num| num.as_f64())
+        .map(|num| num as u32)
+        .unwrap_or(default)
+}
+
+/// Determines if messages are shown at all. Defaults to true.
+pub fn osd_enabled() -> bool {
+    osd_option("enabled")
+        .and_then(|enabled| enabled.as_boolean())
+        .unwrap_or(true)
+}
+
+/// Determines if the name of the workspace is shown when switching to it.
+/// Defaults to true.
+pub fn osd_shows_workspaces() -> bool {
+    osd_option("workspaces")
+        .and_then(|workspaces| workspaces.as_boolean())
+        .unwrap_or(true)
+}
+
+/// Shows the message over the focused output, until its timeout is up.
+pub fn show_osd(message: OsdMessage) {
+    if !osd_enabled() {
+        return
+    }
+    let timeout = message.timeout
+        .unwrap_or_else(|| Duration::from_millis(number_option("timeout", 2000) as u64));
+    if let Ok(mut shown) = SHOWN.lock() {
+        add_message(&mut *shown, message, Instant::now() + timeout);
+    }
+    // Rendering arms the timer for the message
+    for output in WlcOutput::list() {
+        output.schedule_render();
+    }
+}
+
+/// Makes the timer that takes messages away when their time is up.
+///
+/// This has to be called on the main thread, before any messages are shown.
+pub fn init_osd_timer() {
+    if let Ok(mut timer) = EXPIRY_TIMER.lock() {
+        *timer = Timer::new(expire_messages);
+    }
+}
+
+/// Makes the expiry timer fire when the time of the first message
+/// to go is up, unless it already does.
+///
+/// This has to be called on the main thread.
+fn arm_expiry_timer(shown: &[Shown]) {
+    let until = match shown.iter().map(|shown| shown.until).min() {
+        Some(until) => until,
+        None => return
+    };
+    let mut armed_until = match ARMED_UNTIL.lock() {
+        Ok(armed_until) => armed_until,
+        Err(_) => return
+    };
+    if *armed_until == Some(until) {
+        return
+    }
+    let now = Instant::now();
+    let left = if until > now { until.duration_since(now) } else { Duration::from_millis(0) };
+    // Rounded up, so the time is up when it fires
+    let delay = left.as_secs() * 1000 + left.subsec_nanos() as u64 / 1_000_000 + 1;
+    if let Ok(timer) = EXPIRY_TIMER.lock() {
+        if let Some(ref timer) = *timer {
+            timer.arm(delay as u32);
+            *armed_until = Some(until);
+        }
+    }
+}
+
+/// Takes away the messages whose time is up, rendering the outputs
+/// again without them.
+fn expire_messages() {
+    if let Ok(mut armed_until) = ARMED_UNTIL.lock() {
+        *armed_until = None;
+    }
+    let mut shown = match SHOWN.lock() {
+        Ok(shown) => shown,
+        Err(_) => return
+    };
+    let count = shown.len();
+    let now = Instant::now();
+    shown.retain(|message| message.until > now);
+    arm_expiry_timer(&*shown);
+    if shown.len() != count {
+        for output in WlcOutput::list() {
+            output.schedule_render();
+        }
+    }
+}
+
+/// Adds the message after the ones being shown,
+/// taking the place of the one with the same tag.
+fn add_message(shown: &mut Vec<Shown>, message: OsdMessage, until: Instant) {
+    if message.tag.is_some() {
+        shown.retain(|old| old.message.tag != message.tag);
+    }
+    shown.push(Shown {
+        message: message,
+        until: until,
+        osd: None
+    });
+}
+
+/// Places boxes of the sizes under each other, in the middle of the output
+/// and ending a fifth of the way up from its bottom.
+fn stack(sizes: &[Size], resolution: Size) -> Vec<Geometry> {
+    let total = sizes.iter().fold(0, |total, size| total + size.h + SPACING);
+    let mut y = (resolution.h * 4 / 5) as i32 - total as i32;
+    sizes.iter().map(|&size| {
+        let geometry = Geometry {
+            origin: Point { x: (resolution.w as i32 - size.w as i32) / 2, y: y },
+            size: size
+        };
+        y += (size.h + SPACING) as i32;
+        geometry
+    }).collect()
+}
+
+/// Renders the messages if the output is the focused one.
+pub fn render_osd(output: WlcOutput) {
+    let mut shown = match SHOWN.try_lock() {
+        Ok(shown) => shown,
+        Err(_) => return
+    };
+    arm_expiry_timer(&*shown);
+    if shown.is_empty() || output != WlcOutput::focused() {
+        return
+    }
+    let resolution = match logical_resolution(output) {
+        Some(resolution) => resolution,
+        None => return
+    };
+    let font_size = number_option("font_size", 16);
+    let sizes: Vec<Size> = shown.iter()
+        .map(|shown| Osd::size_for(shown.message.text.as_str(),
+                                   shown.message.value, font_size))
+        .collect();
+    for (shown, geometry) in shown.iter_mut().zip(stack(&sizes, resolution)) {
+        let osd = match shown.osd.take() {
+            Some(mut osd) => if osd.get_output() == output
+                && osd.get_geometry().size == geometry.size {
+                    osd.set_geometry(geometry);
+                    Some(osd)
+                } else {
+                    osd.reallocate_buffer(geometry).and_then(Osd::draw)
+                },
+            None => Osd::new(geometry, output).and_then(|mut osd| {
+                let message = &shown.message;
+                osd.text = message.text.clone();
+                osd.value = message.value;
+                osd.font_size = font_size;
+                osd.font_color = number_option("font_color", 0xffffff).into();
+                osd.background_color = match message.kind {
+                    OsdKind::Info => number_option("background_color", 0x222222),
+                    OsdKind::Error => number_option("error_color", 0xd64937)
+                }.into();
+                osd.draw()
+            })
+        };
+        if let Some(mut osd) = osd {
+            osd.render();
+            shown.osd = Some(osd);
+        }
+    }
+}
+
+impl LayoutTree {
+    /// Shows the name of the active workspace, if it isn't the one
+    /// that was active before.
+    pub fn announce_workspace_switch(&self, previous_ix: Option<NodeIndex>) {
+        let workspace_ix = self.active_ix_of(ContainerType::Workspace);
+        if previous_ix.is_none() || workspace_ix == previous_ix || !osd_shows_workspaces() {
+            return
+        }
+        if let Some(name) = workspace_ix.and_then(|ix| self.tree[ix].get_name()) {
+            let mut message = OsdMessage::info(format!("Workspace {}", name));
+            message.tag = Some("workspace".into());
+            show_osd(message);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use rustwlc::{Point, Size};
+    use super::{OsdMessage, add_message, stack};
+
+    #[test]
+    fn replacing_and_stacking() {
+        let mut shown = Vec::new();
+        let until = Instant::now() + Duration::from_secs(1);
+        let mut volume = OsdMessage::info("Volume".into());
+        volume.tag = Some("volume".into());
+        volume.value = Some(40);
+        add_message(&mut shown, volume.clone(), until);
+        add_message(&mut shown, OsdMessage::error("Oops".into()), until);
+        volume.value = Some(50);
+        add_message(&mut shown, volume.clone(), until);
+        // Untagged messages never replace each other
+        add_message(&mut shown, OsdMessage::info("1".into()), until);
+        add_message(&mut shown, OsdMessage::info("1".into()), until);
+        let messages: Vec<_> = shown.iter().map(|shown| shown.message.clone()).collect();
+        assert_eq!(messages, vec![OsdMessage::error("Oops".into()), volume,
+                                  OsdMessage::info("1".into()),
+                                  OsdMessage::info("1".into())]);
+
+        let resolution = Size { w: 1000, h: 1000 };
+        let sizes = [Size { w: 100, h: 40 }, Size { w: 300, h: 50 }];
+        let stacked = stack(&sizes, resolution);
+        assert_eq!(stacked[0].origin, Point { x: 450, y: 800 - 40 - 50 - 16 });
+        assert_eq!(stacked[1].origin, Point { x: 350, y: 800 - 50 - 8 });
+        assert_eq!(stacked[1].size, sizes[1]);
+    }
+}
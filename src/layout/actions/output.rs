@@ -0,0 +1,419 @@
+//! Settings of outputs: their resolution, position, scale factor and
+//! whether they are used at all.
+//!
+//! The settings of an output are read from the outputs category of the
+//! registry, under the name of the output (e.g "eDP-1"), and are applied
+//! when the output appears or the category changes.
+//!
+//! wlc doesn't arrange outputs next to each other, so outputs can't be
+//! positioned. Each output is placed right of the ones before it, that
+//! position orders the outputs and is reported to clients.
+
+use std::collections::BTreeMap;
+
+use petgraph::graph::NodeIndex;
+use rustc_serialize::json::{Json, ToJson};
+use rustwlc::{Point, Size, WlcOutput, WlcView};
+use uuid::Uuid;
+
+use super::super::{LayoutTree, TreeError};
+use super::super::commands::CommandResult;
+use super::super::core::container::{Container, ContainerType, Handle};
+use ::registry;
+use ::render::{output_scale, logical_resolution};
+
+/// The largest scale factor an output can be given.
+pub const MAX_SCALE: u32 = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputErr {
+    /// The scale factor was 0, or larger than `MAX_SCALE`.
+    InvalidScale(u32),
+    /// The resolution had no width or no height.
+    InvalidResolution(u32, u32),
+    /// The output is the only one in use, so it can't be disabled.
+    LastEnabled(Uuid),
+    /// The setting with the name had a value of the wrong type.
+    BadSetting(String),
+    /// Outputs can't be positioned, wlc doesn't arrange them.
+    PositionUnsupported
+}
+
+/// Reads the settings of the output with the name from the outputs category
+/// of the registry.
+pub fn output_settings(name: &str) -> Option<Json> {
+    let lock = registry::clients_read();
+    let client = lock.client(Uuid::nil()).unwrap();
+    let handle = registry::ReadHandle::new(&client);
+    handle.read("outputs".into()).ok()
+        .and_then(|outputs| outputs.get(name).cloned())
+}
+
+/// Gets the scale factor configured for the output, if there is one.
+pub fn configured_scale(output: WlcOutput) -> Option<u32> {
+    output_settings(output.get_name().as_str())
+        .and_then(|settings| settings.find("scale").and_then(Json::as_f64))
+        .and_then(|scale| match whole_scale(scale) {
+            Some(scale) if valid_scale(scale) => Some(scale),
+            _ => {
+                warn!("Ignoring scale {} of {}, it must be a whole number \
+                       between 1 and {}", scale, output.get_name(), MAX_SCALE);
+                None
+            }
+        })
+}
+
+/// Gets the scale factor as a whole number, outputs can't be
+/// given fractional scales.
+fn whole_scale(scale: f64) -> Option<u32> {
+    if scale >= 0.0 && scale.fract() == 0.0 {
+        Some(scale as u32)
+    } else {
+        None
+    }
+}
+
+/// Determines if the output can be given the scale factor.
+pub fn valid_scale(scale: u32) -> bool {
+    scale >= 1 && scale <= MAX_SCALE
+}
+
+/// Determines if the output is in use, outputs that are not are asleep.
+pub fn output_enabled(output: WlcOutput) -> bool {
+    !output.get_sleep()
+}
+
+/// Reads two numbers from a setting, e.g `{ w = 1920, h = 1080 }`.
+fn read_pair(settings: &Json, key: &str, first: &str, second: &str)
+             -> Result<Option<(f64, f64)>, TreeError> {
+    let value = match settings.find(key) {
+        Some(value) => value,
+        None => return Ok(None)
+    };
+    match (value.find(first).and_then(Json::as_f64),
+           value.find(second).and_then(Json::as_f64)) {
+        (Some(first), Some(second)) => Ok(Some((first, second))),
+        _ => Err(TreeError::Output(OutputErr::BadSetting(key.into())))
+    }
+}
+
+/// Describes a view that isn't in the tree, like a bar or a background.
+fn view_to_json(view: WlcView) -> Json {
+    let mut map = BTreeMap::new();
+    map.insert("title".into(), view.get_title().to_json());
+    map.insert("class".into(), view.get_class().to_json());
+    Json::Object(map)
+}
+
+impl LayoutTree {
+    /// Gets the wlc handle of the output behind the id.
+    pub fn output_handle(&self, id: Uuid) -> Result<(NodeIndex, WlcOutput), TreeError> {
+        let output_ix = try!(self.tree.lookup_id(id).ok_or(TreeError::NodeNotFound(id)));
+        match self.tree[output_ix].get_handle() {
+            Some(Handle::Output(output)) => Ok((output_ix, output)),
+            _ => Err(TreeError::UuidWrongType(id, vec!(ContainerType::Output)))
+        }
+    }
+
+    /// Gets the scale factor of the output behind the id.
+    pub fn output_scale(&self, id: Uuid) -> Result<u32, TreeError> {
+        self.output_handle(id).map(|(_, output)| output_scale(output))
+    }
+
+    /// Gets the resolution of the output behind the id, in physical pixels
+    /// and in the logical pixels the layout uses.
+    pub fn output_sizes(&self, id: Uuid) -> Result<(Size, Size), TreeError> {
+        let (_, output) = try!(self.output_handle(id));
+        let physical = output.get_resolution().expect("Output had no resolution");
+        let logical = logical_resolution(output).expect("Output had no resolution");
+        Ok((physical, logical))
+    }
+
+    /// Gets where the output is in the arrangement of the outputs.
+    pub fn output_position(&self, output_ix: NodeIndex) -> Point {
+        match self.tree[output_ix] {
+            Container::Output { position, .. } => position,
+            _ => panic!("Expected an output, got a {:?}", self.tree[output_ix].get_type())
+        }
+    }
+
+    /// Gets the outputs that are in use, ordered by their position
+    /// from left to right, then from top to bottom.
+    pub fn outputs_in_order(&self) -> Vec<NodeIndex> {
+        let mut outputs: Vec<NodeIndex> = self.tree.children_of(self.tree.root_ix())
+            .into_iter()
+            .filter(|output_ix| match self.tree[*output_ix].get_handle() {
+                Some(Handle::Output(output)) => output_enabled(output),
+                _ => false
+            })
+            .collect();
+        outputs.sort_by_key(|output_ix| {
+            let Point { x, y } = self.output_position(*output_ix);
+            (x, y)
+        });
+        outputs
+    }
+
+    /// Places a new output to the right of the other outputs.
+    pub fn place_new_output(&mut self, output_ix: NodeIndex) {
+        let right_edge = self.outputs_in_order().into_iter()
+            .filter(|ix| *ix != output_ix)
+            .filter_map(|ix| match self.tree[ix].get_handle() {
+                Some(Handle::Output(output)) => logical_resolution(output)
+                    .map(|size| self.output_position(ix).x + size.w as i32),
+                _ => None
+            })
+            .max()
+            .unwrap_or(0);
+        if let Container::Output { ref mut position, .. } = self.tree[output_ix] {
+            *position = Point { x: right_edge, y: 0 };
+        }
+    }
+
+    /// Sets the scale factor of the output behind the id, laying out
+    /// the output again so that everything on it is drawn at the new scale.
+    pub fn set_output_scale(&mut self, id: Uuid, scale: u32) -> CommandResult {
+        if !valid_scale(scale) {
+            return Err(TreeError::Output(OutputErr::InvalidScale(scale)))
+        }
+        let (output_ix, output) = try!(self.output_handle(id));
+        if output_scale(output) == scale {
+            return Ok(())
+        }
+        let resolution = output.get_resolution().expect("Output had no resolution");
+        output.set_resolution(resolution, scale);
+        self.layout(output_ix);
+        output.schedule_render();
+        Ok(())
+    }
+
+    /// Sets the resolution of the output behind the id, in physical pixels.
+    pub fn set_output_resolution(&mut self, id: Uuid, size: Size) -> CommandResult {
+        if size.w == 0 || size.h == 0 {
+            return Err(TreeError::Output(OutputErr::InvalidResolution(size.w, size.h)))
+        }
+        let (output_ix, output) = try!(self.output_handle(id));
+        if output.get_resolution() == Some(size) {
+            return Ok(())
+        }
+        output.set_resolution(size, output_scale(output));
+        self.layout(output_ix);
+        output.schedule_render();
+        Ok(())
+    }
+
+    /// Would set where the output behind the id is, but wlc doesn't
+    /// arrange outputs so this always fails.
+    pub fn set_output_position(&mut self, id: Uuid, _point: Point) -> CommandResult {
+        try!(self.output_handle(id));
+        Err(TreeError::Output(OutputErr::PositionUnsupported))
+    }
+
+    /// Starts or stops using the output behind the id.
+    ///
+    /// The workspaces with something on them are moved to the first
+    /// other output before the output is put to sleep. If the focus was on
+    /// the output, it moves to that other output.
+    pub fn set_output_enabled(&mut self, id: Uuid, enabled: bool) -> CommandResult {
+        let (output_ix, output) = try!(self.output_handle(id));
+        if output_enabled(output) == enabled {
+            return Ok(())
+        }
+        if enabled {
+            output.set_sleep(false);
+            self.layout(output_ix);
+            output.schedule_render();
+            return Ok(())
+        }
+        let target_ix = try!(self.outputs_in_order().into_iter()
+                             .find(|ix| *ix != output_ix)
+                             .ok_or(TreeError::Output(OutputErr::LastEnabled(id))));
+        let target_id = self.tree[target_ix].get_id();
+        let names: Vec<String> = self.tree.children_of(output_ix).into_iter()
+            .filter(|ix| self.view_count(*ix) > 0 || !self.placeholders_of(*ix).is_empty())
+            .filter_map(|ix| self.tree[ix].get_name().map(str::to_string))
+            .collect();
+        for name in names {
+            self.move_workspace_to_output(&name, target_id).unwrap_or_else(|err| {
+                warn!("Could not move workspace {} off of a disabled output: {:?}",
+                      name, err);
+            });
+        }
+        if self.active_ix_of(ContainerType::Output) == Some(output_ix) {
+            let workspace_ix = try!(self.tree.follow_path_until(target_ix, ContainerType::Workspace)
+                                    .map_err(|_| TreeError::NoActiveContainer));
+            let root_c_ix = self.tree.children_of(workspace_ix)[0];
+            self.tree.set_ancestor_paths_active(root_c_ix);
+            self.focus_on_next_container(workspace_ix);
+        }
+        output.set_sleep(true);
+        Ok(())
+    }
+
+    /// Applies settings to the output behind the id.
+    ///
+    /// The settings look like
+    /// `{ resolution = { w = 1920, h = 1080 }, position = { x = 0, y = 0 },
+    /// scale = 1, enabled = true }`, every one of them is optional.
+    pub fn configure_output(&mut self, id: Uuid, settings: &Json) -> CommandResult {
+        if !settings.is_object() {
+            return Err(TreeError::Output(OutputErr::BadSetting("settings".into())))
+        }
+        let resolution = try!(read_pair(settings, "resolution", "w", "h"));
+        let position = try!(read_pair(settings, "position", "x", "y"));
+        let scale = match settings.find("scale") {
+            Some(scale) => Some(try!(scale.as_f64().and_then(whole_scale).ok_or(
+                TreeError::Output(OutputErr::BadSetting("scale".into()))))),
+            None => None
+        };
+        let enabled = match settings.find("enabled") {
+            Some(enabled) => Some(try!(enabled.as_boolean().ok_or(
+                TreeError::Output(OutputErr::BadSetting("enabled".into()))))),
+            None => None
+        };
+        if let Some(enabled) = enabled {
+            try!(self.set_output_enabled(id, enabled));
+        }
+        if let Some((w, h)) = resolution {
+            try!(self.set_output_resolution(id, Size { w: w as u32, h: h as u32 }));
+        }
+        if let Some(scale) = scale {
+            try!(self.set_output_scale(id, scale));
+        }
+        if let Some((x, y)) = position {
+            try!(self.set_output_position(id, Point { x: x as i32, y: y as i32 }));
+        }
+        Ok(())
+    }
+
+    /// Applies the settings from the outputs category of the registry
+    /// to every output.
+    pub fn apply_output_settings(&mut self) {
+        let outputs: Vec<(Uuid, String)> = self.tree.children_of(self.tree.root_ix())
+            .into_iter()
+            .map(|output_ix| (self.tree[output_ix].get_id(), self.tree[output_ix].name()))
+            .collect();
+        for (id, name) in outputs {
+            if let Some(settings) = output_settings(name.as_str()) {
+                self.configure_output(id, &settings).unwrap_or_else(|err| {
+                    warn!("Could not apply the settings of output {}: {:?}", name, err);
+                });
+            }
+        }
+    }
+
+    /// Gets the names of the workspaces on the output, in their order.
+    pub fn output_workspaces(&self, output_ix: NodeIndex) -> Vec<String> {
+        self.tree.children_of(output_ix).into_iter()
+            .filter_map(|ix| self.tree[ix].get_name().map(str::to_string))
+            .collect()
+    }
+
+    /// Gets the name of the workspace shown on the output.
+    pub fn output_active_workspace(&self, output_ix: NodeIndex) -> Option<String> {
+        self.tree.follow_path_until(output_ix, ContainerType::Workspace).ok()
+            .and_then(|ix| self.tree[ix].get_name().map(str::to_string))
+    }
+
+    /// Determines if the output is the one with the active container on it.
+    pub fn output_focused(&self, output_ix: NodeIndex) -> bool {
+        self.active_ix_of(ContainerType::Output) == Some(output_ix)
+    }
+
+    /// Describes the output as JSON: its name, id, whether it's in use and
+    /// focused, its resolution, position and scale factor, the modes it
+    /// supports, its workspaces, its bar and background and the space
+    /// reserved along its edges.
+    ///
+    /// wlc doesn't tell us which modes an output supports,
+    /// so the current resolution is the only mode listed.
+    pub fn output_info(&self, output_ix: NodeIndex) -> Json {
+        let output = match self.tree[output_ix].get_handle() {
+            Some(Handle::Output(output)) => output,
+            _ => panic!("Expected an output, got a {:?}", self.tree[output_ix].get_type())
+        };
+        let size_to_json = |size: Size| {
+            let mut map = BTreeMap::new();
+            map.insert("w".into(), size.w.to_json());
+            map.insert("h".into(), size.h.to_json());
+            Json::Object(map)
+        };
+        let resolution = output.get_resolution().unwrap_or(Size { w: 0, h: 0 });
+        let logical = logical_resolution(output).unwrap_or(Size { w: 0, h: 0 });
+        let Point { x, y } = self.output_position(output_ix);
+        let mut position = BTreeMap::new();
+        position.insert("x".into(), x.to_json());
+        position.insert("y".into(), y.to_json());
+        let mut map = BTreeMap::new();
+        map.insert("name".into(), output.get_name().to_json());
+        map.insert("id".into(), self.tree[output_ix].get_id().simple().to_string().to_json());
+        map.insert("enabled".into(), output_enabled(output).to_json());
+        map.insert("resolution".into(), size_to_json(resolution));
+        map.insert("logical_resolution".into(), size_to_json(logical));
+        map.insert("position".into(), Json::Object(position));
+        map.insert("scale".into(), output_scale(output).to_json());
+        map.insert("modes".into(), Json::Array(vec![size_to_json(resolution)]));
+        map.insert("focused".into(), self.output_focused(output_ix).to_json());
+        map.insert("workspaces".into(), self.output_workspaces(output_ix).to_json());
+        map.insert("active_workspace".into(),
+                   self.output_active_workspace(output_ix).to_json());
+        let (bar, background) = match self.tree[output_ix] {
+            Container::Output { ref bar, background, .. } => {
+                (bar.as_ref().map(|bar| bar.view()), background)
+            },
+            _ => unreachable!()
+        };
+        map.insert("bar".into(), bar.map_or(Json::Null, view_to_json));
+        map.insert("background".into(), background.map_or(Json::Null, view_to_json));
+        let reserved = self.output_reservations(output_ix).into_iter()
+            .map(|reservation| {
+                let mut reserved = BTreeMap::new();
+                reserved.insert("id".into(), reservation.id.to_string().to_json());
+                reserved.insert("edge".into(), reservation.edge.name().to_json());
+                reserved.insert("size".into(), reservation.size.to_json());
+                reserved.insert("owner".into(),
+                                reservation.owner.map_or(Json::Null, view_to_json));
+                Json::Object(reserved)
+            })
+            .collect();
+        map.insert("reserved".into(), Json::Array(reserved));
+        Json::Object(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_serialize::json::Json;
+    use ::layout::core::tree::tests::basic_tree;
+
+    #[test]
+    fn output_workspaces() {
+        let tree = basic_tree();
+        let output_ix = tree.tree.children_of(tree.tree.root_ix())[0];
+        assert_eq!(tree.output_workspaces(output_ix), vec!["1".to_string(), "2".to_string()]);
+        assert_eq!(tree.output_active_workspace(output_ix), Some("1".to_string()));
+        assert!(tree.output_focused(output_ix));
+    }
+
+    #[test]
+    fn scale_bounds() {
+        assert!(!valid_scale(0));
+        assert!(valid_scale(1));
+        assert!(valid_scale(MAX_SCALE));
+        assert!(!valid_scale(MAX_SCALE + 1));
+        assert_eq!(whole_scale(2.0), Some(2));
+        assert_eq!(whole_scale(1.5), None);
+        assert_eq!(whole_scale(-1.0), None);
+    }
+
+    #[test]
+    fn reading_pairs() {
+        let settings = Json::from_str(r#"{"position": {"x": 1920, "y": 0},
+                                          "resolution": {"w": 1920}}"#).unwrap();
+        assert_eq!(read_pair(&settings, "position", "x", "y").unwrap(),
+                   Some((1920.0, 0.0)));
+        assert!(read_pair(&settings, "resolution", "w", "h").is_err());
+        assert_eq!(read_pair(&settings, "scale", "x", "y").unwrap(), None);
+    }
+}
@@ -1,10 +1,104 @@
-use rustwlc::{input, Point, ResizeEdge, Geometry,
+use std::sync::Mutex;
+
+use petgraph::graph::NodeIndex;
+use rustc_serialize::json::Json;
+use rustwlc::{input, Point, ResizeEdge, Geometry, WlcView,
               RESIZE_TOPLEFT, RESIZE_TOPRIGHT, RESIZE_BOTTOMLEFT, RESIZE_BOTTOMRIGHT,};
 
-use super::super::{LayoutTree, TreeError};
+use super::super::{LayoutTree, TreeError, try_lock_tree};
 use super::super::commands::{CommandResult};
+use super::super::core::container::{Container, ContainerType};
+use super::focus::FocusError;
+use ::registry;
+use ::wayland::timer::Timer;
 use uuid::Uuid;
 
+/// The view the pointer is over, for focus follows mouse.
+#[derive(Debug, Clone, Copy)]
+struct Hover {
+    view: WlcView,
+    /// Whether the view was already focused (or should not be) while
+    /// the pointer has been over it.
+    handled: bool
+}
+
+/// Where the pointer was last seen, and what it is over.
+#[derive(Debug, Clone, Copy)]
+struct PointerState {
+    last_point: Option<Point>,
+    hover: Option<Hover>
+}
+
+lazy_static! {
+    static ref POINTER: Mutex<PointerState> = Mutex::new(PointerState {
+        last_point: None,
+        hover: None
+    });
+    /// Fires when the pointer has been over a view for `focus_delay`.
+    static ref FOCUS_TIMER: Mutex<Option<Timer>> = Mutex::new(None);
+}
+
+/// Reads an option from the mouse category of the registry.
+fn mouse_option(key: &str) -> Option<Json> {
+    let lock = registry::clients_read();
+    let client = lock.client(Uuid::nil()).unwrap();
+    let handle = registry::ReadHandle::new(&client);
+    handle.read("mouse".into()).ok()
+        .and_then(|mouse| mouse.get(key).cloned())
+}
+
+/// Determines if the `focus_follows_mouse` mouse option is set.
+pub fn focus_follows_mouse() -> bool {
+    mouse_option("focus_follows_mouse")
+        .and_then(|value| value.as_boolean())
+        .unwrap_or(false)
+}
+
+/// Makes the focus timer fire in `delay` milliseconds, or stops it
+/// if there is no delay.
+fn arm_focus_timer(delay: Option<u32>) {
+    let mut timer = match FOCUS_TIMER.lock() {
+        Ok(timer) => timer,
+        Err(_) => return
+    };
+    match delay {
+        Some(delay) => {
+            if timer.is_none() {
+                *timer = Timer::new(focus_timer_fired);
+            }
+            if let Some(ref timer) = *timer {
+                timer.arm(delay)
+            }
+        },
+        None => if let Some(ref timer) = *timer {
+            timer.disarm()
+        }
+    }
+}
+
+/// Focuses on the view the pointer has stayed over for `focus_delay`.
+fn focus_timer_fired() {
+    if !focus_follows_mouse() {
+        return
+    }
+    match try_lock_tree() {
+        Ok(mut tree) => {
+            tree.0.focus_hovered_view().unwrap_or_else(|err| {
+                warn!("Could not focus on the view under the pointer: {:?}", err);
+            })
+        },
+        Err(_) => warn!("Could not lock tree to focus on the view under the pointer")
+    }
+}
+
+/// Determines if the point lies within the geometry.
+pub fn contains(geometry: Geometry, point: Point) -> bool {
+    point.x >= geometry.origin.x
+        && point.y >= geometry.origin.y
+        && point.x < geometry.origin.x + geometry.size.w as i32
+        && point.y < geometry.origin.y + geometry.size.h as i32
+}
+
 impl LayoutTree {
     /// Sets the absolute position of the cursor on the screen.
     pub fn set_pointer_pos(&mut self, point: Point) -> CommandResult {
@@ -34,4 +128,138 @@ impl LayoutTree {
         }
         Ok(origin)
     }
+
+    /// Focuses on the view under the pointer. Callers check that the
+    /// `focus_follows_mouse` mouse option is set.
+    ///
+    /// The view is only focused once the pointer has been over it for
+    /// `focus_delay` milliseconds, even if the pointer stops moving.
+    /// Views only count as entered when the pointer moved onto them,
+    /// not when the layout moved them under a pointer that stood still.
+    pub fn pointer_moved(&mut self, view: WlcView, point: Point) -> CommandResult {
+        let mut state = match POINTER.lock() {
+            Ok(state) => state,
+            Err(_) => return Ok(())
+        };
+        let last_point = state.last_point;
+        state.last_point = Some(point);
+        if view.is_root() || self.tree.descendant_with_handle(
+            self.tree.root_ix(), &view).is_none() {
+            state.hover = None;
+            drop(state);
+            arm_focus_timer(None);
+            return Ok(())
+        }
+        if last_point == Some(point) {
+            return Ok(())
+        }
+        let entered = match state.hover {
+            Some(hover) => hover.view != view,
+            None => true
+        };
+        if !entered {
+            return Ok(())
+        }
+        let came_from_outside = match (last_point, view.get_geometry()) {
+            (Some(last_point), Some(geometry)) => !contains(geometry, last_point),
+            _ => true
+        };
+        state.hover = Some(Hover {
+            view: view,
+            handled: !came_from_outside
+        });
+        drop(state);
+        if !came_from_outside {
+            arm_focus_timer(None);
+            return Ok(())
+        }
+        let delay = mouse_option("focus_delay")
+            .and_then(|value| value.as_u64())
+            .unwrap_or(0);
+        if delay > 0 {
+            arm_focus_timer(Some(delay as u32));
+            return Ok(())
+        }
+        arm_focus_timer(None);
+        self.focus_hovered_view()
+    }
+
+    /// Focuses on the view the pointer is over, unless it was focused
+    /// (or should not be) since the pointer entered it.
+    fn focus_hovered_view(&mut self) -> CommandResult {
+        let view = {
+            let mut state = match POINTER.lock() {
+                Ok(state) => state,
+                Err(_) => return Ok(())
+            };
+            let mut hover = match state.hover {
+                Some(hover) if !hover.handled => hover,
+                _ => return Ok(())
+            };
+            hover.handled = true;
+            state.hover = Some(hover);
+            hover.view
+        };
+        if self.tree.descendant_with_handle(self.tree.root_ix(), &view).is_none() {
+            return Ok(())
+        }
+        let active_view = self.active_container.and_then(|active_ix| {
+            match self.tree[active_ix] {
+                Container::View { handle, .. } => Some(handle),
+                _ => None
+            }
+        });
+        if active_view == Some(view) {
+            return Ok(())
+        }
+        match self.set_active_view(view) {
+            Err(TreeError::Focus(FocusError::BlockedByFullscreen(_, _))) => Ok(()),
+            result => result
+        }
+    }
+
+    /// Moves the pointer to the center of the active view, if the
+    /// `warp_to_focus` mouse option is set and the pointer isn't on it already.
+    pub fn warp_pointer_to_active(&mut self) {
+        let enabled = mouse_option("warp_to_focus")
+            .and_then(|value| value.as_boolean())
+            .unwrap_or(false);
+        if !enabled {
+            return
+        }
+        let view = match self.active_ix_of(ContainerType::View)
+            .map(|active_ix| &self.tree[active_ix]) {
+            Some(&Container::View { handle, .. }) => handle,
+            _ => return
+        };
+        let geometry = match view.get_geometry() {
+            Some(geometry) => geometry,
+            None => return
+        };
+        if contains(geometry, input::pointer::get_position()) {
+            return
+        }
+        let center = Point {
+            x: geometry.origin.x + geometry.size.w as i32 / 2,
+            y: geometry.origin.y + geometry.size.h as i32 / 2
+        };
+        input::pointer::set_position(center);
+        // Don't let focus follows mouse act on the warp
+        if let Ok(mut state) = POINTER.lock() {
+            state.last_point = Some(center);
+            state.hover = Some(Hover {
+                view: view,
+                handled: true
+            });
+        }
+        arm_focus_timer(None);
+    }
+
+    /// Warps the pointer to the active view if the focus moved
+    /// away from the output.
+    pub fn warp_pointer_if_output_changed(&mut self, previous_output: Option<NodeIndex>) {
+        if self.active_ix_of(ContainerType::Output) != previous_output {
+            self.warp_pointer_to_active();
+        }
+    }
 }
@@ -0,0 +1,238 @@
+//! Reserving space along the edges of outputs, for docks and panels.
+//!
+//! Space can be reserved by clients over D-Bus and Lua, or for a view by the
+//! `reserve_edge` option of a window rule. A view a rule reserves space for
+//! is a panel: it isn't tiled, but placed in the space reserved for it, and
+//! the space is released when the view closes. Unless the rule sets how
+//! much space to reserve, the space follows the size the panel asks for.
+
+use petgraph::graph::NodeIndex;
+use rustwlc::{Geometry, ResizeEdge, Size, WlcView};
+use uuid::Uuid;
+
+use super::super::{LayoutTree, TreeError};
+use super::super::commands::CommandResult;
+use super::super::core::container::Container;
+use super::super::core::reservation::{self, Edge, Reservation};
+use super::super::core::rules;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReservationErr {
+    /// No output had a reservation with the id.
+    NotFound(Uuid),
+    /// The name was not the name of an edge.
+    BadEdge(String)
+}
+
+impl LayoutTree {
+    /// Gets the space reserved along the edges of the output,
+    /// in the order it was reserved.
+    pub fn output_reservations(&self, output_ix: NodeIndex) -> Vec<Reservation> {
+        match self.tree[output_ix] {
+            Container::Output { ref reserved, .. } => reserved.clone(),
+            _ => panic!("Expected an output, got a {:?}", self.tree[output_ix].get_type())
+        }
+    }
+
+    /// Reserves space along the edge of the output behind the id,
+    /// returning the id of the reservation.
+    ///
+    /// If the space is reserved for a view, the view is placed in it.
+    pub fn reserve_edge(&mut self, output_id: Uuid, edge: Edge, size: u32,
+                        owner: Option<WlcView>) -> Result<Uuid, TreeError> {
+        let (output_ix, _) = try!(self.output_handle(output_id));
+        let new = Reservation::new(edge, size, owner);
+        let id = new.id;
+        match self.tree[output_ix] {
+            Container::Output { ref mut reserved, .. } => reserved.push(new),
+            _ => unreachable!()
+        }
+        self.layout(output_ix);
+        Ok(id)
+    }
+
+    /// Releases the reserved space with the id, on whichever output it is.
+    pub fn release_reservation(&mut self, id: Uuid) -> CommandResult {
+        for output_ix in self.tree.children_of(self.tree.root_ix()) {
+            let released = match self.tree[output_ix] {
+                Container::Output { ref mut reserved, .. } => {
+                    let count = reserved.len();
+                    reserved.retain(|reservation| reservation.id != id);
+                    reserved.len() != count
+                },
+                _ => false
+            };
+            if released {
+                self.layout(output_ix);
+                return Ok(())
+            }
+        }
+        Err(TreeError::Reservation(ReservationErr::NotFound(id)))
+    }
+
+    /// Releases all the space reserved for the view,
+    /// returning whether there was any.
+    pub fn release_view_reservations(&mut self, view: WlcView) -> bool {
+        let mut released = false;
+        for output_ix in self.tree.children_of(self.tree.root_ix()) {
+            let changed = match self.tree[output_ix] {
+                Container::Output { ref mut reserved, .. } => {
+                    let count = reserved.len();
+                    reserved.retain(|reservation| reservation.owner != Some(view));
+                    reserved.len() != count
+                },
+                _ => false
+            };
+            if changed {
+                self.layout(output_ix);
+                released = true;
+            }
+        }
+        released
+    }
+
+    /// Moves the views that space is reserved for on the output
+    /// into their space.
+    pub fn place_panels(&self, output_ix: NodeIndex) {
+        let geometry = match self.tree[output_ix].unreserved_geometry() {
+            Some(geometry) => geometry,
+            None => return
+        };
+        let reserved = self.output_reservations(output_ix);
+        for reservation in &reserved {
+            if let Some(view) = reservation.owner {
+                if let Some(space) = reservation::reserved_space(geometry, &reserved,
+                                                                 reservation.id) {
+                    view.set_geometry(ResizeEdge::empty(), space);
+                }
+            }
+        }
+    }
+
+    /// Reserves space for the view on its output, if a rule that matches
+    /// the view has the `reserve_edge` option. Returns whether it did.
+    ///
+    /// The `reserve_size` option sets how much space, otherwise the
+    /// height or width of the view is used. That is often 0 until the view
+    /// asks for a size, see `resize_panel`.
+    pub fn add_panel(&mut self, view: WlcView) -> Result<bool, TreeError> {
+        let options = rules::options_for(view);
+        let edge = match options.get("reserve_edge").and_then(|edge| edge.as_string()) {
+            Some(name) => try!(Edge::from_name(name).ok_or_else(|| {
+                TreeError::Reservation(ReservationErr::BadEdge(name.to_string()))
+            })),
+            None => return Ok(false)
+        };
+        let Size { w, h } = view.get_geometry()
+            .map(|geometry| geometry.size)
+            .unwrap_or(Size { w: 0, h: 0 });
+        let size = options.get("reserve_size")
+            .and_then(|size| size.as_f64())
+            .map(|size| size as u32)
+            .unwrap_or(match edge {
+                Edge::Top | Edge::Bottom => h,
+                Edge::Left | Edge::Right => w
+            });
+        let output = view.get_output();
+        let output_id = try!(self.tree.children_of(self.tree.root_ix()).into_iter()
+            .find(|output_ix| match self.tree[*output_ix] {
+                Container::Output { handle, .. } => handle == output,
+                _ => false
+            })
+            .map(|output_ix| self.tree[output_ix].get_id())
+            .ok_or(TreeError::OutputNotFound(output)));
+        try!(self.reserve_edge(output_id, edge, size, Some(view)));
+        Ok(true)
+    }
+
+    /// Fits the space reserved for the view to the geometry it asked for,
+    /// unless the `reserve_size` option of its rule fixed the size.
+    /// Returns whether the view is a panel.
+    pub fn resize_panel(&mut self, view: WlcView, geometry: Geometry) -> bool {
+        let output_ixs: Vec<NodeIndex> = self.tree.children_of(self.tree.root_ix())
+            .into_iter()
+            .filter(|output_ix| self.output_reservations(*output_ix).iter()
+                    .any(|reservation| reservation.owner == Some(view)))
+            .collect();
+        if output_ixs.is_empty() {
+            return false
+        }
+        if rules::options_for(view).get("reserve_size").is_some() {
+            return true
+        }
+        for output_ix in output_ixs {
+            let changed = match self.tree[output_ix] {
+                Container::Output { ref mut reserved, .. } => {
+                    let mut changed = false;
+                    for reservation in reserved.iter_mut()
+                        .filter(|reservation| reservation.owner == Some(view)) {
+                        let size = match reservation.edge {
+                            Edge::Top | Edge::Bottom => geometry.size.h,
+                            Edge::Left | Edge::Right => geometry.size.w
+                        };
+                        changed |= reservation.size != size;
+                        reservation.size = size;
+                    }
+                    changed
+                },
+                _ => false
+            };
+            if changed {
+                self.layout(output_ix);
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::layout::core::tree::tests::basic_tree;
+    use ::layout::core::reservation::{self, Edge};
+    use ::layout::core::container::ContainerType;
+    use rustwlc::{Geometry, Point, Size, WlcView};
+    use super::super::super::TreeError;
+    use super::ReservationErr;
+
+    #[test]
+    fn reserving_and_releasing() {
+        let mut tree = basic_tree();
+        let output_ix = tree.active_ix_of(ContainerType::Output).unwrap();
+        let output_id = tree.tree[output_ix].get_id();
+        let before = tree.tree[output_ix].get_geometry().unwrap();
+        let top = tree.reserve_edge(output_id, Edge::Top, 20, None).unwrap();
+        let left = tree.reserve_edge(output_id, Edge::Left, 30, None).unwrap();
+        let reserved = tree.output_reservations(output_ix);
+        assert_eq!(reserved.iter().map(|reservation| reservation.id).collect::<Vec<_>>(),
+                   vec![top, left]);
+        assert_eq!(tree.tree[output_ix].get_geometry().unwrap(),
+                   reservation::shrink(before, &reserved));
+        assert!(tree.release_reservation(top).is_ok());
+        assert_eq!(tree.release_reservation(top),
+                   Err(TreeError::Reservation(ReservationErr::NotFound(top))));
+        assert!(tree.release_reservation(left).is_ok());
+        assert_eq!(tree.tree[output_ix].get_geometry().unwrap(), before);
+        assert!(tree.output_reservations(output_ix).is_empty());
+    }
+
+    #[test]
+    fn panel_reservation_follows_its_size() {
+        let mut tree = basic_tree();
+        let output_ix = tree.active_ix_of(ContainerType::Output).unwrap();
+        let output_id = tree.tree[output_ix].get_id();
+        let before = tree.tree[output_ix].get_geometry().unwrap();
+        let panel = WlcView::dummy(10);
+        tree.reserve_edge(output_id, Edge::Top, 0, Some(panel)).unwrap();
+        let requested = Geometry {
+            origin: Point { x: 0, y: 0 },
+            size: Size { w: 500, h: 25 }
+        };
+        assert!(!tree.resize_panel(WlcView::root(), requested));
+        assert!(tree.resize_panel(panel, requested));
+        let reserved = tree.output_reservations(output_ix);
+        assert_eq!(reserved[0].size, 25);
+        assert_eq!(tree.tree[output_ix].get_geometry().unwrap(),
+                   reservation::shrink(before, &reserved));
+        assert!(tree.release_view_reservations(panel));
+    }
+}
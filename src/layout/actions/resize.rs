@@ -3,7 +3,7 @@ use rustwlc::{Point, ResizeEdge, Geometry,
 
 use super::super::{Action, Direction, LayoutTree, TreeError};
 use super::super::commands::{CommandResult};
-use super::super::core::container::{ContainerType, MIN_SIZE};
+use super::super::core::container::{ContainerType, SizeLimits};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -30,8 +30,9 @@ impl LayoutTree {
             }
             let geo = container.get_actual_geometry()
                 .expect("Could not get geometry of the container");
+            let limits = container.size_limits();
 
-            let new_geo = calculate_resize(geo, edge, pointer, action.grab);
+            let new_geo = calculate_resize(geo, limits, edge, pointer, action.grab);
             container.set_geometry(edge, new_geo);
             container.resize_borders(new_geo);
             container.draw_borders();
@@ -51,6 +52,9 @@ impl LayoutTree {
             .flat_map(|result| result.into_iter())
             .collect();
         for ancestor_id in next_containers.iter().map(|uuids| uuids.0) {
+            let limits = try!(self.tree.lookup_id(ancestor_id)
+                              .map(|node_ix| self.size_limits_of(node_ix))
+                              .ok_or(TreeError::NodeNotFound(ancestor_id)));
             let container = try!(self.lookup(ancestor_id));
             if container.floating() {
                 return Err(TreeError::Resize(ResizeErr::ExpectedNotFloating(ancestor_id)))
@@ -62,10 +66,10 @@ impl LayoutTree {
             }
             let geo = container.get_geometry()
                 .expect("Could not get geometry of the container");
-            if geometry_resize_too_small(geo, edge, pointer, action.grab) {
+            if geometry_resize_too_small(geo, limits, edge, pointer, action.grab) {
                 return Ok(pointer)
             }
-            let new_geo = calculate_resize(geo, edge, pointer, action.grab);
+            let new_geo = calculate_resize(geo, limits, edge, pointer, action.grab);
             resizing_ops.push((ancestor_id, (edge, new_geo)));
         }
         let siblings: Vec<Uuid> = next_containers.into_iter()
@@ -75,6 +79,9 @@ impl LayoutTree {
             .map(|dir| dir.reverse()).collect();
         let reversed_edge = Direction::to_edge(reversed_dir.as_slice());
         for sibling in siblings {
+            let limits = try!(self.tree.lookup_id(sibling)
+                              .map(|node_ix| self.size_limits_of(node_ix))
+                              .ok_or(TreeError::NodeNotFound(sibling)));
             let container = try!(self.lookup_mut(sibling));
             if container.floating() {
                 return Err(TreeError::Resize(ResizeErr::ExpectedNotFloating(container.get_id())))
@@ -86,11 +93,12 @@ impl LayoutTree {
             }
             let geo = container.get_geometry()
                 .expect("Could not get geometry of the container");
-            if geometry_resize_too_small(geo, reversed_edge, pointer, action.grab) {
+            if geometry_resize_too_small(geo, limits, reversed_edge, pointer, action.grab) {
                 return Ok(pointer)
             }
-            let new_geo = calculate_resize(geo, reversed_edge, pointer, action.grab);
-            if new_geo.size.w <= MIN_SIZE.w || new_geo.size.h <= MIN_SIZE.h {
+            let new_geo = calculate_resize(geo, limits, reversed_edge, pointer, action.grab);
+            if new_geo.size.w <= limits.min.w || new_geo.size.h <= limits.min.h
+                || new_geo.size.w >= limits.max.w || new_geo.size.h >= limits.max.h {
                 return Ok(pointer)
             }
             resizing_ops.push((sibling, (reversed_edge, new_geo)));
@@ -109,9 +117,10 @@ impl LayoutTree {
 }
 
 /// Calculates what the new geometry is of a window.
-/// Needs the geometry of the window, the edge direction the pointer is moving in,
-/// the current position of the pointer, and the previous place the pointer was at.
-fn calculate_resize(geo: Geometry, edge: ResizeEdge,
+/// Needs the geometry of the window, the limits on its size, the edge direction
+/// the pointer is moving in, the current position of the pointer,
+/// and the previous place the pointer was at.
+fn calculate_resize(geo: Geometry, limits: SizeLimits, edge: ResizeEdge,
                     cur_pointer: Point, prev_pointer: Point) -> Geometry {
     let mut new_geo = geo.clone();
     let dx = cur_pointer.x - prev_pointer.x;
@@ -148,14 +157,15 @@ fn calculate_resize(geo: Geometry, edge: ResizeEdge,
         }
     }
 
-    if new_geo.size.w < MIN_SIZE.w {
+    let clamped_size = limits.clamp(new_geo.size);
+    if clamped_size.w != new_geo.size.w {
         new_geo.origin.x = geo.origin.x;
-        new_geo.size.w = MIN_SIZE.w;
+        new_geo.size.w = clamped_size.w;
     }
 
-    if new_geo.size.h < MIN_SIZE.h {
+    if clamped_size.h != new_geo.size.h {
         new_geo.origin.y = geo.origin.y;
-        new_geo.size.h = MIN_SIZE.h;
+        new_geo.size.h = clamped_size.h;
     }
     new_geo
 }
@@ -165,9 +175,9 @@ fn calculate_resize(geo: Geometry, edge: ResizeEdge,
 /// the it returns true (to indicate you should abandon all operations).
 ///
 /// Otherwise returns false
-fn geometry_resize_too_small(geo: Geometry, edge: ResizeEdge, cur_point: Point,
-                       prev_point: Point) -> bool {
-    if geo.size.w > MIN_SIZE.w && geo.size.h > MIN_SIZE.h {
+fn geometry_resize_too_small(geo: Geometry, limits: SizeLimits, edge: ResizeEdge,
+                             cur_point: Point, prev_point: Point) -> bool {
+    if geo.size.w > limits.min.w && geo.size.h > limits.min.h {
         return false
     }
     if edge.contains(RESIZE_RIGHT) && cur_point.x - prev_point.x < 0 {
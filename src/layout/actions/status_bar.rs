@@ -0,0 +1,201 @@
+//! Keeps the status bar of each output up to date with the tree.
+//!
+//! The bars are drawn by `render::StatusBar`, and are only drawn again
+//! when what they show changes. Whatever changes the tree, the bar category
+//! of the registry or the blocks marks the bars outdated with
+//! `mark_bars_outdated`, until then rendering doesn't look at the tree.
+//! Text blocks can be set in the bar category of the registry, or by
+//! clients over D-Bus.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use petgraph::graph::NodeIndex;
+use rustwlc::{Point, WlcOutput};
+
+use super::super::LayoutTree;
+use super::super::core::container::{Container, ContainerType, Handle};
+use super::workspace::WorkspacePolicy;
+use ::render::{BarContent, Renderable, StatusBar, WorkspaceTag};
+
+lazy_static! {
+    /// The text blocks set over D-Bus, by their names.
+    static ref BLOCKS: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+    /// The outputs whose bars show what they should.
+    static ref UP_TO_DATE: Mutex<Vec<WlcOutput>> = Mutex::new(Vec::new());
+}
+
+/// Sets the text of the block with the name, adding it if it's new.
+pub fn set_bar_block(name: String, text: String) {
+    BLOCKS.lock().expect("Could not lock the bar blocks").insert(name, text);
+    mark_bars_outdated();
+}
+
+/// Removes the block with the name, returning whether it was there.
+pub fn remove_bar_block(name: &str) -> bool {
+    let removed = BLOCKS.lock().expect("Could not lock the bar blocks")
+        .remove(name).is_some();
+    if removed {
+        mark_bars_outdated();
+    }
+    removed
+}
+
+/// Makes every bar be updated the next time its output is rendered,
+/// and schedules that render.
+///
+/// Outputs whose bars are already outdated have a render coming,
+/// so this is cheap to call many times in a row.
+pub fn mark_bars_outdated() {
+    let outputs: Vec<WlcOutput> = match UP_TO_DATE.lock() {
+        Ok(mut up_to_date) => up_to_date.drain(..).collect(),
+        Err(_) => return
+    };
+    for output in outputs {
+        output.schedule_render();
+    }
+}
+
+/// Determines if the bar of the output has to be updated.
+pub fn bar_outdated(output: WlcOutput) -> bool {
+    UP_TO_DATE.lock()
+        .map(|up_to_date| !up_to_date.contains(&output))
+        .unwrap_or(true)
+}
+
+/// Notes that the bar of the output shows what it should.
+fn mark_bar_up_to_date(output: WlcOutput) {
+    if let Ok(mut up_to_date) = UP_TO_DATE.lock() {
+        if !up_to_date.contains(&output) {
+            up_to_date.push(output);
+        }
+    }
+}
+
+/// Gets the text of every block, ordered by their names.
+///
+/// A block set over D-Bus takes the place of the one with the same name
+/// in the registry.
+pub fn bar_blocks() -> Vec<String> {
+    let mut blocks: BTreeMap<String, String> = StatusBar::configured_blocks()
+        .into_iter().collect();
+    if let Ok(set) = BLOCKS.lock() {
+        blocks.extend(set.iter().map(|(name, text)| (name.clone(), text.clone())));
+    }
+    blocks.into_iter().map(|(_, text)| text).collect()
+}
+
+/// Gets the name of the workspace on the bar under the point,
+/// if the point is on the bar of the output.
+pub fn bar_workspace_at(bars: &[StatusBar], output: WlcOutput, point: Point)
+                        -> Option<String> {
+    bars.iter()
+        .find(|bar| bar.get_output() == output)
+        .and_then(|bar| bar.workspace_at(point))
+}
+
+impl LayoutTree {
+    /// Gets the state of the workspaces shown on the bar of the output.
+    ///
+    /// When every output has its own workspaces only those are shown,
+    /// otherwise the workspaces of every output are.
+    pub fn workspace_tags(&self, output_ix: NodeIndex, policy: WorkspacePolicy)
+                          -> Vec<WorkspaceTag> {
+        let active_workspace = self.active_ix_of(ContainerType::Workspace);
+        let outputs = match policy {
+            WorkspacePolicy::PerOutput => vec![output_ix],
+            WorkspacePolicy::Global => self.tree.children_of(self.tree.root_ix())
+        };
+        outputs.into_iter()
+            .flat_map(|output_ix| self.tree.children_of(output_ix))
+            .filter_map(|workspace_ix| {
+                self.tree[workspace_ix].get_name().map(|name| WorkspaceTag {
+                    name: name.to_string(),
+                    focused: active_workspace == Some(workspace_ix),
+                    visible: self.workspace_visible(workspace_ix),
+                    urgent: self.workspace_urgent(workspace_ix)
+                })
+            })
+            .collect()
+    }
+
+    /// Gets the title of the focused view, if it's on the output.
+    pub fn bar_title(&self, output_ix: NodeIndex) -> String {
+        match self.active_container {
+            Some(active_ix) if self.tree.ancestor_of_type(active_ix, ContainerType::Output)
+                .ok() == Some(output_ix) => match self.tree[active_ix] {
+                    Container::View { handle, .. } => Container::get_title(handle),
+                    _ => String::new()
+                },
+            _ => String::new()
+        }
+    }
+
+    /// Draws the bar of the output again if what it shows changed,
+    /// or removes it if the bar was turned off.
+    pub fn update_status_bar(&self, bars: &mut Vec<StatusBar>, output: WlcOutput) {
+        // Before reading anything, so changes made meanwhile aren't lost
+        mark_bar_up_to_date(output);
+        let output_ix = self.tree.children_of(self.tree.root_ix()).into_iter()
+            .find(|output_ix| self.tree[*output_ix].get_handle() == Some(Handle::Output(output)));
+        let (output_ix, bar_g) = match (output_ix, StatusBar::reserved(output)) {
+            (Some(output_ix), Some(bar_g)) => (output_ix, bar_g),
+            _ => {
+                bars.retain(|bar| bar.get_output() != output);
+                return
+            }
+        };
+        let content = BarContent {
+            workspaces: self.workspace_tags(output_ix, WorkspacePolicy::current()),
+            title: self.bar_title(output_ix),
+            blocks: bar_blocks()
+        };
+        let colors = StatusBar::configured_colors();
+        let index = bars.iter().position(|bar| bar.get_output() == output);
+        if let Some(index) = index {
+            let bar = &bars[index];
+            if bar.content == content && bar.colors == colors
+                && bar.get_geometry() == bar_g {
+                return
+            }
+        }
+        let old = index.map(|index| bars.remove(index));
+        let bar = match old {
+            Some(bar) => bar.reallocate_buffer(bar_g),
+            None => StatusBar::new(bar_g, output)
+        };
+        let bar = bar.and_then(|mut bar| {
+            bar.content = content;
+            bar.colors = colors;
+            bar.draw()
+        });
+        if let Some(bar) = bar {
+            bars.push(bar);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::layout::core::tree::tests::basic_tree;
+    use super::super::workspace::WorkspacePolicy;
+
+    #[test]
+    fn workspace_tags() {
+        let tree = basic_tree();
+        let output_ix = tree.tree.children_of(tree.tree.root_ix())[0];
+        let tags = tree.workspace_tags(output_ix, WorkspacePolicy::Global);
+        let names: Vec<&str> = tags.iter().map(|tag| tag.name.as_str()).collect();
+        assert!(names.contains(&"1"));
+        assert!(names.contains(&"2"));
+        // Only the active workspace is focused, and it's shown
+        let focused: Vec<_> = tags.iter().filter(|tag| tag.focused).collect();
+        assert_eq!(focused.len(), 1);
+        assert!(focused[0].visible);
+        assert_eq!(tree.workspace_tags(output_ix, WorkspacePolicy::PerOutput),
+                   tree.workspace_tags(output_ix, WorkspacePolicy::Global)
+                   .into_iter().filter(|tag| {
+                       tree.output_workspaces(output_ix).contains(&tag.name)
+                   }).collect::<Vec<_>>());
+    }
+}
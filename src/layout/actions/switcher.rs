@@ -0,0 +1,221 @@
+//! The window switcher, a list of every view drawn in the middle of the
+//! active output.
+//!
+//! The views are listed with the most recently focused first. Pressing the
+//! switcher key again moves the selection down the list, and letting go of
+//! the modifier focuses on the selected view. Typing narrows the list down
+//! to the views whose title, class or workspace contain the typed text.
+
+use rustwlc::{KeyMod, WlcOutput};
+use uuid::Uuid;
+
+use super::super::{LayoutTree, TreeError};
+use super::super::core::container::{Container, ContainerType, Handle};
+use ::render::{Menu, Renderable};
+
+/// The size of the font of the switcher, in pixels.
+const SWITCHER_FONT_SIZE: u32 = 16;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SwitcherErr {
+    /// There were no views to switch between.
+    NoViews
+}
+
+/// A view listed in the switcher.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwitcherEntry {
+    pub id: Uuid,
+    pub title: String,
+    pub class: String,
+    pub workspace: String
+}
+
+impl SwitcherEntry {
+    /// Determines if the title, class or workspace of the view
+    /// contain the filter, ignoring case.
+    pub fn matches(&self, filter: &str) -> bool {
+        let filter = filter.to_lowercase();
+        self.title.to_lowercase().contains(&filter)
+            || self.class.to_lowercase().contains(&filter)
+            || self.workspace.to_lowercase().contains(&filter)
+    }
+
+    /// The line of text the view is shown as in the switcher.
+    pub fn line(&self) -> String {
+        format!("{}  ({})  [{}]", self.title, self.class, self.workspace)
+    }
+}
+
+/// The state of the switcher, while it is shown.
+#[derive(Debug)]
+pub struct Switcher {
+    entries: Vec<SwitcherEntry>,
+    filter: String,
+    /// Index into the entries that match the filter.
+    selected: usize,
+    /// The modifiers that were held when the switcher was opened.
+    held: Option<KeyMod>,
+    output: Option<WlcOutput>,
+    menu: Option<Menu>
+}
+
+impl Switcher {
+    /// Gets the views that match the filter, in the order they are listed.
+    pub fn matches(&self) -> Vec<&SwitcherEntry> {
+        self.entries.iter()
+            .filter(|entry| entry.matches(self.filter.as_str()))
+            .collect()
+    }
+
+    /// Moves the selection down the list, or up it if not forward.
+    /// Wraps around at either end.
+    pub fn select_next(&mut self, forward: bool) {
+        let count = self.matches().len();
+        if count == 0 {
+            return
+        }
+        self.selected = if forward {
+            (self.selected + 1) % count
+        } else {
+            (self.selected + count - 1) % count
+        };
+        self.redraw();
+    }
+
+    /// Adds a character to the filter, selecting the first view that matches.
+    pub fn type_char(&mut self, letter: char) {
+        self.filter.push(letter);
+        self.selected = 0;
+        self.redraw();
+    }
+
+    /// Removes the last character of the filter.
+    pub fn backspace(&mut self) {
+        self.filter.pop();
+        self.selected = 0;
+        self.redraw();
+    }
+
+    /// Gets the id of the selected view, if any view matches the filter.
+    pub fn chosen(&self) -> Option<Uuid> {
+        self.matches().get(self.selected).map(|entry| entry.id)
+    }
+
+    /// Gets the modifiers that were held when the switcher was opened,
+    /// if they are known yet.
+    pub fn held(&self) -> Option<KeyMod> {
+        self.held
+    }
+
+    /// Sets the modifiers that keep the switcher open while they are held.
+    pub fn set_held(&mut self, held: KeyMod) {
+        self.held = Some(held);
+    }
+
+    /// Draws the list again, after the selection or the filter changed.
+    fn redraw(&mut self) {
+        let output = match self.output {
+            Some(output) => output,
+            None => return
+        };
+        let lines: Vec<String> = self.matches().iter().map(|entry| entry.line()).collect();
+        let heading = if self.filter.is_empty() {
+            "Switch to:".to_string()
+        } else {
+            format!("Switch to: {}", self.filter)
+        };
+        let selected = if lines.is_empty() { None } else { Some(self.selected) };
+        self.menu = Menu::centered(heading.as_str(), lines, selected,
+                                   SWITCHER_FONT_SIZE, output)
+            .and_then(|menu| menu.draw());
+    }
+
+    /// Renders the switcher, if it is shown on the output.
+    pub fn render(&mut self, output: WlcOutput) {
+        if let Some(ref mut menu) = self.menu {
+            if menu.get_output() == output {
+                menu.render();
+            }
+        }
+    }
+}
+
+impl LayoutTree {
+    /// Lists every view, on every workspace, to open the switcher.
+    ///
+    /// The view that was focused before the active one is selected,
+    /// so that opening and closing the switcher flips between the two.
+    pub fn start_switcher(&self) -> Result<Switcher, TreeError> {
+        let entries: Vec<SwitcherEntry> = self.views_by_recent_focus().into_iter()
+            .filter_map(|view_ix| {
+                let handle = match self.tree[view_ix] {
+                    Container::View { handle, .. } => handle,
+                    _ => return None
+                };
+                let workspace = self.tree.ancestor_of_type(view_ix, ContainerType::Workspace)
+                    .ok()
+                    .and_then(|ix| self.tree[ix].get_name().map(str::to_string))
+                    .unwrap_or_default();
+                Some(SwitcherEntry {
+                    id: self.tree[view_ix].get_id(),
+                    title: Container::get_title(handle),
+                    class: handle.get_class(),
+                    workspace: workspace
+                })
+            }).collect();
+        if entries.is_empty() {
+            return Err(TreeError::Switcher(SwitcherErr::NoViews))
+        }
+        let output = self.active_ix_of(ContainerType::Output)
+            .and_then(|output_ix| match self.tree[output_ix].get_handle() {
+                Some(Handle::Output(output)) => Some(output),
+                _ => None
+            });
+        let selected = if entries.len() > 1 { 1 } else { 0 };
+        let mut switcher = Switcher {
+            entries: entries,
+            filter: String::new(),
+            selected: selected,
+            held: None,
+            output: output,
+            menu: None
+        };
+        switcher.redraw();
+        Ok(switcher)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::layout::core::tree::tests::basic_tree;
+
+    #[test]
+    fn filtering_and_cycling() {
+        let tree = basic_tree();
+        let mut switcher = tree.start_switcher().unwrap();
+        let count = switcher.matches().len();
+        assert!(count > 1);
+        // The active view comes first, the one after it is selected
+        let active_id = tree.tree[tree.active_container.unwrap()].get_id();
+        assert_eq!(switcher.matches()[0].id, active_id);
+        assert_eq!(switcher.chosen(), Some(switcher.matches()[1].id));
+        switcher.select_next(false);
+        assert_eq!(switcher.chosen(), Some(active_id));
+        switcher.select_next(false);
+        assert_eq!(switcher.chosen(), Some(switcher.matches()[count - 1].id));
+        switcher.select_next(true);
+        assert_eq!(switcher.chosen(), Some(active_id));
+        // Every view in the basic tree is on workspace 1 or 2
+        switcher.type_char('2');
+        assert!(switcher.matches().len() > 0);
+        assert!(switcher.matches().iter().all(|entry| entry.workspace == "2"));
+        switcher.type_char('x');
+        assert!(switcher.matches().is_empty());
+        assert_eq!(switcher.chosen(), None);
+        switcher.backspace();
+        switcher.backspace();
+        assert!(switcher.filter.is_empty());
+        assert_eq!(switcher.matches().len(), count);
+    }
+}
@@ -0,0 +1,304 @@
+//! Layout templates, which lay out a workspace before its views are opened.
+//!
+//! A template is a JSON object nested like the containers of the tree:
+//!
+//! ```json
+//! { "layout": "horizontal",
+//!   "children": [
+//!     { "class": "Firefox", "size": 0.6 },
+//!     { "layout": "vertical", "size": 0.4,
+//!       "children": [ { "title": "vim" }, { "app_id": "weston-terminal" } ] }
+//!   ]
+//! }
+//! ```
+//!
+//! The top level object describes the root container of the workspace.
+//! Objects with `children` become containers, objects with criteria
+//! (`class`, `title`, `app_id`, matched like window rules) become
+//! placeholders. A placeholder is an empty container that is filled by the
+//! first view that matches its criteria, instead of that view being added
+//! next to the active container. `size` is the share of the parent's space
+//! a node takes up, nodes without one share evenly.
+
+use petgraph::graph::NodeIndex;
+use rustc_serialize::json::Json;
+use rustwlc::{Geometry, WlcView};
+
+use super::super::{LayoutTree, TreeError};
+use super::super::commands::CommandResult;
+use super::super::core::container::{Container, ContainerType, Layout};
+use super::super::core::rules::Criteria;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TemplateErr {
+    /// A node of the template was not a JSON object.
+    NotAnObject,
+    /// The layout of a node was not "horizontal" or "vertical".
+    UnknownLayout(String),
+    /// The size of a node was not a positive number.
+    InvalidSize,
+    /// A node had neither children nor criteria.
+    EmptyContainer,
+    /// A node had both children and criteria.
+    PlaceholderWithChildren,
+    /// The top level node had criteria, but it describes the root container.
+    RootPlaceholder
+}
+
+/// A node of a parsed layout template.
+#[derive(Debug, Clone, PartialEq)]
+struct TemplateNode {
+    layout: Layout,
+    size: Option<f64>,
+    criteria: Option<Criteria>,
+    children: Vec<TemplateNode>
+}
+
+impl TemplateNode {
+    /// Parses a node of the template, and all of its children.
+    fn parse(json: &Json) -> Result<Self, TemplateErr> {
+        let object = try!(json.as_object().ok_or(TemplateErr::NotAnObject));
+        let layout = match object.get("layout").map(|layout| layout.as_string()) {
+            None => Layout::Horizontal,
+            Some(Some(layout)) if layout.eq_ignore_ascii_case("horizontal") => Layout::Horizontal,
+            Some(Some(layout)) if layout.eq_ignore_ascii_case("vertical") => Layout::Vertical,
+            Some(layout) => {
+                let layout = layout.map(str::to_string)
+                    .unwrap_or_else(|| format!("{}", object["layout"]));
+                return Err(TemplateErr::UnknownLayout(layout))
+            }
+        };
+        let size = match object.get("size") {
+            None => None,
+            Some(size) => match size.as_f64() {
+                Some(size) if size > 0.0 => Some(size),
+                _ => return Err(TemplateErr::InvalidSize)
+            }
+        };
+        let criteria = Some(Criteria::from_table(object))
+            .into_iter()
+            .find(|criteria| !criteria.is_empty());
+        let mut children = vec![];
+        if let Some(json_children) = object.get("children") {
+            let json_children = try!(json_children.as_array()
+                                     .ok_or(TemplateErr::NotAnObject));
+            for child in json_children {
+                children.push(try!(TemplateNode::parse(child)));
+            }
+        }
+        match (criteria.is_some(), children.is_empty()) {
+            (true, false) => return Err(TemplateErr::PlaceholderWithChildren),
+            (false, true) => return Err(TemplateErr::EmptyContainer),
+            _ => {}
+        }
+        Ok(TemplateNode {
+            layout: layout,
+            size: size,
+            criteria: criteria,
+            children: children
+        })
+    }
+}
+
+impl LayoutTree {
+    /// Applies the layout template to the workspace with the name,
+    /// creating the workspace if it doesn't exist.
+    ///
+    /// The containers and placeholders of the template are added
+    /// after the containers that are already in the workspace.
+    pub fn apply_template(&mut self, workspace_name: &str, template: &Json) -> CommandResult {
+        let template = try!(TemplateNode::parse(template)
+                            .map_err(|err| TreeError::Template(err)));
+        if template.criteria.is_some() {
+            return Err(TreeError::Template(TemplateErr::RootPlaceholder))
+        }
+        let root_ix = self.tree.root_ix();
+        if self.tree.follow_path_until(root_ix, ContainerType::Output).is_err() {
+            return Err(TreeError::NoActiveContainer)
+        }
+        let prev_workspace_ix = self.active_ix_of(ContainerType::Workspace);
+        let workspace_ix = self.get_or_make_workspace(workspace_name);
+        if let Some(prev_workspace_ix) = prev_workspace_ix {
+            if prev_workspace_ix != workspace_ix {
+                // Making a new workspace puts the active path on it,
+                // but the user should stay where they are.
+                self.tree.set_family_visible(workspace_ix, false);
+                let path_end_ix = self.active_container
+                    .into_iter()
+                    .find(|active_ix| !self.tree[*active_ix].floating())
+                    .unwrap_or_else(|| self.tree.children_of(prev_workspace_ix)[0]);
+                self.tree.set_ancestor_paths_active(path_end_ix);
+            }
+        }
+        let root_c_ix = self.tree.children_of(workspace_ix)[0];
+        self.set_layout(root_c_ix, template.layout);
+        let geometry = self.tree[root_c_ix].get_geometry()
+            .expect("Root container had no geometry");
+        self.add_template_children(root_c_ix, geometry, template.layout,
+                                   &template.children);
+        self.layout(workspace_ix);
+        self.validate();
+        self.validate_path();
+        Ok(())
+    }
+
+    /// Adds the children of a template node to the container,
+    /// sized by their share of the container's geometry.
+    fn add_template_children(&mut self, parent_ix: NodeIndex, geometry: Geometry,
+                             layout: Layout, children: &[TemplateNode]) {
+        let even_share = 1.0 / children.len() as f64;
+        let total: f64 = children.iter()
+            .map(|child| child.size.unwrap_or(even_share))
+            .sum();
+        for child in children {
+            let share = child.size.unwrap_or(even_share) / total;
+            let mut child_geometry = geometry;
+            match layout {
+                Layout::Horizontal => {
+                    child_geometry.size.w = (geometry.size.w as f64 * share) as u32
+                },
+                Layout::Vertical => {
+                    child_geometry.size.h = (geometry.size.h as f64 * share) as u32
+                }
+            }
+            let mut container = Container::new_container(child_geometry);
+            container.set_layout(child.layout)
+                .expect("Could not set the layout of a new container");
+            container.set_placeholder(child.criteria.clone())
+                .expect("Could not make a new container a placeholder");
+            let child_ix = self.tree.add_child(parent_ix, container, false);
+            self.add_template_children(child_ix, child_geometry, child.layout,
+                                       &child.children);
+        }
+    }
+
+    /// Gets the node indices of every placeholder beneath the node.
+    pub fn placeholders_of(&self, node_ix: NodeIndex) -> Vec<NodeIndex> {
+        self.tree.all_descendants_of(node_ix).into_iter()
+            .filter(|child_ix| self.tree[*child_ix].placeholder().is_some())
+            .collect()
+    }
+
+    /// Finds a placeholder waiting for the view.
+    /// Placeholders in the active workspace are preferred.
+    pub fn placeholder_for(&self, view: WlcView) -> Option<NodeIndex> {
+        let root_ix = self.tree.root_ix();
+        let mut workspaces = vec![];
+        for output_ix in self.tree.children_of(root_ix) {
+            workspaces.extend(self.tree.children_of(output_ix));
+        }
+        let active_workspace_ix = self.active_ix_of(ContainerType::Workspace);
+        workspaces.sort_by_key(|workspace_ix| Some(*workspace_ix) != active_workspace_ix);
+        workspaces.into_iter()
+            .flat_map(|workspace_ix| self.placeholders_of(workspace_ix))
+            .find(|placeholder_ix| {
+                self.tree[*placeholder_ix].placeholder()
+                    .map_or(false, |criteria| criteria.matches_view(view))
+            })
+    }
+
+    /// Puts the view in the placeholder, which becomes a normal container.
+    ///
+    /// The view is only focused if its workspace is visible.
+    pub fn fill_placeholder(&mut self, placeholder_ix: NodeIndex, view: WlcView)
+                            -> CommandResult {
+        let id = self.tree[placeholder_ix].get_id();
+        if self.tree[placeholder_ix].placeholder().is_none() {
+            return Err(TreeError::UuidWrongType(id, vec!(ContainerType::Container)))
+        }
+        try!(self.tree[placeholder_ix].set_placeholder(None)
+             .map_err(|err| TreeError::Container(err)));
+        let view_ix = self.add_view_container(placeholder_ix, view, false);
+        let workspace_ix = try!(self.tree.ancestor_of_type(view_ix, ContainerType::Workspace)
+                                .map_err(|err| TreeError::PetGraph(err)));
+        let output_ix = try!(self.tree.parent_of(workspace_ix)
+                             .map_err(|err| TreeError::PetGraph(err)));
+        if self.tree.follow_path_until(output_ix, ContainerType::Workspace) == Ok(workspace_ix) {
+            try!(self.focus_new_view(view_ix));
+        } else {
+            self.tree[view_ix].set_visibility(false);
+        }
+        self.layout(workspace_ix);
+        self.validate();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustc_serialize::json::Json;
+    use rustwlc::WlcView;
+    use super::{TemplateErr, TemplateNode};
+    use super::super::super::TreeError;
+    use super::super::super::core::container::{ContainerType, Layout};
+    use ::layout::core::tree::tests::basic_tree;
+
+    const TEMPLATE: &'static str = r#"{
+        "layout": "vertical",
+        "children": [
+            { "class": "Firefox", "size": 0.6 },
+            { "layout": "horizontal", "size": 0.4,
+              "children": [ { "title": "vim" }, { "app_id": "weston-terminal" } ] }
+        ]
+    }"#;
+
+    fn parse(template: &str) -> Result<TemplateNode, TemplateErr> {
+        TemplateNode::parse(&Json::from_str(template).unwrap())
+    }
+
+    #[test]
+    fn parse_template() {
+        let template = parse(TEMPLATE).unwrap();
+        assert_eq!(template.children.len(), 2);
+        let size = template.children[0].size.unwrap();
+        assert!((size - 0.6).abs() < 1e-9);
+        assert_eq!(template.children[0].criteria.as_ref()
+                   .and_then(|criteria| criteria.class.clone()),
+                   Some("Firefox".into()));
+        assert_eq!(template.children[1].children.len(), 2);
+        assert_eq!(parse("[]"), Err(TemplateErr::NotAnObject));
+        assert_eq!(parse(r#"{"layout": "diagonal", "children": [{"class": "a"}]}"#),
+                   Err(TemplateErr::UnknownLayout("diagonal".into())));
+        assert_eq!(parse(r#"{"children": [{"class": "a", "size": -1}]}"#),
+                   Err(TemplateErr::InvalidSize));
+        assert_eq!(parse(r#"{"children": [{"layout": "vertical"}]}"#),
+                   Err(TemplateErr::EmptyContainer));
+        assert_eq!(parse(r#"{"children": [{"class": "a", "children": [{"class": "b"}]}]}"#),
+                   Err(TemplateErr::PlaceholderWithChildren));
+    }
+
+    #[test]
+    fn apply_template() {
+        let mut tree = basic_tree();
+        let template = Json::from_str(TEMPLATE).unwrap();
+        let active_ix = tree.active_container;
+        tree.apply_template("3", &template).unwrap();
+        // The user stays where they were
+        assert_eq!(tree.active_container, active_ix);
+        let workspace_ix = tree.tree.workspace_ix_by_name("3").unwrap();
+        assert_eq!(tree.placeholders_of(workspace_ix).len(), 3);
+        // Placeholders keep the workspace around
+        tree.switch_to_workspace("3");
+        tree.switch_to_workspace("1");
+        assert!(tree.tree.workspace_ix_by_name("3").is_some());
+        let root_placeholder = Json::from_str(r#"{"class": "a"}"#).unwrap();
+        assert_eq!(tree.apply_template("3", &root_placeholder),
+                   Err(TreeError::Template(TemplateErr::RootPlaceholder)));
+    }
+
+    #[test]
+    fn fill_placeholder() {
+        let mut tree = basic_tree();
+        let template = Json::from_str(TEMPLATE).unwrap();
+        tree.apply_template("1", &template).unwrap();
+        let workspace_ix = tree.tree.workspace_ix_by_name("1").unwrap();
+        let placeholder_ix = tree.placeholders_of(workspace_ix)[0];
+        tree.fill_placeholder(placeholder_ix, WlcView::root()).unwrap();
+        assert!(tree.tree[placeholder_ix].placeholder().is_none());
+        assert_eq!(tree.placeholders_of(workspace_ix).len(), 2);
+        let view_ix = tree.tree.children_of(placeholder_ix)[0];
+        assert_eq!(tree.tree[view_ix].get_type(), ContainerType::View);
+        assert_eq!(tree.active_container, Some(view_ix));
+        assert!(tree.fill_placeholder(placeholder_ix, WlcView::root()).is_err());
+    }
+}
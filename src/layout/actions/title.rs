@@ -0,0 +1,77 @@
+//! Keeps the title bars of views up to date with the tree.
+//!
+//! See `core::borders::title` for the placeholders of the title format.
+
+use petgraph::graph::NodeIndex;
+
+use super::super::LayoutTree;
+use super::super::core::borders::title::{self, TitleFields, TitleStyle};
+use super::super::core::container::{Container, ContainerType};
+use super::super::core::rules;
+
+impl LayoutTree {
+    /// Gets what the placeholders of the title format are replaced with
+    /// for the view, or `None` if the node isn't a view.
+    pub fn title_fields(&self, node_ix: NodeIndex) -> Option<TitleFields> {
+        let view = match self.tree[node_ix] {
+            Container::View { handle, .. } => handle,
+            _ => return None
+        };
+        let workspace = self.tree.ancestor_of_type(node_ix, ContainerType::Workspace).ok()
+            .and_then(|workspace_ix| self.tree[workspace_ix].get_name().map(str::to_string))
+            .unwrap_or_default();
+        let mut marks = Vec::new();
+        if self.tree[node_ix].urgent() {
+            marks.push("urgent".to_string());
+        }
+        Some(TitleFields {
+            title: Container::get_title(view),
+            class: view.get_class(),
+            app_id: view.get_app_id(),
+            workspace: workspace,
+            marks: marks,
+            floating: self.tree[node_ix].floating()
+        })
+    }
+
+    /// Writes the title of the view in its title bar, in the format and style
+    /// set for it. The borders need to be drawn afterwards.
+    pub fn update_title_bar(&mut self, node_ix: NodeIndex) {
+        let fields = match self.title_fields(node_ix) {
+            Some(fields) => fields,
+            None => return
+        };
+        let options = match self.tree[node_ix] {
+            Container::View { handle, .. } => rules::options_for(handle),
+            _ => unreachable!()
+        };
+        let text = title::format_title(&title::title_format(&options), &fields);
+        let style = TitleStyle::configured(&options);
+        if let Container::View { ref mut borders, .. } = self.tree[node_ix] {
+            if let Some(borders) = borders.as_mut() {
+                borders.title = text;
+                borders.title_style = style;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::layout::core::tree::tests::basic_tree;
+    use ::layout::core::container::ContainerType;
+
+    #[test]
+    fn title_fields() {
+        let mut tree = basic_tree();
+        let view_ix = tree.active_ix_of(ContainerType::View).unwrap();
+        let workspace_ix = tree.active_ix_of(ContainerType::Workspace).unwrap();
+        let fields = tree.title_fields(view_ix).unwrap();
+        assert_eq!(Some(fields.workspace.as_str()), tree.tree[workspace_ix].get_name());
+        assert!(fields.marks.is_empty());
+        assert!(!fields.floating);
+        tree.tree[view_ix].set_urgent(true).unwrap();
+        assert_eq!(tree.title_fields(view_ix).unwrap().marks, vec!["urgent".to_string()]);
+        assert_eq!(tree.title_fields(workspace_ix), None);
+    }
+}
@@ -0,0 +1,206 @@
+//! Clicking and dragging the title bars of views.
+//!
+//! Clicking a title focuses its view, and dragging it moves the view:
+//! floating views follow the pointer, tiled views are docked next to the
+//! view they are dropped on. Double-clicking a title toggles floating and
+//! middle-clicking closes the view. See `core::borders::buttons` for the
+//! buttons at the end of the title bar.
+
+use std::sync::Mutex;
+
+use petgraph::graph::NodeIndex;
+use rustwlc::{Point, WlcOutput, WlcView};
+
+use super::super::{LayoutTree, TreeError};
+use super::super::commands::CommandResult;
+use super::super::core::ShiftDirection;
+use super::super::core::borders::buttons::{self, TitleButton};
+use super::super::core::container::{Container, ContainerType, Handle, Layout};
+use super::pointer::contains;
+
+/// How long there may be between the clicks of a double-click,
+/// in milliseconds.
+const DOUBLE_CLICK_TIME: u32 = 400;
+
+lazy_static! {
+    /// The view whose title was last clicked, and when.
+    static ref LAST_CLICK: Mutex<Option<(WlcView, u32)>> = Mutex::new(None);
+}
+
+/// The part of a title bar that is under the pointer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitleBarPart {
+    Title,
+    Button(TitleButton)
+}
+
+/// What clicking on a title bar does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitleBarClick {
+    /// Focuses on the view, and starts dragging it.
+    Drag,
+    Close,
+    ToggleFloating,
+    ToggleFullscreen
+}
+
+/// Gets what a left click on the part of the title bar of the view does,
+/// at the time of the click in milliseconds.
+pub fn title_bar_click(view: WlcView, part: TitleBarPart, time: u32) -> TitleBarClick {
+    let double = match LAST_CLICK.lock() {
+        Ok(mut last_click) => {
+            let double = match *last_click {
+                Some((last_view, last_time)) => last_view == view
+                    && part == TitleBarPart::Title
+                    && time.wrapping_sub(last_time) <= DOUBLE_CLICK_TIME,
+                None => false
+            };
+            // A third click starts over
+            *last_click = if double { None } else { Some((view, time)) };
+            double
+        },
+        Err(_) => false
+    };
+    click_for(part, double)
+}
+
+/// Gets what a left click on the part of a title bar does.
+fn click_for(part: TitleBarPart, double: bool) -> TitleBarClick {
+    match part {
+        TitleBarPart::Button(TitleButton::Close) => TitleBarClick::Close,
+        TitleBarPart::Button(TitleButton::Fullscreen) => TitleBarClick::ToggleFullscreen,
+        TitleBarPart::Button(TitleButton::Float) => TitleBarClick::ToggleFloating,
+        TitleBarPart::Title if double => TitleBarClick::ToggleFloating,
+        TitleBarPart::Title => TitleBarClick::Drag
+    }
+}
+
+impl LayoutTree {
+    /// Gets the active workspace of the focused output, which the pointer
+    /// is on.
+    fn pointer_workspace_ix(&self) -> Option<NodeIndex> {
+        let output = WlcOutput::focused();
+        self.tree.children_of(self.tree.root_ix()).into_iter()
+            .find(|ix| self.tree[*ix].get_handle() == Some(Handle::Output(output)))
+            .and_then(|output_ix| self.tree.follow_path_until(output_ix,
+                                                               ContainerType::Workspace).ok())
+    }
+
+    /// Gets the views shown on the workspace, floating ones first
+    /// since they are drawn over the tiled ones.
+    fn shown_views_of(&self, workspace_ix: NodeIndex) -> Vec<NodeIndex> {
+        let mut views: Vec<NodeIndex> = self.tree.all_descendants_of(workspace_ix).into_iter()
+            .filter(|ix| self.tree[*ix].get_type() == ContainerType::View
+                    && !self.tree[*ix].minimized())
+            .collect();
+        views.sort_by_key(|ix| !self.tree[*ix].floating());
+        views
+    }
+
+    /// Finds the title bar under the point on the focused output,
+    /// and the part of it that the point is on.
+    pub fn title_bar_at(&self, point: Point) -> Option<(NodeIndex, TitleBarPart)> {
+        let workspace_ix = match self.pointer_workspace_ix() {
+            Some(workspace_ix) => workspace_ix,
+            None => return None
+        };
+        // Title bars are hidden behind fullscreen views
+        if self.tree[workspace_ix].fullscreen_c().map_or(false, |views| !views.is_empty()) {
+            return None
+        }
+        self.shown_views_of(workspace_ix).into_iter()
+            .filter_map(|view_ix| match self.tree[view_ix] {
+                Container::View { handle, ref borders, .. } => borders.as_ref()
+                    .and_then(|borders| handle.get_geometry()
+                              .and_then(|geometry| borders.title_bar_geometry(geometry)))
+                    .map(|title_bar| (view_ix, title_bar)),
+                _ => None
+            })
+            .find(|&(_, title_bar)| contains(title_bar, point))
+            .map(|(view_ix, title_bar)| {
+                let relative = Point {
+                    x: point.x - title_bar.origin.x,
+                    y: point.y - title_bar.origin.y
+                };
+                let button = if buttons::buttons_enabled() {
+                    buttons::button_at(title_bar.size, relative)
+                } else {
+                    None
+                };
+                (view_ix, button.map_or(TitleBarPart::Title, TitleBarPart::Button))
+            })
+    }
+
+    /// Docks the tiled view next to the tiled view under the point, before
+    /// or after it depending on which half of it the point is on.
+    ///
+    /// Nothing happens if the view is floating or there's no other view there.
+    pub fn dock_view(&mut self, node_ix: NodeIndex, point: Point) -> CommandResult {
+        if self.tree[node_ix].floating() {
+            return Ok(())
+        }
+        let workspace_ix = match self.pointer_workspace_ix() {
+            Some(workspace_ix) => workspace_ix,
+            None => return Ok(())
+        };
+        let target = self.shown_views_of(workspace_ix).into_iter()
+            .filter(|ix| *ix != node_ix && !self.tree[*ix].floating())
+            .filter_map(|ix| self.tree[ix].get_geometry().map(|geometry| (ix, geometry)))
+            .find(|&(_, geometry)| contains(geometry, point));
+        let (target_ix, geometry) = match target {
+            Some(target) => target,
+            None => return Ok(())
+        };
+        let target_parent_ix = try!(self.tree.parent_of(target_ix)
+                                    .map_err(|err| TreeError::PetGraph(err)));
+        let before = match self.tree[target_parent_ix] {
+            Container::Container { layout: Layout::Vertical, .. } =>
+                point.y < geometry.origin.y + geometry.size.h as i32 / 2,
+            _ => point.x < geometry.origin.x + geometry.size.w as i32 / 2
+        };
+        let dir = if before { ShiftDirection::Left } else { ShiftDirection::Right };
+        let old_workspace_ix = try!(self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)
+                                    .map_err(|err| TreeError::PetGraph(err)));
+        let parent_ix = try!(self.tree.parent_of(node_ix)
+                             .map_err(|err| TreeError::PetGraph(err)));
+        // Removing the old parent can move the other nodes to new indices
+        let id = self.tree[node_ix].get_id();
+        let mut workspace_ids = vec![self.tree[workspace_ix].get_id()];
+        if old_workspace_ix != workspace_ix {
+            workspace_ids.push(self.tree[old_workspace_ix].get_id());
+        }
+        try!(self.tree.place_node_at(node_ix, target_ix, dir)
+             .map_err(|err| TreeError::PetGraph(err)));
+        self.bind_views_to_output(node_ix);
+        if self.tree.can_remove_empty_parent(parent_ix) {
+            try!(self.remove_view_or_container(parent_ix));
+        }
+        let node_ix = try!(self.tree.lookup_id(id).ok_or(TreeError::NodeNotFound(id)));
+        try!(self.set_active_node(node_ix));
+        for workspace_id in workspace_ids {
+            if let Some(workspace_ix) = self.tree.lookup_id(workspace_id) {
+                self.layout(workspace_ix);
+            }
+        }
+        self.validate();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TitleBarClick, TitleBarPart, click_for};
+    use ::layout::core::borders::buttons::TitleButton;
+
+    #[test]
+    fn clicking_title_bars() {
+        assert_eq!(click_for(TitleBarPart::Title, false), TitleBarClick::Drag);
+        assert_eq!(click_for(TitleBarPart::Title, true), TitleBarClick::ToggleFloating);
+        assert_eq!(click_for(TitleBarPart::Button(TitleButton::Close), true),
+                   TitleBarClick::Close);
+        assert_eq!(click_for(TitleBarPart::Button(TitleButton::Fullscreen), false),
+                   TitleBarClick::ToggleFullscreen);
+        assert_eq!(click_for(TitleBarPart::Button(TitleButton::Float), false),
+                   TitleBarClick::ToggleFloating);
+    }
+}
@@ -0,0 +1,118 @@
+//! Urgency hints, for views that want the attention of the user.
+//!
+//! A view is marked as urgent when it asks to be activated while it isn't
+//! focused, and stays urgent until it is focused. A workspace is urgent
+//! if any of its views are.
+
+use petgraph::graph::NodeIndex;
+use uuid::Uuid;
+
+use super::super::{LayoutTree, TreeError};
+use super::super::commands::CommandResult;
+use super::super::core::container::ContainerType;
+use super::status_bar::mark_bars_outdated;
+use ::ipc::{self, DBusMessage};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum UrgentErr {
+    /// There were no views that wanted attention.
+    NoUrgentViews
+}
+
+impl LayoutTree {
+    /// Marks the view behind the id as wanting attention, or clears the mark.
+    ///
+    /// The active view can't be marked, it already has the attention.
+    pub fn set_urgent(&mut self, id: Uuid, urgent: bool) -> CommandResult {
+        let node_ix = try!(self.tree.lookup_id(id).ok_or(TreeError::NodeNotFound(id)));
+        if self.tree[node_ix].get_type() != ContainerType::View {
+            return Err(TreeError::UuidWrongType(id, vec!(ContainerType::View)))
+        }
+        let urgent = urgent && self.active_container != Some(node_ix);
+        if self.tree[node_ix].urgent() == urgent {
+            return Ok(())
+        }
+        try!(self.tree[node_ix].set_urgent(urgent)
+             .map_err(|err| TreeError::Container(err)));
+        if self.active_container != Some(node_ix) {
+            if urgent {
+                try!(self.tree[node_ix].urgent_border_color());
+            } else {
+                try!(self.tree[node_ix].clear_border_color());
+            }
+            self.update_title_bar(node_ix);
+            self.tree[node_ix].draw_borders();
+        }
+        self.urgent_changed();
+        Ok(())
+    }
+
+    /// Determines if any view on the workspace wants attention.
+    pub fn workspace_urgent(&self, workspace_ix: NodeIndex) -> bool {
+        !self.urgent_views_of(workspace_ix).is_empty()
+    }
+
+    /// Gets the names of the workspaces with views that want attention.
+    pub fn urgent_workspaces(&self) -> Vec<String> {
+        self.tree.all_descendants_of(self.tree.root_ix()).into_iter()
+            .filter(|ix| self.tree[*ix].get_type() == ContainerType::Workspace)
+            .filter(|ix| self.workspace_urgent(*ix))
+            .filter_map(|ix| self.tree[ix].get_name().map(str::to_string))
+            .collect()
+    }
+
+    /// Gets the node indices of every urgent view beneath the node.
+    pub fn urgent_views_of(&self, node_ix: NodeIndex) -> Vec<NodeIndex> {
+        self.tree.all_descendants_of(node_ix).into_iter()
+            .filter(|ix| self.tree[*ix].urgent())
+            .collect()
+    }
+
+    /// Focuses on a view that wants attention, switching to its workspace
+    /// (or restoring it, if it was minimized) if need be.
+    pub fn focus_urgent(&mut self) -> CommandResult {
+        let root_ix = self.tree.root_ix();
+        let node_ix = try!(self.urgent_views_of(root_ix).into_iter().next()
+                           .ok_or(TreeError::Urgent(UrgentErr::NoUrgentViews)));
+        let id = self.tree[node_ix].get_id();
+        self.focus_anywhere(id)
+    }
+
+    /// Tells anyone listening which workspaces want attention now.
+    pub fn urgent_changed(&self) {
+        ipc::send(DBusMessage::UrgentWorkspaces(self.urgent_workspaces()));
+        mark_bars_outdated();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::core::container::ContainerType;
+    use ::layout::core::tree::tests::basic_tree;
+
+    #[test]
+    fn urgent_workspaces() {
+        let mut tree = basic_tree();
+        let active_ix = tree.active_container.unwrap();
+        let active_id = tree.tree[active_ix].get_id();
+        let workspace_2_ix = tree.tree.workspace_ix_by_name("2").unwrap();
+        let view_ix = tree.tree.all_descendants_of(workspace_2_ix).into_iter()
+            .find(|ix| tree.tree[*ix].get_type() == ContainerType::View)
+            .unwrap();
+        let view_id = tree.tree[view_ix].get_id();
+        assert!(tree.urgent_workspaces().is_empty());
+        // The active view already has the attention
+        tree.set_urgent(active_id, true).unwrap();
+        assert!(!tree.tree[active_ix].urgent());
+        tree.set_urgent(view_id, true).unwrap();
+        assert!(tree.tree[view_ix].urgent());
+        assert!(tree.workspace_urgent(workspace_2_ix));
+        assert_eq!(tree.urgent_workspaces(), vec!["2".to_string()]);
+        // Focusing on the view clears it
+        tree.focus_urgent().unwrap();
+        assert_eq!(tree.active_container, Some(view_ix));
+        assert!(!tree.tree[view_ix].urgent());
+        assert!(tree.urgent_workspaces().is_empty());
+        assert!(tree.focus_urgent().is_err());
+    }
+}
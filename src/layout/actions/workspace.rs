@@ -1,8 +1,16 @@
+use std::cmp::Ordering;
+
 use petgraph::graph::NodeIndex;
+use rustc_serialize::json::Json;
+use rustwlc::{Geometry, Point, ResizeEdge};
 use uuid::Uuid;
-use super::super::LayoutTree;
-use super::super::core::container::{Container, ContainerType};
+use super::super::{LayoutTree, TreeError};
+use super::super::commands::CommandResult;
+use super::super::core::container::{Container, ContainerType, Handle};
+use super::status_bar::mark_bars_outdated;
+use super::background::mark_backgrounds_outdated;
 use ::debug_enabled;
+use ::registry;
 
 // TODO This module needs to be updated like the other modules...
 // Need to add some errors for this (such as when trying to move a non-container/view,
@@ -10,16 +18,123 @@ use ::debug_enabled;
 //
 // Also the code is generally pretty crap, cause it's pretty old (mid-2016)
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkspaceErr {
+    /// There is no workspace with the name.
+    NotFound(String),
+    /// There already is a workspace with the name.
+    NameTaken(String),
+    /// Workspaces can't have an empty name.
+    EmptyName,
+    /// The workspace still has views on it, so it can't be deleted.
+    NotEmpty(String),
+    /// The workspace is shown on its output, so it can't be deleted.
+    Visible(String)
+}
+
+/// Which workspaces are cycled through when going to the next
+/// or previous workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkspaceScope {
+    /// The workspaces of every output
+    Global,
+    /// Only the workspaces of the active output
+    Output
+}
+
+/// How workspace names are shared between outputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkspacePolicy {
+    /// All outputs share one set of workspaces, so a name always refers
+    /// to the same workspace, whichever output it is on.
+    Global,
+    /// Every output has its own set of workspaces, so a name refers to
+    /// the workspace with that name on the active output.
+    PerOutput
+}
+
+impl WorkspacePolicy {
+    /// Reads the policy from the `per_output` option of the workspaces category.
+    pub fn current() -> Self {
+        let per_output = workspaces_option("per_output")
+            .and_then(|value| value.as_boolean())
+            .unwrap_or(false);
+        if per_output {
+            WorkspacePolicy::PerOutput
+        } else {
+            WorkspacePolicy::Global
+        }
+    }
+}
+
+/// Gets the name of the output a new workspace should be made on,
+/// from the `outputs` option of the workspaces category.
+fn assigned_output(workspace_name: &str) -> Option<String> {
+    workspaces_option("outputs")
+        .and_then(|outputs| outputs.as_object()
+                  .and_then(|outputs| outputs.get(workspace_name))
+                  .and_then(Json::as_string)
+                  .map(str::to_string))
+}
+
+/// Reads an option from the workspaces category of the registry.
+fn workspaces_option(key: &str) -> Option<Json> {
+    let lock = registry::clients_read();
+    let client = lock.client(Uuid::nil()).unwrap();
+    let handle = registry::ReadHandle::new(&client);
+    handle.read("workspaces".into()).ok()
+        .and_then(|workspaces| workspaces.get(key).cloned())
+}
+
+/// Orders workspace names so that numbered workspaces come first
+/// (in numeric order, so "10" comes after "9"), followed by the other
+/// workspaces in alphabetical order.
+pub fn workspace_order(a: &str, b: &str) -> Ordering {
+    match (a.parse::<u64>(), b.parse::<u64>()) {
+        (Ok(a_num), Ok(b_num)) => a_num.cmp(&b_num),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b)
+    }
+}
+
 impl LayoutTree {
-    /// Gets a workspace by name or creates it
-    fn get_or_make_workspace(&mut self, name: &str) -> NodeIndex {
+    /// Gets a workspace by name or creates it.
+    ///
+    /// How the name is looked up, and which output a new workspace is made on,
+    /// depends on the `WorkspacePolicy`.
+    pub fn get_or_make_workspace(&mut self, name: &str) -> NodeIndex {
+        self.get_or_make_workspace_with(name, WorkspacePolicy::current())
+    }
+
+    /// Gets a workspace by name or creates it, following the policy.
+    pub fn get_or_make_workspace_with(&mut self, name: &str, policy: WorkspacePolicy)
+                                      -> NodeIndex {
         let active_index = self.active_ix_of(ContainerType::Output)
             .or_else(|| {
                 self.tree.follow_path_until(self.tree.root_ix(), ContainerType::Output).ok()
             })
             .expect("get_or_make_wksp: Couldn't get output");
-        let workspace_ix = self.tree.workspace_ix_by_name(name).unwrap_or_else(|| {
-            let root_ix = self.init_workspace(name.to_string(), active_index);
+        let workspace_ix = self.find_workspace_with(name, policy).unwrap_or_else(|| {
+            let output_ix = match policy {
+                WorkspacePolicy::PerOutput => active_index,
+                WorkspacePolicy::Global => assigned_output(name)
+                    .and_then(|output_name| self.output_ix_by_name(&output_name))
+                    .unwrap_or(active_index)
+            };
+            let shown_ix = self.tree.follow_path_until(output_ix, ContainerType::Workspace).ok();
+            let root_ix = self.init_workspace(name.to_string(), output_ix);
+            if output_ix != active_index {
+                // Don't change what is shown on the other output
+                if let Some(shown_ix) = shown_ix {
+                    let shown_root_ix = self.tree.children_of(shown_ix)[0];
+                    self.tree.set_ancestor_paths_active(shown_root_ix);
+                }
+                if let Some(active_workspace_ix) = self.active_ix_of(ContainerType::Workspace) {
+                    let active_root_ix = self.tree.children_of(active_workspace_ix)[0];
+                    self.tree.set_ancestor_paths_active(active_root_ix);
+                }
+            }
             self.tree.parent_of(root_ix)
                 .expect("Workspace was not properly initialized with a root container")
         });
@@ -27,6 +142,35 @@ impl LayoutTree {
         workspace_ix
     }
 
+    /// Looks up a workspace by name, following the `WorkspacePolicy`.
+    pub fn find_workspace(&self, name: &str) -> Option<NodeIndex> {
+        self.find_workspace_with(name, WorkspacePolicy::current())
+    }
+
+    /// Looks up a workspace by name, following the policy.
+    ///
+    /// If there is no active output, every output is searched.
+    pub fn find_workspace_with(&self, name: &str, policy: WorkspacePolicy)
+                               -> Option<NodeIndex> {
+        match (policy, self.active_ix_of(ContainerType::Output)) {
+            (WorkspacePolicy::PerOutput, Some(output_ix)) =>
+                self.workspace_ix_on_output(output_ix, name),
+            _ => self.tree.workspace_ix_by_name(name)
+        }
+    }
+
+    /// Looks up a workspace by name among the workspaces of the output.
+    pub fn workspace_ix_on_output(&self, output_ix: NodeIndex, name: &str) -> Option<NodeIndex> {
+        self.tree.children_of(output_ix).into_iter()
+            .find(|ix| self.tree[*ix].get_name() == Some(name))
+    }
+
+    /// Looks up an output by its name (e.g "DP-1").
+    fn output_ix_by_name(&self, name: &str) -> Option<NodeIndex> {
+        self.tree.children_of(self.tree.root_ix()).into_iter()
+            .find(|ix| self.tree[*ix].name() == name)
+    }
+
     /// Initializes a workspace and gets the index of the root container
     pub fn init_workspace(&mut self, name: String, output_ix: NodeIndex)
                       -> NodeIndex {
@@ -40,12 +184,23 @@ impl LayoutTree {
         let container_ix = self.tree.add_child(worksp_ix,
                                                Container::new_container(geometry), false);
         self.tree.set_ancestor_paths_active(container_ix);
+        mark_bars_outdated();
         self.validate();
         container_ix
     }
 
     /// Switch to the specified workspace
     pub fn switch_to_workspace(&mut self, name: &str) {
+        trace!("Switching to workspace {}", name);
+        if let Some(old_worksp_ix) = self.switching_from() {
+            // Get the new workspace, or create one if it doesn't work
+            let workspace_ix = self.get_or_make_workspace(name);
+            self.switch_between_workspaces(old_worksp_ix, workspace_ix);
+        }
+    }
+
+    /// Gets the workspace that is being switched away from.
+    pub fn switching_from(&self) -> Option<NodeIndex> {
         let maybe_active_ix = self.active_container
             .or_else(|| {
                 let new_active = self.tree.follow_path(self.tree.root_ix());
@@ -60,49 +215,66 @@ impl LayoutTree {
         if maybe_active_ix.is_none() {
             warn!("{:#?}", self);
             warn!("No active container, cannot switch");
-            return;
+            return None;
         }
         let active_ix = maybe_active_ix.unwrap();
         // Get the old (current) workspace
-        let old_worksp_ix: NodeIndex;
         if let Ok(index) = self.tree.ancestor_of_type(active_ix, ContainerType::Workspace) {
-            old_worksp_ix = index;
-            trace!("Switching to workspace {}", name);
+            Some(index)
         } else {
             match self.tree[active_ix].get_type() {
-                ContainerType::Workspace => {
-                    old_worksp_ix = active_ix;
-                    trace!("Switching to workspace {}", name);
-                },
+                ContainerType::Workspace => Some(active_ix),
                 _ => {
                     warn!("Could not find old workspace, could not set invisible");
-                    return;
+                    None
                 }
             }
         }
-        // Get the new workspace, or create one if it doesn't work
-        let mut workspace_ix = self.get_or_make_workspace(name);
+    }
+
+    /// Switches from the old workspace to the new one.
+    ///
+    /// If they are on the same output the old workspace is hidden, otherwise
+    /// the workspace shown on the output of the new one is hidden.
+    pub fn switch_between_workspaces(&mut self, old_worksp_ix: NodeIndex, workspace_ix: NodeIndex) {
         if old_worksp_ix == workspace_ix {
             return;
         }
+        mark_bars_outdated();
+        mark_backgrounds_outdated();
+        // Remember where we came from, for going back and forth
+        self.previous_workspace = self.tree[old_worksp_ix].get_name()
+            .map(str::to_string);
+        let output_ix = self.tree.parent_of(workspace_ix)
+            .expect("Workspace had no output");
+        let hidden_ix = if self.tree.parent_of(old_worksp_ix).ok() == Some(output_ix) {
+            Some(old_worksp_ix)
+        } else {
+            self.tree.follow_path_until(output_ix, ContainerType::Workspace).ok()
+                .and_then(|shown_ix| if shown_ix != workspace_ix { Some(shown_ix) } else { None })
+        };
         // Set the old one to invisible
-        self.tree.set_family_visible(old_worksp_ix, false);
+        if let Some(hidden_ix) = hidden_ix {
+            self.tree.set_family_visible(hidden_ix, false);
+        }
         // Set the new one to visible
         self.tree.set_family_visible(workspace_ix, true);
-        // Delete the old workspace if it has no views on it
+        // Delete the old workspace if it has no views on it (not even minimized ones),
+        // and no placeholders waiting for views
         self.active_container = None;
-        if self.tree.descendant_of_type(old_worksp_ix, ContainerType::View).is_err() {
-            trace!("Removing workspace: {:?}", self.tree[old_worksp_ix].get_name()
-                   .expect("Workspace had no name"));
-            if let Err(err) = self.remove_workspace(old_worksp_ix) {
-                warn!("Tried to remove empty workspace {:#?}, error: {:?}",
-                      old_worksp_ix, err);
-                debug!("{:#?}", self);
-                panic!("Could not remove old workspace");
+        if let Some(hidden_ix) = hidden_ix {
+            if !self.workspace_has_views(hidden_ix)
+                && self.placeholders_of(hidden_ix).is_empty() {
+                trace!("Removing workspace: {:?}", self.tree[hidden_ix].get_name()
+                       .expect("Workspace had no name"));
+                if let Err(err) = self.remove_workspace(hidden_ix) {
+                    warn!("Tried to remove empty workspace {:#?}, error: {:?}",
+                          hidden_ix, err);
+                    debug!("{:#?}", self);
+                    panic!("Could not remove old workspace");
+                }
             }
         }
-        workspace_ix = self.tree.workspace_ix_by_name(name)
-            .expect("Workspace we just made was deleted!");
         let active_ix = self.tree.follow_path(workspace_ix);
         match self.tree[active_ix].get_type() {
             ContainerType::View  => {
@@ -123,7 +295,7 @@ impl LayoutTree {
             },
             _ => {
                 self.active_container = self.tree
-                    .descendant_of_type(active_ix, ContainerType::View)
+                    .focusable_descendant_of_type(active_ix, ContainerType::View)
                     .or_else(|_| self.tree.descendant_of_type(active_ix,
                                                               ContainerType::Container)).ok();
                 match self.tree[self.active_container.expect("Workspace had NO children!")] {
@@ -149,6 +321,315 @@ impl LayoutTree {
         self.validate_path();
     }
 
+    /// Gets the workspace that comes after (or before, if `next` is false)
+    /// the active workspace, wrapping around at the ends.
+    ///
+    /// If `skip_empty` is set, workspaces without any views are passed over.
+    /// Returns `None` if there is no other workspace to go to.
+    fn neighbouring_workspace_ix(&self, scope: WorkspaceScope, next: bool,
+                                 skip_empty: bool) -> Option<NodeIndex> {
+        let active_ix = match self.active_ix_of(ContainerType::Workspace) {
+            Some(active_ix) => active_ix,
+            None => return None
+        };
+        let outputs = match scope {
+            WorkspaceScope::Global => self.tree.children_of(self.tree.root_ix()),
+            WorkspaceScope::Output => match self.tree.parent_of(active_ix) {
+                Ok(output_ix) => vec![output_ix],
+                Err(_) => return None
+            }
+        };
+        let mut workspaces: Vec<NodeIndex> = outputs.into_iter()
+            .flat_map(|output_ix| self.tree.children_of(output_ix))
+            .filter(|ix| *ix == active_ix || !skip_empty || self.workspace_has_views(*ix))
+            .collect();
+        workspaces.sort_by(|a, b| {
+            let a_name = self.tree[*a].get_name().expect("Workspace had no name");
+            let b_name = self.tree[*b].get_name().expect("Workspace had no name");
+            workspace_order(a_name, b_name)
+        });
+        let len = workspaces.len();
+        let pos = match workspaces.iter().position(|ix| *ix == active_ix) {
+            Some(pos) => pos,
+            None => return None
+        };
+        let neighbour_ix = if next {
+            workspaces[(pos + 1) % len]
+        } else {
+            workspaces[(pos + len - 1) % len]
+        };
+        if neighbour_ix == active_ix {
+            return None
+        }
+        Some(neighbour_ix)
+    }
+
+    /// Switches to the workspace after (or before) the active one,
+    /// see `neighbouring_workspace_ix`.
+    pub fn switch_to_neighbouring_workspace(&mut self, scope: WorkspaceScope, next: bool,
+                                            skip_empty: bool) {
+        // Switch by index, the name might be used on other outputs too
+        if let Some(workspace_ix) = self.neighbouring_workspace_ix(scope, next, skip_empty) {
+            if let Some(old_worksp_ix) = self.switching_from() {
+                self.switch_between_workspaces(old_worksp_ix, workspace_ix);
+            }
+        }
+    }
+
+    /// Switches back to the workspace that was active before the current one.
+    pub fn switch_workspace_back_and_forth(&mut self) {
+        if let Some(name) = self.previous_workspace.clone() {
+            self.switch_to_workspace(&name);
+        }
+    }
+
+    /// Renames the workspace, keeping everything on it.
+    pub fn rename_workspace(&mut self, name: &str, new_name: &str) -> CommandResult {
+        if new_name.is_empty() {
+            return Err(TreeError::Workspace(WorkspaceErr::EmptyName))
+        }
+        let workspace_ix = try!(self.workspace_ix(name));
+        if name == new_name {
+            return Ok(())
+        }
+        let output_ix = try!(self.tree.parent_of(workspace_ix)
+                             .map_err(TreeError::PetGraph));
+        if self.name_taken(output_ix, new_name) {
+            return Err(TreeError::Workspace(WorkspaceErr::NameTaken(new_name.into())))
+        }
+        self.tree[workspace_ix].set_name(new_name.into());
+        self.history.rename_workspace(name, new_name);
+        if self.previous_workspace.as_ref().map(String::as_str) == Some(name) {
+            self.previous_workspace = Some(new_name.into());
+        }
+        mark_bars_outdated();
+        // The background may have been set for the workspace by its name
+        mark_backgrounds_outdated();
+        Ok(())
+    }
+
+    /// Moves the workspace to the position (starting at 0) among the
+    /// other workspaces of its output. If the position is past the end,
+    /// the workspace is moved to the end.
+    pub fn reorder_workspace(&mut self, name: &str, position: usize) -> CommandResult {
+        let workspace_ix = try!(self.workspace_ix(name));
+        let output_ix = try!(self.tree.parent_of(workspace_ix)
+                             .map_err(TreeError::PetGraph));
+        loop {
+            let siblings = self.tree.children_of(output_ix);
+            let cur_pos = siblings.iter().position(|ix| *ix == workspace_ix)
+                .expect("Workspace was not a child of its output");
+            let target_pos = ::std::cmp::min(position, siblings.len() - 1);
+            let neighbour_ix = if cur_pos < target_pos {
+                siblings[cur_pos + 1]
+            } else if cur_pos > target_pos {
+                siblings[cur_pos - 1]
+            } else {
+                return Ok(())
+            };
+            try!(self.tree.swap_node_order(workspace_ix, neighbour_ix)
+                 .map_err(TreeError::PetGraph));
+            mark_bars_outdated();
+        }
+    }
+
+    /// Deletes a workspace that has no views on it (not even minimized ones).
+    /// Any placeholders on the workspace are deleted with it.
+    ///
+    /// Workspaces that are shown on their output can't be deleted.
+    pub fn delete_workspace(&mut self, name: &str) -> CommandResult {
+        let workspace_ix = try!(self.workspace_ix(name));
+        if self.workspace_has_views(workspace_ix) {
+            return Err(TreeError::Workspace(WorkspaceErr::NotEmpty(name.into())))
+        }
+        if self.workspace_visible(workspace_ix) {
+            return Err(TreeError::Workspace(WorkspaceErr::Visible(name.into())))
+        }
+        try!(self.remove_workspace(workspace_ix));
+        if self.previous_workspace.as_ref().map(String::as_str) == Some(name) {
+            self.previous_workspace = None;
+        }
+        mark_bars_outdated();
+        self.validate();
+        Ok(())
+    }
+
+    /// Moves the workspace, with everything on it, to the output.
+    ///
+    /// The workspace is shown on the new output, and if it was the active
+    /// workspace it stays active. The output it came from shows another of
+    /// its workspaces instead, or a new one if it has none left.
+    pub fn move_workspace_to_output(&mut self, name: &str, output_id: Uuid) -> CommandResult {
+        let workspace_ix = try!(self.workspace_ix(name));
+        let output_ix = try!(self.tree.lookup_id(output_id)
+                             .ok_or(TreeError::NodeNotFound(output_id)));
+        if self.tree[output_ix].get_type() != ContainerType::Output {
+            return Err(TreeError::UuidWrongType(output_id, vec![ContainerType::Output]))
+        }
+        let old_output_ix = try!(self.tree.parent_of(workspace_ix)
+                                 .map_err(TreeError::PetGraph));
+        if old_output_ix == output_ix {
+            return Ok(())
+        }
+        if WorkspacePolicy::current() == WorkspacePolicy::PerOutput
+            && self.workspace_ix_on_output(output_ix, name).is_some() {
+            return Err(TreeError::Workspace(WorkspaceErr::NameTaken(name.into())))
+        }
+        let active_workspace_ix = self.active_ix_of(ContainerType::Workspace);
+        let shown_ix = self.tree.follow_path_until(output_ix, ContainerType::Workspace).ok();
+
+        // Leave a workspace to show on the old output
+        if self.workspace_visible(workspace_ix) {
+            let siblings = self.tree.children_of(old_output_ix);
+            let pos = siblings.iter().position(|ix| *ix == workspace_ix)
+                .expect("Workspace was not a child of its output");
+            let replacement_ix = if siblings.len() > 1 {
+                siblings[if pos + 1 < siblings.len() { pos + 1 } else { pos - 1 }]
+            } else {
+                let name = self.unused_workspace_name(old_output_ix);
+                let root_c_ix = self.init_workspace(name, old_output_ix);
+                self.tree.parent_of(root_c_ix)
+                    .expect("Workspace was not properly initialized with a root container")
+            };
+            let root_c_ix = self.tree.children_of(replacement_ix)[0];
+            self.tree.set_ancestor_paths_active(root_c_ix);
+            self.tree.set_family_visible(replacement_ix, true);
+        } else if self.tree.children_of(old_output_ix).len() == 1 {
+            let name = self.unused_workspace_name(old_output_ix);
+            self.init_workspace(name, old_output_ix);
+        }
+
+        try!(self.tree.move_into(workspace_ix, output_ix)
+             .map_err(TreeError::PetGraph));
+        self.bind_views_to_output(workspace_ix);
+        self.keep_floating_on_output(workspace_ix);
+        if let Some(shown_ix) = shown_ix {
+            self.tree.set_family_visible(shown_ix, false);
+        }
+        self.tree.set_family_visible(workspace_ix, true);
+
+        // Follow the workspace if it was active, or if it hid the active one
+        if active_workspace_ix == Some(workspace_ix) || active_workspace_ix == shown_ix {
+            let root_c_ix = self.tree.children_of(workspace_ix)[0];
+            self.tree.set_ancestor_paths_active(root_c_ix);
+            self.focus_on_next_container(workspace_ix);
+        } else if let Some(active_workspace_ix) = active_workspace_ix {
+            let root_c_ix = self.tree.children_of(active_workspace_ix)[0];
+            self.tree.set_ancestor_paths_active(root_c_ix);
+        }
+        // The workspace it hid is removed if there was nothing on it
+        if let Some(shown_ix) = shown_ix {
+            if !self.workspace_has_views(shown_ix) && self.placeholders_of(shown_ix).is_empty() {
+                try!(self.remove_workspace(shown_ix));
+            }
+        }
+        let root_ix = self.tree.root_ix();
+        self.layout(root_ix);
+        self.fill_output_with_fullscreen(workspace_ix);
+        mark_backgrounds_outdated();
+        self.validate();
+        self.validate_path();
+        Ok(())
+    }
+
+    /// Makes the fullscreen containers of the workspace cover its output
+    /// again, e.g after the workspace moved to another output.
+    fn fill_output_with_fullscreen(&mut self, workspace_ix: NodeIndex) {
+        let fullscreen_ixs = self.tree[workspace_ix].fullscreen_c()
+            .map(|ids| ids.iter().filter_map(|id| self.tree.lookup_id(*id)).collect())
+            .unwrap_or_else(Vec::new);
+        self.layout_fullscreen_apps(fullscreen_ixs);
+    }
+
+    /// Makes the views at or beneath the node be drawn on the output
+    /// the node is on.
+    pub fn bind_views_to_output(&self, node_ix: NodeIndex) {
+        let output = match self.tree.ancestor_of_type(node_ix, ContainerType::Output).ok()
+            .and_then(|output_ix| self.tree[output_ix].get_handle()) {
+            Some(Handle::Output(output)) => output,
+            _ => return
+        };
+        let mut nodes = self.tree.all_descendants_of(node_ix);
+        nodes.push(node_ix);
+        for view_ix in nodes {
+            if let Some(Handle::View(view)) = self.tree[view_ix].get_handle() {
+                view.set_output(output);
+            }
+        }
+    }
+
+    /// Moves the floating views of the workspace back onto their output,
+    /// in case they ended up outside of it (e.g the output is smaller).
+    fn keep_floating_on_output(&mut self, workspace_ix: NodeIndex) {
+        let output_ix = self.tree.parent_of(workspace_ix)
+            .expect("Workspace had no output");
+        let output_size = match self.tree[output_ix].get_actual_geometry() {
+            Some(geometry) => geometry.size,
+            None => return
+        };
+        let root_c_ix = self.tree.children_of(workspace_ix)[0];
+        for view_ix in self.tree.floating_children(root_c_ix) {
+            let container = &mut self.tree[view_ix];
+            if let Some(geometry) = container.get_geometry() {
+                let max_x = output_size.w as i32 - geometry.size.w as i32;
+                let max_y = output_size.h as i32 - geometry.size.h as i32;
+                let origin = Point {
+                    x: ::std::cmp::max(0, ::std::cmp::min(geometry.origin.x, max_x)),
+                    y: ::std::cmp::max(0, ::std::cmp::min(geometry.origin.y, max_y))
+                };
+                if origin != geometry.origin {
+                    container.set_geometry(ResizeEdge::empty(), Geometry {
+                        origin: origin,
+                        size: geometry.size
+                    });
+                }
+            }
+        }
+    }
+
+    /// Gets the lowest number that can be the name of a new workspace
+    /// on the output.
+    fn unused_workspace_name(&self, output_ix: NodeIndex) -> String {
+        (1..).map(|num: u32| num.to_string())
+            .find(|name| !self.name_taken(output_ix, name))
+            .expect("Every workspace name was taken")
+    }
+
+    /// Determines if a new workspace on the output can't have the name,
+    /// because it would be the same as another workspace.
+    fn name_taken(&self, output_ix: NodeIndex, name: &str) -> bool {
+        match WorkspacePolicy::current() {
+            WorkspacePolicy::Global => self.tree.workspace_ix_by_name(name).is_some(),
+            WorkspacePolicy::PerOutput => self.workspace_ix_on_output(output_ix, name).is_some()
+        }
+    }
+
+    /// Determines if the workspace is the one shown on its output.
+    pub fn workspace_visible(&self, workspace_ix: NodeIndex) -> bool {
+        self.tree.parent_of(workspace_ix).ok()
+            .and_then(|output_ix| self.tree.follow_path_until(output_ix,
+                                                              ContainerType::Workspace).ok())
+            == Some(workspace_ix)
+    }
+
+    /// Counts the views on the workspace, including floating and minimized ones.
+    pub fn view_count(&self, workspace_ix: NodeIndex) -> usize {
+        self.tree.all_descendants_of(workspace_ix).into_iter()
+            .filter(|ix| self.tree[*ix].get_type() == ContainerType::View)
+            .count()
+    }
+
+    /// Looks up a workspace by its name.
+    fn workspace_ix(&self, name: &str) -> Result<NodeIndex, TreeError> {
+        self.find_workspace(name)
+            .ok_or_else(|| TreeError::Workspace(WorkspaceErr::NotFound(name.into())))
+    }
+
+    /// Determines if the workspace has any views, counting minimized ones.
+    fn workspace_has_views(&self, workspace_ix: NodeIndex) -> bool {
+        self.tree.descendant_of_type(workspace_ix, ContainerType::View).is_ok()
+    }
+
     /// Moves the active container to a new workspace.
     pub fn send_active_to_workspace(&mut self, name: &str) {
         if let Some(active_ix) = self.active_container {
@@ -191,6 +672,13 @@ impl LayoutTree {
             debug!("Moving container {:?} to workspace {}",
                 self.get_active_container(), name);
             self.tree.move_node(active_ix, next_work_root_ix);
+            // The workspace might be on another output, where it could be shown
+            if self.tree.parent_of(next_work_ix).ok() != self.tree.parent_of(curr_work_ix).ok() {
+                self.bind_views_to_output(active_ix);
+                if self.workspace_visible(next_work_ix) {
+                    self.tree.set_family_visible(active_ix, true);
+                }
+            }
 
             // If it's a fullscreen app, then update the fullscreen lists
             self.transfer_fullscreen(curr_work_ix, next_work_ix, id);
@@ -229,7 +717,7 @@ impl LayoutTree {
     }
 
     /// Transfers a fullscreen app from this workspace to another.
-    fn transfer_fullscreen(&mut self, cur_work_ix: NodeIndex, next_work_ix: NodeIndex,
+    pub fn transfer_fullscreen(&mut self, cur_work_ix: NodeIndex, next_work_ix: NodeIndex,
                            fullscreen_id: Uuid) {
         if let Some(fullscreen_ids) = self.tree[cur_work_ix].fullscreen_c() {
             if !fullscreen_ids.iter().any(|id| *id == fullscreen_id) {
@@ -248,6 +736,23 @@ impl LayoutTree {
 #[cfg(test)]
 mod tests {
     use ::layout::core::tree::tests::basic_tree;
+    use ::layout::core::container::{Container, ContainerType};
+    use rustwlc::{Geometry, Point, ResizeEdge, Size, WlcView};
+    use ::layout::TreeError;
+    use super::{workspace_order, WorkspaceErr, WorkspacePolicy, WorkspaceScope};
+
+    fn active_workspace_name(tree: &::layout::LayoutTree) -> String {
+        let workspace_ix = tree.active_ix_of(ContainerType::Workspace)
+            .expect("No active workspace");
+        tree.tree[workspace_ix].get_name().unwrap().to_string()
+    }
+
+    /// Makes a workspace without switching to it
+    fn make_hidden_workspace(tree: &mut ::layout::LayoutTree, name: &str) {
+        tree.get_or_make_workspace(name);
+        let active_ix = tree.active_container.expect("No active container");
+        tree.tree.set_ancestor_paths_active(active_ix);
+    }
 
     #[test]
     pub fn switch_empty_workspaces() {
@@ -258,4 +763,177 @@ mod tests {
         tree.switch_to_workspace("4");
         tree.switch_to_workspace("2");
     }
+
+    #[test]
+    fn workspace_name_order() {
+        let mut names = vec!["web", "10", "2", "mail", "1"];
+        names.sort_by(|a, b| workspace_order(a, b));
+        assert_eq!(names, vec!["1", "2", "10", "mail", "web"]);
+    }
+
+    #[test]
+    fn neighbouring_workspaces() {
+        let mut tree = basic_tree();
+        let neighbour = |tree: &::layout::LayoutTree, scope, next, skip_empty| {
+            tree.neighbouring_workspace_ix(scope, next, skip_empty)
+                .and_then(|ix| tree.tree[ix].get_name().map(str::to_string))
+        };
+        assert_eq!(neighbour(&tree, WorkspaceScope::Global, true, false),
+                   Some("2".into()));
+        // wraps around
+        assert_eq!(neighbour(&tree, WorkspaceScope::Output, false, false),
+                   Some("2".into()));
+        make_hidden_workspace(&mut tree, "web");
+        assert_eq!(neighbour(&tree, WorkspaceScope::Global, false, false),
+                   Some("web".into()));
+        assert_eq!(neighbour(&tree, WorkspaceScope::Global, false, true),
+                   Some("2".into()));
+        tree.switch_to_neighbouring_workspace(WorkspaceScope::Output, true, true);
+        assert_eq!(active_workspace_name(&tree), "2");
+    }
+
+    #[test]
+    fn back_and_forth() {
+        let mut tree = basic_tree();
+        tree.switch_workspace_back_and_forth();
+        assert_eq!(active_workspace_name(&tree), "1");
+        tree.switch_to_workspace("2");
+        tree.switch_workspace_back_and_forth();
+        assert_eq!(active_workspace_name(&tree), "1");
+        tree.switch_workspace_back_and_forth();
+        assert_eq!(active_workspace_name(&tree), "2");
+    }
+
+    #[test]
+    fn rename_workspace() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        tree.rename_workspace("1", "web").unwrap();
+        assert!(tree.tree.workspace_ix_by_name("1").is_none());
+        assert!(tree.tree.workspace_ix_by_name("web").is_some());
+        assert_eq!(tree.rename_workspace("web", "2"),
+                   Err(TreeError::Workspace(WorkspaceErr::NameTaken("2".into()))));
+        assert_eq!(tree.rename_workspace("1", "3"),
+                   Err(TreeError::Workspace(WorkspaceErr::NotFound("1".into()))));
+        // going back follows the rename
+        tree.switch_workspace_back_and_forth();
+        assert_eq!(active_workspace_name(&tree), "web");
+    }
+
+    #[test]
+    fn reorder_workspace() {
+        let mut tree = basic_tree();
+        make_hidden_workspace(&mut tree, "3");
+        let names = |tree: &::layout::LayoutTree| {
+            let output_ix = tree.active_ix_of(ContainerType::Output).unwrap();
+            tree.tree.children_of(output_ix).into_iter()
+                .map(|ix| tree.tree[ix].get_name().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        tree.reorder_workspace("3", 0).unwrap();
+        assert_eq!(names(&tree), vec!["3", "1", "2"]);
+        tree.reorder_workspace("3", 10).unwrap();
+        assert_eq!(names(&tree), vec!["1", "2", "3"]);
+        tree.reorder_workspace("1", 1).unwrap();
+        assert_eq!(names(&tree), vec!["2", "1", "3"]);
+        // the active workspace didn't change
+        assert_eq!(active_workspace_name(&tree), "1");
+    }
+
+    #[test]
+    fn delete_workspace() {
+        let mut tree = basic_tree();
+        make_hidden_workspace(&mut tree, "3");
+        assert_eq!(tree.delete_workspace("2"),
+                   Err(TreeError::Workspace(WorkspaceErr::NotEmpty("2".into()))));
+        tree.delete_workspace("3").unwrap();
+        assert!(tree.tree.workspace_ix_by_name("3").is_none());
+        tree.switch_to_workspace("4");
+        assert_eq!(tree.delete_workspace("4"),
+                   Err(TreeError::Workspace(WorkspaceErr::Visible("4".into()))));
+    }
+
+    #[test]
+    fn move_workspace_to_output() {
+        let mut tree = basic_tree();
+        let root_ix = tree.tree.root_ix();
+        let new_output = WlcView::dummy(2).as_output();
+        let output_ix = tree.tree.add_child(root_ix, Container::new_output(new_output), false);
+        tree.init_workspace("3".into(), output_ix);
+        let active_ix = tree.active_container.unwrap();
+        tree.tree.set_ancestor_paths_active(active_ix);
+        let output_id = tree.tree[output_ix].get_id();
+        // the empty workspace shown on the output is replaced
+        tree.move_workspace_to_output("2", output_id).unwrap();
+        let workspace_2_ix = tree.tree.workspace_ix_by_name("2").unwrap();
+        assert_eq!(tree.tree.parent_of(workspace_2_ix).unwrap(), output_ix);
+        assert!(tree.workspace_visible(workspace_2_ix));
+        assert!(tree.tree.workspace_ix_by_name("3").is_none());
+        assert_eq!(active_workspace_name(&tree), "1");
+        // the active workspace stays active, and leaves a new one behind
+        tree.move_workspace_to_output("1", output_id).unwrap();
+        let workspace_1_ix = tree.tree.workspace_ix_by_name("1").unwrap();
+        assert_eq!(tree.tree.parent_of(workspace_1_ix).unwrap(), output_ix);
+        assert!(tree.workspace_visible(workspace_1_ix));
+        assert_eq!(active_workspace_name(&tree), "1");
+        assert!(tree.tree.workspace_ix_by_name("3").is_some());
+    }
+
+    #[test]
+    fn move_fullscreen_workspace_to_output() {
+        let mut tree = basic_tree();
+        let root_ix = tree.tree.root_ix();
+        let new_output = WlcView::dummy(2).as_output();
+        let output_ix = tree.tree.add_child(root_ix, Container::new_output(new_output), false);
+        tree.init_workspace("3".into(), output_ix);
+        let active_ix = tree.active_container.unwrap();
+        tree.tree.set_ancestor_paths_active(active_ix);
+        let output_id = tree.tree[output_ix].get_id();
+        let workspace_2_ix = tree.tree.workspace_ix_by_name("2").unwrap();
+        let root_c_ix = tree.tree.children_of(workspace_2_ix)[0];
+        let container_ix = tree.tree.children_of(root_c_ix)[0];
+        let container_id = tree.tree[container_ix].get_id();
+        tree.tree[container_ix].set_fullscreen(true).unwrap();
+        tree.tree[workspace_2_ix].update_fullscreen_c(container_id, true).unwrap();
+        // Where it was on the old output
+        tree.tree[container_ix].set_geometry(ResizeEdge::empty(), Geometry {
+            origin: Point { x: -100, y: -100 },
+            size: Size { w: 1, h: 1 }
+        });
+        tree.move_workspace_to_output("2", output_id).unwrap();
+        let workspace_2_ix = tree.tree.workspace_ix_by_name("2").unwrap();
+        assert_eq!(tree.tree[workspace_2_ix].fullscreen_c(), Some(&vec![container_id]));
+        let container_ix = tree.tree.lookup_id(container_id).unwrap();
+        let output_ix = tree.tree.lookup_id(output_id).unwrap();
+        assert_eq!(tree.tree[container_ix].get_geometry(),
+                   tree.tree[output_ix].get_actual_geometry());
+    }
+
+    #[test]
+    fn workspace_policies() {
+        let mut tree = basic_tree();
+        let root_ix = tree.tree.root_ix();
+        let first_output_ix = tree.active_ix_of(ContainerType::Output).unwrap();
+        let new_output = WlcView::dummy(2).as_output();
+        let output_ix = tree.tree.add_child(root_ix, Container::new_output(new_output), false);
+        tree.init_workspace("web".into(), output_ix);
+        let active_ix = tree.active_container.unwrap();
+        tree.tree.set_ancestor_paths_active(active_ix);
+        let web_ix = tree.tree.workspace_ix_by_name("web").unwrap();
+        assert_eq!(tree.find_workspace_with("web", WorkspacePolicy::Global), Some(web_ix));
+        assert_eq!(tree.find_workspace_with("web", WorkspacePolicy::PerOutput), None);
+        // each output gets its own
+        let other_web_ix = tree.get_or_make_workspace_with("web", WorkspacePolicy::PerOutput);
+        assert!(other_web_ix != web_ix);
+        assert_eq!(tree.tree.parent_of(other_web_ix).unwrap(), first_output_ix);
+        let active_ix = tree.active_container.unwrap();
+        tree.tree.set_ancestor_paths_active(active_ix);
+        tree.delete_workspace("web").unwrap();
+        // switching to a workspace on another output leaves this one shown
+        tree.switch_to_workspace("web");
+        assert_eq!(tree.active_ix_of(ContainerType::Workspace), Some(web_ix));
+        let workspace_1_ix = tree.tree.workspace_ix_by_name("1").unwrap();
+        assert!(tree.workspace_visible(workspace_1_ix));
+        assert!(tree.workspace_visible(web_ix));
+    }
 }
@@ -1,14 +1,19 @@
 //! Commands from the user to manipulate the tree
 
-use super::{try_lock_tree, lock_tree, try_lock_action};
-use super::{Action, ActionErr, Bar, Container, ContainerType,
-            Direction, Handle, Layout, TreeError};
+use super::{try_lock_tree, lock_tree, try_lock_action, try_lock_hints, try_lock_switcher};
+use super::{Action, ActionErr, Bar, BorderSettings, Container, ContainerType,
+            Direction, Edge, Handle, Layout, TreeError, WorkspaceScope,
+            HintAction, HintInput, TitleBarClick, TitleBarPart};
 use super::Tree;
+use super::core::rules;
 use ::registry;
+use ::render::{Background, StatusBar};
 
 use uuid::Uuid;
-use rustwlc::{Point, Size, Geometry, ResizeEdge, WlcView, WlcOutput, ViewType};
+use rustwlc::{Point, Size, Geometry, ResizeEdge, WlcView, WlcOutput, ViewType, KeyMod,
+              KeyState, MOD_CTRL, MOD_ALT, MOD_MOD4, MOD_SHIFT};
 use rustwlc::input::pointer;
+use rustwlc::xkb::{Keysym, keysyms};
 use rustc_serialize::json::{Json, ToJson};
 
 pub type CommandResult = Result<(), TreeError>;
@@ -34,7 +39,7 @@ pub fn remove_active() {
         // views have it removed in view_destroyed callback
         // container should be removed here though.
         if handle_to_remove.is_none() {
-            if let Err(err) = tree.0.remove_active() {
+            if let Err(err) = tree.0.record_change(|tree| tree.remove_active().map(|_| ())) {
                 warn!("Could not remove the active container! {:?}\n{:?}\n{:?}",
                       tree.0.get_active_container(), err, *tree.0);
             };
@@ -71,13 +76,13 @@ pub fn tile_switch() {
 
 pub fn split_vertical() {
     if let Ok(mut tree) = try_lock_tree() {
-        tree.0.toggle_active_layout(Layout::Vertical).ok();
+        tree.0.record_change(|tree| tree.toggle_active_layout(Layout::Vertical)).ok();
     }
 }
 
 pub fn split_horizontal() {
     if let Ok(mut tree) = try_lock_tree() {
-        tree.0.toggle_active_layout(Layout::Horizontal).ok();
+        tree.0.record_change(|tree| tree.toggle_active_layout(Layout::Horizontal)).ok();
     }
 }
 
@@ -167,6 +172,335 @@ pub fn move_active_down() {
     }
 }
 
+pub fn minimize_active() {
+    if let Ok(mut tree) = try_lock_tree() {
+        if let Some(id) = tree.active_id() {
+            tree.minimize_view(id).unwrap_or_else(|err| {
+                warn!("Could not minimize the active view: {:#?}", err);
+            });
+        }
+    }
+}
+
+/// Restores the most recently minimized view of the active workspace
+pub fn restore_minimized() {
+    if let Ok(mut tree) = try_lock_tree() {
+        let maybe_id = tree.0.active_ix_of(ContainerType::Workspace)
+            .and_then(|workspace_ix| tree.0.minimized_views_of(workspace_ix)
+                      .last().cloned())
+            .map(|view_ix| tree.0.tree[view_ix].get_id());
+        if let Some(id) = maybe_id {
+            tree.restore_view(id).unwrap_or_else(|err| {
+                warn!("Could not restore minimized view: {:#?}", err);
+            });
+        }
+    }
+}
+
+/// Focuses on a view that wants attention, switching workspaces if need be
+pub fn focus_urgent() {
+    if let Ok(mut tree) = try_lock_tree() {
+        tree.focus_urgent().unwrap_or_else(|err| {
+            warn!("Could not focus on an urgent view: {:#?}", err);
+        });
+    }
+}
+
+/// Labels every visible view, typing a label focuses on that view
+pub fn hint_focus() {
+    start_hints(HintAction::Focus);
+}
+
+/// Labels every visible view, typing a label swaps the active view with it
+pub fn hint_swap() {
+    start_hints(HintAction::Swap);
+}
+
+/// Labels every visible view, typing a label moves the active view next to it
+pub fn hint_move() {
+    start_hints(HintAction::Move);
+}
+
+fn start_hints(action: HintAction) {
+    if let Ok(tree) = try_lock_tree() {
+        match tree.0.start_hints(action) {
+            Ok(hints) => {
+                if let Ok(mut lock) = try_lock_hints() {
+                    *lock = Some(hints);
+                }
+                schedule_render();
+            },
+            Err(err) => warn!("Could not start hint mode: {:#?}", err)
+        }
+    }
+}
+
+/// Passes a key press to hint mode, if it is active.
+///
+/// Returns whether hint mode took the key, in which case it should not be
+/// passed on to the focused view. Escape, or a key that is not part of
+/// any label, leaves hint mode.
+#[allow(deprecated)] // keysyms
+pub fn hint_key(sym: &Keysym) -> bool {
+    let chosen = {
+        let mut lock = match try_lock_hints() {
+            Ok(lock) => lock,
+            Err(_) => return false
+        };
+        let input = match *lock {
+            Some(ref mut hints) => {
+                if *sym == keysyms::KEY_Escape {
+                    HintInput::NoMatch
+                } else {
+                    match ::std::char::from_u32(sym.to_utf32()) {
+                        Some(letter) if letter != '\0' => hints.type_char(letter),
+                        // Modifiers and such don't leave hint mode
+                        _ => return true
+                    }
+                }
+            },
+            None => return false
+        };
+        match input {
+            HintInput::Pending => None,
+            HintInput::NoMatch => {
+                lock.take();
+                None
+            },
+            HintInput::Chosen(id) => lock.take().map(|hints| (hints.action(), id))
+        }
+    };
+    if let Some((action, id)) = chosen {
+        if let Ok(mut tree) = try_lock_tree() {
+            tree.hint_chosen(action, id).unwrap_or_else(|err| {
+                warn!("Could not act on the chosen hint: {:#?}", err);
+            });
+        }
+    }
+    schedule_render();
+    true
+}
+
+/// Opens the window switcher, or selects the next view if it's already open
+pub fn switcher_next() {
+    cycle_switcher(true);
+}
+
+/// Opens the window switcher, or selects the previous view if it's already open
+pub fn switcher_prev() {
+    cycle_switcher(false);
+}
+
+fn cycle_switcher(forward: bool) {
+    if let Ok(mut lock) = try_lock_switcher() {
+        if let Some(ref mut switcher) = *lock {
+            switcher.select_next(forward);
+            schedule_render();
+            return
+        }
+        if let Ok(tree) = try_lock_tree() {
+            match tree.0.start_switcher() {
+                Ok(mut switcher) => {
+                    if !forward {
+                        // Skip past the view after the active one
+                        switcher.select_next(false);
+                        switcher.select_next(false);
+                    }
+                    *lock = Some(switcher);
+                },
+                Err(err) => warn!("Could not open the window switcher: {:#?}", err)
+            }
+        }
+    }
+    schedule_render();
+}
+
+/// Gets the modifier a modifier key sets, if the key is one.
+#[allow(deprecated)] // keysyms
+fn modifier_of(sym: &Keysym) -> Option<KeyMod> {
+    if *sym == keysyms::KEY_Alt_L || *sym == keysyms::KEY_Alt_R {
+        Some(MOD_ALT)
+    } else if *sym == keysyms::KEY_Super_L || *sym == keysyms::KEY_Super_R {
+        Some(MOD_MOD4)
+    } else if *sym == keysyms::KEY_Control_L || *sym == keysyms::KEY_Control_R {
+        Some(MOD_CTRL)
+    } else {
+        None
+    }
+}
+
+/// Passes a key to the window switcher, if it is open.
+///
+/// Returns whether the switcher took the key, in which case it should not be
+/// passed on to the focused view. Tab and the arrow keys move the selection,
+/// typing filters the list, and letting go of the modifier that was held when
+/// the switcher was opened (or Return) focuses on the selected view.
+/// Escape closes the switcher without doing anything.
+#[allow(deprecated)] // keysyms
+pub fn switcher_key(sym: &Keysym, mods: KeyMod, state: KeyState) -> bool {
+    let chosen = {
+        let mut lock = match try_lock_switcher() {
+            Ok(lock) => lock,
+            Err(_) => return false
+        };
+        let done = match *lock {
+            Some(ref mut switcher) => {
+                let pressed = state == KeyState::Pressed;
+                // Shift picks the direction, it doesn't keep the switcher open
+                let holding = mods & (MOD_CTRL | MOD_ALT | MOD_MOD4);
+                let held = match switcher.held() {
+                    Some(held) => held,
+                    None => {
+                        switcher.set_held(holding);
+                        holding
+                    }
+                };
+                let released = !pressed && modifier_of(sym)
+                    .map_or(false, |modifier| held.contains(modifier));
+                if released || (!held.is_empty() && (holding & held).is_empty()) {
+                    Some(true)
+                } else if !pressed {
+                    None
+                } else if *sym == keysyms::KEY_Escape {
+                    Some(false)
+                } else if *sym == keysyms::KEY_Return {
+                    Some(true)
+                } else if *sym == keysyms::KEY_Tab || *sym == keysyms::KEY_ISO_Left_Tab {
+                    switcher.select_next(!mods.contains(MOD_SHIFT));
+                    None
+                } else if *sym == keysyms::KEY_Down {
+                    switcher.select_next(true);
+                    None
+                } else if *sym == keysyms::KEY_Up {
+                    switcher.select_next(false);
+                    None
+                } else if *sym == keysyms::KEY_BackSpace {
+                    switcher.backspace();
+                    None
+                } else {
+                    match ::std::char::from_u32(sym.to_utf32()) {
+                        Some(letter) if letter != '\0' && !letter.is_control() => {
+                            switcher.type_char(letter)
+                        },
+                        // Modifiers and such don't close the switcher
+                        _ => {}
+                    }
+                    None
+                }
+            },
+            None => return false
+        };
+        match done {
+            Some(true) => lock.take().and_then(|switcher| switcher.chosen()),
+            Some(false) => {
+                lock.take();
+                None
+            },
+            None => None
+        }
+    };
+    if let Some(id) = chosen {
+        if let Ok(mut tree) = try_lock_tree() {
+            tree.focus_anywhere(id).unwrap_or_else(|err| {
+                warn!("Could not focus on the view chosen in the switcher: {:#?}", err);
+            });
+        }
+    }
+    schedule_render();
+    true
+}
+
+/// Asks every output to be drawn again, e.g after the labels of hint mode changed.
+fn schedule_render() {
+    for output in WlcOutput::list() {
+        output.schedule_render();
+    }
+}
+
+pub fn undo() {
+    if let Ok(mut tree) = try_lock_tree() {
+        tree.undo().unwrap_or_else(|err| {
+            warn!("Could not undo: {:#?}", err);
+        });
+    }
+}
+
+pub fn redo() {
+    if let Ok(mut tree) = try_lock_tree() {
+        tree.redo().unwrap_or_else(|err| {
+            warn!("Could not redo: {:#?}", err);
+        });
+    }
+}
+
+pub fn switch_workspace_next() {
+    switch_workspace_neighbour(WorkspaceScope::Global, true);
+}
+
+pub fn switch_workspace_prev() {
+    switch_workspace_neighbour(WorkspaceScope::Global, false);
+}
+
+pub fn switch_workspace_next_on_output() {
+    switch_workspace_neighbour(WorkspaceScope::Output, true);
+}
+
+pub fn switch_workspace_prev_on_output() {
+    switch_workspace_neighbour(WorkspaceScope::Output, false);
+}
+
+pub fn switch_workspace_back_and_forth() {
+    if let Ok(mut tree) = try_lock_tree() {
+        tree.switch_workspace_back_and_forth().unwrap_or_else(|err| {
+            warn!("Could not switch back and forth: {:#?}", err);
+        });
+    }
+}
+
+/// Moves the active workspace to the output after the active one,
+/// wrapping around to the first output.
+pub fn move_workspace_to_next_output() {
+    if let Ok(mut tree) = try_lock_tree() {
+        let maybe_target = tree.0.active_ix_of(ContainerType::Workspace)
+            .and_then(|workspace_ix| {
+                let output_ix = tree.0.tree.parent_of(workspace_ix).ok();
+                let outputs = tree.0.outputs_in_order();
+                outputs.iter().position(|ix| Some(*ix) == output_ix)
+                    .map(|pos| outputs[(pos + 1) % outputs.len()])
+                    .and_then(|next_ix| if Some(next_ix) != output_ix {
+                        Some((tree.0.tree[workspace_ix].name(), tree.0.tree[next_ix].get_id()))
+                    } else {
+                        None
+                    })
+            });
+        if let Some((name, output_id)) = maybe_target {
+            tree.move_workspace_to_output(&name, output_id).unwrap_or_else(|err| {
+                warn!("Could not move workspace {} to the next output: {:#?}", name, err);
+            });
+        }
+    }
+}
+
+/// Switches to the next or previous workspace, skipping empty workspaces
+/// if the `skip_empty` option of the workspaces category is set.
+fn switch_workspace_neighbour(scope: WorkspaceScope, next: bool) {
+    let skip_empty = {
+        let lock = registry::clients_read();
+        let client = lock.client(Uuid::nil()).unwrap();
+        let handle = registry::ReadHandle::new(&client);
+        handle.read("workspaces".into()).ok()
+            .and_then(|workspaces| workspaces.get("skip_empty")
+                      .and_then(|data| data.as_boolean()))
+            .unwrap_or(false)
+    };
+    if let Ok(mut tree) = try_lock_tree() {
+        tree.switch_to_neighbouring_workspace(scope, next, skip_empty)
+            .unwrap_or_else(|err| {
+                warn!("Could not switch workspace: {:#?}", err);
+            });
+    }
+}
+
 pub fn tree_as_json() -> Json {
     if let Ok(tree) = lock_tree() {
         tree.0.to_json()
@@ -256,7 +590,7 @@ impl Tree {
     }
 
     pub fn toggle_cardinal_tiling(&mut self, id: Uuid) -> CommandResult {
-        self.0.toggle_cardinal_tiling(id)
+        self.0.record_change(|tree| tree.toggle_cardinal_tiling(id))
             .and_then(|_| self.layout_active_of(ContainerType::Workspace))
     }
 
@@ -269,7 +603,7 @@ impl Tree {
                         .or_else(|| self.0.get_active_container()
                                  .and_then(|container| Some(container.get_id())))
                         .ok_or(TreeError::NoActiveContainer));
-        try!(self.0.move_container(uuid, direction));
+        try!(self.0.record_change(|tree| tree.move_container(uuid, direction)));
         // NOTE Make this not layout the active, but actually the node index's workspace.
         try!(self.layout_active_of(ContainerType::Output));
         Ok(())
@@ -303,12 +637,127 @@ impl Tree {
             .collect()
     }
 
-    pub fn output_resolution(&self, id: Uuid) -> Result<Size, TreeError> {
-        let output = match try!(self.0.lookup(id)).get_handle() {
-            Some(Handle::Output(output)) => output,
-            _ => return Err(TreeError::UuidNotAssociatedWith(ContainerType::Output))
-        };
-        Ok(output.get_resolution().expect("Output had no resolution"))
+    /// Gets the UUID of the output with the name (e.g "DP-1"), if there is one.
+    pub fn output_by_name(&self, name: &str) -> Option<Uuid> {
+        let root_ix = self.0.tree.root_ix();
+        self.0.tree.children_of(root_ix).into_iter()
+            .find(|output_ix| self.0.tree[*output_ix].name() == name)
+            .map(|output_ix| self.0.tree[output_ix].get_id())
+    }
+
+    /// Gets the resolution of the output, in physical and in logical pixels.
+    pub fn output_sizes(&self, id: Uuid) -> Result<(Size, Size), TreeError> {
+        self.0.output_sizes(id)
+    }
+
+    /// Gets the scale factor of the output.
+    pub fn output_scale(&self, id: Uuid) -> Result<u32, TreeError> {
+        self.0.output_scale(id)
+    }
+
+    /// Sets the scale factor of the output, which must be between 1 and 4.
+    pub fn set_output_scale(&mut self, id: Uuid, scale: u32) -> CommandResult {
+        self.0.set_output_scale(id, scale)
+    }
+
+    /// Applies the settings of the outputs category to every output.
+    pub fn apply_output_settings(&mut self) -> CommandResult {
+        self.0.apply_output_settings();
+        Ok(())
+    }
+
+    /// Applies settings (resolution, position, scale and enabled)
+    /// to the output.
+    pub fn configure_output(&mut self, id: Uuid, settings: &Json) -> CommandResult {
+        self.0.configure_output(id, settings)
+    }
+
+    /// Sets the resolution of the output, in physical pixels.
+    pub fn set_output_resolution(&mut self, id: Uuid, size: Size) -> CommandResult {
+        self.0.set_output_resolution(id, size)
+    }
+
+    /// Sets where the output is in the arrangement of the outputs.
+    pub fn set_output_position(&mut self, id: Uuid, position: Point) -> CommandResult {
+        self.0.set_output_position(id, position)
+    }
+
+    /// Starts or stops using the output, moving its workspaces away
+    /// when it is disabled.
+    pub fn set_output_enabled(&mut self, id: Uuid, enabled: bool) -> CommandResult {
+        self.0.set_output_enabled(id, enabled)
+    }
+
+    /// Gets the UUID of the output the active container is on.
+    pub fn active_output_id(&self) -> Option<Uuid> {
+        self.0.active_ix_of(ContainerType::Output)
+            .map(|output_ix| self.0.tree[output_ix].get_id())
+    }
+
+    /// Gets a JSON object describing the output.
+    pub fn output_info(&self, id: Uuid) -> Result<Json, TreeError> {
+        self.0.output_handle(id).map(|(output_ix, _)| self.0.output_info(output_ix))
+    }
+
+    /// Gets the name of the output, e.g "DP-1".
+    pub fn output_name(&self, id: Uuid) -> Result<String, TreeError> {
+        self.0.output_handle(id).map(|(_, output)| output.get_name())
+    }
+
+    /// Gets where the output is in the arrangement of the outputs.
+    pub fn output_position(&self, id: Uuid) -> Result<Point, TreeError> {
+        self.0.output_handle(id).map(|(output_ix, _)| self.0.output_position(output_ix))
+    }
+
+    /// Gets the workspace shown on the output, and all the workspaces on it.
+    pub fn output_workspaces(&self, id: Uuid)
+                             -> Result<(Option<String>, Vec<String>), TreeError> {
+        self.0.output_handle(id).map(|(output_ix, _)| {
+            (self.0.output_active_workspace(output_ix),
+             self.0.output_workspaces(output_ix))
+        })
+    }
+
+    /// Determines if the output is the one with the active container on it.
+    pub fn output_focused(&self, id: Uuid) -> Result<bool, TreeError> {
+        self.0.output_handle(id).map(|(output_ix, _)| self.0.output_focused(output_ix))
+    }
+
+    /// Reserves space along the edge of the output behind the id,
+    /// returning the id of the reservation.
+    pub fn reserve_edge(&mut self, output_id: Uuid, edge: Edge, size: u32)
+                        -> Result<Uuid, TreeError> {
+        self.0.reserve_edge(output_id, edge, size, None)
+    }
+
+    /// Releases the reserved space with the id.
+    pub fn release_reservation(&mut self, id: Uuid) -> CommandResult {
+        self.0.release_reservation(id)
+    }
+
+    /// Reserves space for the view if a rule makes it a panel,
+    /// returning whether it did.
+    pub fn add_panel(&mut self, view: WlcView) -> Result<bool, TreeError> {
+        self.0.add_panel(view)
+    }
+
+    /// Fits the space reserved for the view to the geometry it asked for,
+    /// returning whether the view is a panel.
+    pub fn resize_panel(&mut self, view: WlcView, geometry: Geometry) -> bool {
+        self.0.resize_panel(view, geometry)
+    }
+
+    /// Releases the space reserved for the view, returning whether there was any.
+    pub fn release_view_reservations(&mut self, view: WlcView) -> bool {
+        self.0.release_view_reservations(view)
+    }
+
+    /// Gets a JSON list describing every output, in the order they were added.
+    pub fn outputs_info(&self) -> Json {
+        let root_ix = self.0.tree.root_ix();
+        Json::Array(self.0.tree.children_of(root_ix).into_iter()
+                    .map(|output_ix| self.0.output_info(output_ix))
+                    .collect())
     }
 
     /// Binds a view to be the background for the given outputs.
@@ -318,12 +767,101 @@ impl Tree {
         self.0.attach_background(view, outputs)
     }
 
+    /// Applies a layout template to the workspace, see `template.rs`
+    pub fn apply_template(&mut self, workspace_name: &str, template: &Json) -> CommandResult {
+        self.0.apply_template(workspace_name, template)
+    }
+
     /// Adds a Workspace to the tree. Never fails
     pub fn switch_to_workspace(&mut self, name: &str) -> CommandResult {
+        let output_ix = self.0.active_ix_of(ContainerType::Output);
+        let workspace_ix = self.0.active_ix_of(ContainerType::Workspace);
         self.0.switch_to_workspace(name);
+        self.0.warp_pointer_if_output_changed(output_ix);
+        self.0.announce_workspace_switch(workspace_ix);
         Ok(())
     }
 
+    /// Switches to the workspace after (or before) the active one. Never fails
+    pub fn switch_to_neighbouring_workspace(&mut self, scope: WorkspaceScope, next: bool,
+                                            skip_empty: bool) -> CommandResult {
+        let output_ix = self.0.active_ix_of(ContainerType::Output);
+        let workspace_ix = self.0.active_ix_of(ContainerType::Workspace);
+        self.0.switch_to_neighbouring_workspace(scope, next, skip_empty);
+        self.0.warp_pointer_if_output_changed(output_ix);
+        self.0.announce_workspace_switch(workspace_ix);
+        Ok(())
+    }
+
+    /// Switches back to the previously active workspace. Never fails
+    pub fn switch_workspace_back_and_forth(&mut self) -> CommandResult {
+        let output_ix = self.0.active_ix_of(ContainerType::Output);
+        let workspace_ix = self.0.active_ix_of(ContainerType::Workspace);
+        self.0.switch_workspace_back_and_forth();
+        self.0.warp_pointer_if_output_changed(output_ix);
+        self.0.announce_workspace_switch(workspace_ix);
+        Ok(())
+    }
+
+    /// Renames a workspace, failing if the new name is already taken
+    pub fn rename_workspace(&mut self, name: &str, new_name: &str) -> CommandResult {
+        self.0.rename_workspace(name, new_name)
+    }
+
+    /// Moves a workspace to the position among the workspaces of its output
+    pub fn reorder_workspace(&mut self, name: &str, position: usize) -> CommandResult {
+        self.0.reorder_workspace(name, position)
+    }
+
+    /// Moves a workspace, with everything on it, to the output
+    pub fn move_workspace_to_output(&mut self, name: &str, output_id: Uuid) -> CommandResult {
+        self.0.move_workspace_to_output(name, output_id)
+    }
+
+    /// Deletes a workspace that has no views on it
+    pub fn delete_workspace(&mut self, name: &str) -> CommandResult {
+        self.0.delete_workspace(name)
+    }
+
+    /// Gets a JSON list of every workspace, in the order of their outputs
+    /// and their order on the output.
+    pub fn workspaces(&self) -> Json {
+        use std::collections::BTreeMap;
+        let tree = &self.0.tree;
+        let active_workspace = self.0.active_ix_of(ContainerType::Workspace);
+        let mut workspaces = vec![];
+        for output_ix in tree.children_of(tree.root_ix()) {
+            let output_name = tree[output_ix].name();
+            for workspace_ix in tree.children_of(output_ix) {
+                let name = tree[workspace_ix].get_name()
+                    .expect("Workspace had no name");
+                let mut workspace_map = BTreeMap::new();
+                workspace_map.insert("name".into(), name.to_json());
+                workspace_map.insert("output".into(), output_name.to_json());
+                workspace_map.insert("visible".into(),
+                                     self.0.workspace_visible(workspace_ix).to_json());
+                workspace_map.insert("focused".into(),
+                                     (active_workspace == Some(workspace_ix)).to_json());
+                workspace_map.insert("urgent".into(),
+                                     self.0.workspace_urgent(workspace_ix).to_json());
+                workspace_map.insert("views".into(),
+                                     self.0.view_count(workspace_ix).to_json());
+                workspaces.push(Json::Object(workspace_map));
+            }
+        }
+        Json::Array(workspaces)
+    }
+
+    /// Draws the status bar of the output again, if what it shows changed.
+    pub fn update_status_bar(&self, bars: &mut Vec<StatusBar>, output: WlcOutput) {
+        self.0.update_status_bar(bars, output)
+    }
+
+    /// Paints the background of the output again, if it changed.
+    pub fn update_background(&self, backgrounds: &mut Vec<Background>, output: WlcOutput) {
+        self.0.update_background(backgrounds, output)
+    }
+
     /// Tiles the active container of some container type. Never fails
     pub fn layout_active_of(&mut self, c_type: ContainerType) -> CommandResult {
         self.0.layout_active_of(c_type);
@@ -332,15 +870,63 @@ impl Tree {
 
     /// Attempts to set the node behind the id to be floating
     pub fn float_container(&mut self, id: Uuid) -> CommandResult {
-        self.0.float_container(id)
+        self.0.record_change(|tree| tree.float_container(id))
     }
 
     /// Attempts to set the node behind the id to be not floating
     pub fn ground_container(&mut self, id: Uuid) -> CommandResult {
-        self.0.ground_container(id)
+        self.0.record_change(|tree| tree.ground_container(id))
     }
 
-    /// Adds a view to the workspace of the active container
+    /// Undoes the last structural change to the layout
+    pub fn undo(&mut self) -> CommandResult {
+        self.0.undo()
+    }
+
+    /// Redoes the last structural change to the layout that was undone
+    pub fn redo(&mut self) -> CommandResult {
+        self.0.redo()
+    }
+
+    /// Minimizes the view behind the id, hiding it until it is restored
+    pub fn minimize_view(&mut self, id: Uuid) -> CommandResult {
+        self.0.minimize_view(id)
+    }
+
+    /// Restores the minimized view behind the id to where it was minimized
+    /// from, switching to its workspace and focusing on it.
+    pub fn restore_view(&mut self, id: Uuid) -> CommandResult {
+        self.0.restore_view(id)
+    }
+
+    /// Gets a JSON list of every minimized view, with their id, title,
+    /// and the name of the workspace they will be restored to.
+    pub fn minimized_views(&self) -> Json {
+        use std::collections::BTreeMap;
+        let root_ix = self.0.tree.root_ix();
+        let views = self.0.minimized_views_of(root_ix).into_iter()
+            .map(|view_ix| {
+                let container = &self.0.tree[view_ix];
+                let workspace_ix = self.0.tree
+                    .ancestor_of_type(view_ix, ContainerType::Workspace)
+                    .expect("Minimized view was not in a workspace");
+                let workspace_name = self.0.tree[workspace_ix].get_name()
+                    .expect("Workspace had no name");
+                let title = match container.get_handle() {
+                    Some(Handle::View(view)) => view.get_title(),
+                    _ => unreachable!()
+                };
+                let mut view_map = BTreeMap::new();
+                view_map.insert("id".into(), container.get_id().to_string().to_json());
+                view_map.insert("title".into(), title.to_json());
+                view_map.insert("workspace".into(), workspace_name.to_json());
+                Json::Object(view_map)
+            }).collect();
+        Json::Array(views)
+    }
+
+    /// Adds a view to the workspace of the active container,
+    /// unless there is a placeholder from a layout template waiting for it.
     pub fn add_view(&mut self, view: WlcView) -> CommandResult {
         let tree = &mut self.0;
         let output = view.get_output();
@@ -352,7 +938,11 @@ impl Tree {
         if view.get_type() != ViewType::empty() || has_parent {
             try!(tree.add_floating_view(view, None));
         } else {
-            try!(tree.add_view(view));
+            if let Some(placeholder_ix) = tree.placeholder_for(view) {
+                try!(tree.fill_placeholder(placeholder_ix, view));
+            } else {
+                try!(tree.add_view(view));
+            }
             tree.normalize_view(view);
         }
         tree.layout_active_of(ContainerType::Workspace);
@@ -402,12 +992,82 @@ impl Tree {
     pub fn update_title(&mut self, view: WlcView) -> CommandResult {
         let id = try!(self.lookup_view(view)
                       .map_err(|_|TreeError::ViewNotFound(view)));
-        let container = try!(self.0.lookup_mut(id));
-        container.set_name(Container::get_title(view));
-        container.draw_borders();
+        let node_ix = try!(self.0.tree.lookup_view(view)
+                           .ok_or(TreeError::ViewNotFound(view)));
+        self.0.update_title_bar(node_ix);
+        self.0.tree[node_ix].draw_borders();
+        let urgent_on_title_change = rules::options_for(view)
+            .get("urgent_on_title_change")
+            .and_then(|value| value.as_boolean())
+            .unwrap_or(false);
+        if urgent_on_title_change {
+            try!(self.0.set_urgent(id, true));
+        }
+        Ok(())
+    }
+
+    /// Marks the view behind the id as wanting attention, or clears the mark.
+    ///
+    /// The active view can't be marked.
+    pub fn set_urgent(&mut self, id: Uuid, urgent: bool) -> CommandResult {
+        self.0.set_urgent(id, urgent)
+    }
+
+    /// Sets the style and colors of the borders of the view behind the id,
+    /// instead of what its rules say.
+    pub fn set_border_settings(&mut self, id: Uuid, settings: BorderSettings)
+                               -> CommandResult {
+        self.0.set_border_settings(id, settings)
+    }
+
+    /// Updates the borders of the view, after it changed whether it draws
+    /// its own decorations.
+    pub fn update_border_style(&mut self, view: WlcView) -> CommandResult {
+        self.0.update_border_style(view)
+    }
+
+    /// Focuses on a view that wants attention, switching to its workspace
+    /// or restoring it if need be.
+    pub fn focus_urgent(&mut self) -> CommandResult {
+        let output_ix = self.0.active_ix_of(ContainerType::Output);
+        try!(self.0.focus_urgent());
+        self.0.warp_pointer_if_output_changed(output_ix);
+        Ok(())
+    }
+
+    /// Focuses on the view behind the id, switching to its workspace
+    /// or restoring it if need be.
+    pub fn focus_anywhere(&mut self, id: Uuid) -> CommandResult {
+        let output_ix = self.0.active_ix_of(ContainerType::Output);
+        try!(self.0.focus_anywhere(id));
+        self.0.warp_pointer_if_output_changed(output_ix);
         Ok(())
     }
 
+    /// Gets the names of the workspaces with views that want attention.
+    pub fn urgent_workspaces(&self) -> Vec<String> {
+        self.0.urgent_workspaces()
+    }
+
+    /// Does the action of hint mode to the view behind the id.
+    pub fn hint_chosen(&mut self, action: HintAction, id: Uuid) -> CommandResult {
+        match action {
+            HintAction::Focus => self.0.hint_chosen(action, id),
+            HintAction::Swap | HintAction::Move => {
+                self.0.record_change(|tree| tree.hint_chosen(action, id))
+            }
+        }
+    }
+
+    /// Handles the view asking for focus, according to its focus policy.
+    ///
+    /// `previous` is the container that was active before the view was made,
+    /// which is focused again if the view isn't allowed to take the focus.
+    pub fn focus_requested(&mut self, view: WlcView, previous: Option<Uuid>)
+                           -> CommandResult {
+        self.0.focus_requested(view, previous)
+    }
+
     /// Sets the view to be the new active container.
     /// Will fail if the container is floating.
     pub fn set_active_view(&mut self, view: WlcView) -> CommandResult {
@@ -416,6 +1076,42 @@ impl Tree {
         Ok(())
     }
 
+    /// Finds the title bar under the point on the focused output,
+    /// and the part of it that the point is on.
+    pub fn title_bar_at(&self, point: Point) -> Option<(WlcView, TitleBarPart)> {
+        self.0.title_bar_at(point).and_then(|(view_ix, part)| {
+            match self.0.tree[view_ix] {
+                Container::View { handle, .. } => Some((handle, part)),
+                _ => None
+            }
+        })
+    }
+
+    /// Focuses on the view whose title bar was clicked, then toggles its
+    /// floating or fullscreen if that's what the click does.
+    ///
+    /// Closing and dragging the view are left to the caller.
+    pub fn click_title_bar(&mut self, view: WlcView, click: TitleBarClick) -> CommandResult {
+        try!(self.set_active_view(view));
+        match click {
+            TitleBarClick::ToggleFloating => self.toggle_float(),
+            TitleBarClick::ToggleFullscreen => {
+                let id = try!(self.lookup_view(view));
+                let fullscreen = try!(self.0.lookup(id)).fullscreen();
+                self.set_fullscreen(id, !fullscreen)
+            },
+            TitleBarClick::Drag | TitleBarClick::Close => Ok(())
+        }
+    }
+
+    /// Docks the tiled view dragged by its title bar next to the view
+    /// it was dropped on.
+    pub fn dock_view(&mut self, view: WlcView, point: Point) -> CommandResult {
+        let node_ix = try!(self.0.tree.lookup_view(view)
+                           .ok_or(TreeError::ViewNotFound(view)));
+        self.0.record_change(|tree| tree.dock_view(node_ix, point))
+    }
+
     #[allow(dead_code)]
     /// Sets the active container to be the container at the UUID
     /// Fails if the container is not a container or view, or if the
@@ -483,13 +1179,21 @@ impl Tree {
 
     pub fn move_focus(&mut self, dir: Direction) -> CommandResult {
         try!(self.0.move_focus(dir));
+        self.0.warp_pointer_to_active();
         Ok(())
     }
 
+    /// Lets focus follows mouse know the pointer moved over the view.
+    pub fn pointer_moved(&mut self, view: WlcView, point: Point) -> CommandResult {
+        self.0.pointer_moved(view, point)
+    }
+
     /// Moves the active container to a workspace
     pub fn send_active_to_workspace(&mut self, workspace_name: &str) -> CommandResult {
-        self.0.send_active_to_workspace(workspace_name);
-        Ok(())
+        self.0.record_change(|tree| {
+            tree.send_active_to_workspace(workspace_name);
+            Ok(())
+        })
     }
 
     /// Resizes the container, as if it was dragged at the edge to a certain point
@@ -527,8 +1231,10 @@ impl Tree {
     }
 
     pub fn send_to_workspace(&mut self, id: Uuid, workspace_name: &str) -> CommandResult {
-        self.0.send_to_workspace(id, workspace_name);
-        Ok(())
+        self.0.record_change(|tree| {
+            tree.send_to_workspace(id, workspace_name);
+            Ok(())
+        })
     }
 
     pub fn set_pointer_pos(&mut self, point: Point) -> CommandResult {
@@ -572,13 +1278,4 @@ impl Tree {
         }
         Ok(())
     }
-
-    /// Renders the borders for the view.
-    pub fn render_borders(&mut self, view: WlcView) -> CommandResult {
-        let id = try!(self.lookup_view(view)
-                      .map_err(|_|TreeError::ViewNotFound(view)));
-        let container = try!(self.0.lookup_mut(id));
-        container.render_borders();
-        Ok(())
-    }
 }
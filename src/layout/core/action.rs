@@ -4,7 +4,10 @@ use rustwlc::{Point, ResizeEdge, WlcView};
 pub struct Action {
     pub view: WlcView,
     pub grab: Point,
-    pub edges: ResizeEdge
+    pub edges: ResizeEdge,
+    /// Whether the view was grabbed by its title bar.
+    /// Tiled views are docked where they are dropped.
+    pub title_bar: bool
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -1,13 +1,35 @@
 use std::iter;
 use std::fmt::{self, Debug};
 use std::cmp::{Eq, PartialEq};
-use rustwlc::{Geometry, Size, WlcOutput};
+use rustwlc::{Geometry, Point, Size, WlcOutput};
 use rustwlc::render::{calculate_stride};
 use cairo::{ImageSurface, Format};
 
 use uuid::Uuid;
 use ::registry;
-use ::render::{Color, Renderable};
+use ::render::{Color, Renderable, output_scale, logical_resolution};
+use super::title::TitleStyle;
+use super::buttons;
+
+/// Everything that decides what drawn borders look like.
+///
+/// Borders are only drawn again when this changes.
+#[derive(Clone, PartialEq)]
+struct DrawnState {
+    /// The geometry of what the borders are around.
+    geometry: Geometry,
+    title: String,
+    title_style: TitleStyle,
+    color: Color,
+    title_color: Color,
+    title_font_color: Color,
+    thickness: u32,
+    title_size: u32,
+    buttons: bool,
+    /// The borders are clamped to the output, so they change with it.
+    resolution: Option<Size>,
+    scale: u32
+}
 
 /// The borders of a container.
 ///
@@ -16,6 +38,8 @@ use ::render::{Color, Renderable};
 pub struct Borders {
     /// The title displayed in the title border.
     pub title: String,
+    /// How the title is written.
+    pub title_style: TitleStyle,
     /// The surface that contains the bytes we give to wlc to draw.
     surface: ImageSurface,
     /// The geometry where the buffer is written.
@@ -35,42 +59,16 @@ pub struct Borders {
     /// The specific color the font for the title bar should be colored.
     ///
     /// If unspecified, the default is used.
-    title_font_color: Option<Color>
+    title_font_color: Option<Color>,
+    /// Whether there is a title bar above the top border.
+    show_title: bool,
+    /// What the surface looked like when it was last drawn.
+    drawn: Option<DrawnState>
 }
 
 impl Renderable for Borders {
-    fn new(mut geometry: Geometry, output: WlcOutput) -> Option<Self> {
-        let thickness = Borders::thickness();
-        let title_size = Borders::title_bar_size();
-        if thickness == 0 {
-            return None
-        }
-        // Add the thickness to the geometry.
-        geometry.origin.x -= thickness as i32;
-        geometry.origin.y -= thickness as i32;
-        geometry.origin.y -= title_size as i32;
-        geometry.size.w += thickness;
-        geometry.size.h += thickness;
-        geometry.size.h += title_size;
-        let Size { w, h } = geometry.size;
-        let stride = calculate_stride(w) as i32;
-        let data: Vec<u8> = iter::repeat(0).take(h as usize * stride as usize).collect();
-        let buffer = data.into_boxed_slice();
-        let surface = ImageSurface::create_for_data(buffer,
-                                                    drop_data,
-                                                    Format::ARgb32,
-                                                    w as i32,
-                                                    h as i32,
-                                                    stride);
-        Some(Borders {
-            title: "".into(),
-            surface: surface,
-            geometry: geometry,
-            output: output,
-            color: None,
-            title_color: None,
-            title_font_color: None
-        })
+    fn new(geometry: Geometry, output: WlcOutput) -> Option<Self> {
+        Borders::with_title(geometry, output, true)
     }
 
     fn get_surface(&mut self) -> &mut ImageSurface {
@@ -97,7 +95,7 @@ impl Renderable for Borders {
     fn reallocate_buffer(mut self, mut geometry: Geometry) -> Option<Self>{
         // Add the thickness to the geometry.
         let thickness = Borders::thickness();
-        let title_size = Borders::title_bar_size();
+        let title_size = self.title_size();
         if thickness == 0 {
             return None;
         }
@@ -107,10 +105,13 @@ impl Renderable for Borders {
         geometry.size.w += thickness;
         geometry.size.h += thickness;
         geometry.size.h += title_size;
+        let scale = output_scale(self.output);
         let Size { w, h } = geometry.size;
-        if w == self.geometry.size.w && h == self.geometry.size.h {
+        if w == self.geometry.size.w && h == self.geometry.size.h
+            && self.surface.get_width() == (w * scale) as i32 {
             return Some(self);
         }
+        let (w, h) = (w * scale, h * scale);
         let stride = calculate_stride(w) as i32;
         let data: Vec<u8> = iter::repeat(0).take(h as usize * stride as usize).collect();
         let buffer = data.into_boxed_slice();
@@ -122,11 +123,54 @@ impl Renderable for Borders {
                                                     stride);
         self.geometry = geometry;
         self.surface = surface;
+        self.drawn = None;
         Some(self)
     }
 }
 
 impl Borders {
+    /// Makes borders around the geometry, with or without a title bar.
+    pub fn with_title(mut geometry: Geometry, output: WlcOutput, show_title: bool)
+                      -> Option<Self> {
+        let thickness = Borders::thickness();
+        let title_size = if show_title { Borders::title_bar_size() } else { 0 };
+        if thickness == 0 {
+            return None
+        }
+        // Add the thickness to the geometry.
+        geometry.origin.x -= thickness as i32;
+        geometry.origin.y -= thickness as i32;
+        geometry.origin.y -= title_size as i32;
+        geometry.size.w += thickness;
+        geometry.size.h += thickness;
+        geometry.size.h += title_size;
+        // The buffer has a pixel for every pixel of the output
+        let scale = output_scale(output);
+        let Size { w, h } = geometry.size;
+        let (w, h) = (w * scale, h * scale);
+        let stride = calculate_stride(w) as i32;
+        let data: Vec<u8> = iter::repeat(0).take(h as usize * stride as usize).collect();
+        let buffer = data.into_boxed_slice();
+        let surface = ImageSurface::create_for_data(buffer,
+                                                    drop_data,
+                                                    Format::ARgb32,
+                                                    w as i32,
+                                                    h as i32,
+                                                    stride);
+        Some(Borders {
+            title: "".into(),
+            title_style: TitleStyle::default(),
+            surface: surface,
+            geometry: geometry,
+            output: output,
+            color: None,
+            title_color: None,
+            title_font_color: None,
+            show_title: show_title,
+            drawn: None
+        })
+    }
+
     /// Gets the gap size
     pub fn gap_size() -> u32 {
         let lock = registry::clients_read();
@@ -203,6 +247,19 @@ impl Borders {
             .map(|num| (num as u32).into())
     }
 
+    /// Gets the border color of views that want attention, if one is set.
+    pub fn urgent_color() -> Option<Color> {
+        let lock = registry::clients_read();
+        let client = lock.client(Uuid::nil()).unwrap();
+        let handle = registry::ReadHandle::new(&client);
+        handle.read("windows".into()).ok()
+            .and_then(|windows| windows.get("borders".into()))
+            .and_then(|borders| borders.as_object()
+                      .and_then(|borders| borders.get("urgent_color"))
+                      .and_then(|color| color.as_f64()))
+            .map(|num| (num as u32).into())
+    }
+
     /// Fetches the default title background color from the registry.
     ///
     /// If the value is unset, black borders are returned.
@@ -232,6 +289,20 @@ impl Borders {
             .map(|num| (num as u32).into())
     }
 
+    /// Gets the title background color of views that want attention,
+    /// if one is set.
+    pub fn urgent_title_color() -> Option<Color> {
+        let lock = registry::clients_read();
+        let client = lock.client(Uuid::nil()).unwrap();
+        let handle = registry::ReadHandle::new(&client);
+        handle.read("windows".into()).ok()
+            .and_then(|windows| windows.get("title_bar"))
+            .and_then(|title_bar| title_bar.as_object()
+                      .and_then(|title_bar| title_bar.get("urgent_background_color"))
+                      .and_then(|color| color.as_f64()))
+            .map(|num| (num as u32).into())
+    }
+
     /// Fetches the default title font color from the registry.
     ///
     /// If the value is unset, white font are returned.
@@ -261,6 +332,19 @@ impl Borders {
             .map(|num| (num as u32).into())
     }
 
+    /// Gets the title font color of views that want attention, if one is set.
+    pub fn urgent_title_font_color() -> Option<Color> {
+        let lock = registry::clients_read();
+        let client = lock.client(Uuid::nil()).unwrap();
+        let handle = registry::ReadHandle::new(&client);
+        handle.read("windows".into()).ok()
+            .and_then(|windows| windows.get("title_bar"))
+            .and_then(|title_bar| title_bar.as_object()
+                      .and_then(|title_bar| title_bar.get("urgent_font_color"))
+                      .and_then(|color| color.as_f64()))
+            .map(|num| (num as u32).into())
+    }
+
     /// Gets the color for these borders.
     ///
     /// If a specific one is unset, then the default color is returned.
@@ -304,6 +388,95 @@ impl Borders {
     pub fn title(&self) -> &str {
         self.title.as_str()
     }
+
+    /// Determines if these borders have a title bar.
+    pub fn has_title(&self) -> bool {
+        self.show_title
+    }
+
+    /// Gets the size of the title bar of these borders,
+    /// which is 0 if they don't have one.
+    pub fn title_size(&self) -> u32 {
+        if self.show_title {
+            Borders::title_bar_size()
+        } else {
+            0
+        }
+    }
+
+    /// Gets where the title bar is when these borders are drawn around
+    /// the geometry of a view, or `None` if they don't have one.
+    pub fn title_bar_geometry(&self, view_g: Geometry) -> Option<Geometry> {
+        let title_size = self.title_size();
+        if title_size == 0 {
+            return None
+        }
+        let thickness = Borders::thickness();
+        Some(Geometry {
+            origin: Point {
+                x: view_g.origin.x - (thickness / 2) as i32,
+                y: view_g.origin.y - (thickness / 2) as i32 - title_size as i32
+            },
+            size: Size { w: view_g.size.w + thickness, h: title_size }
+        })
+    }
+
+    /// Gets what the borders look like when drawn around the geometry.
+    fn drawn_state(&self, geometry: Geometry) -> DrawnState {
+        DrawnState {
+            geometry: geometry,
+            title: self.title.clone(),
+            title_style: self.title_style.clone(),
+            color: self.color(),
+            title_color: self.title_background_color(),
+            title_font_color: self.title_font_color(),
+            thickness: Borders::thickness(),
+            title_size: self.title_size(),
+            buttons: buttons::buttons_enabled(),
+            resolution: logical_resolution(self.output),
+            scale: output_scale(self.output)
+        }
+    }
+
+    /// Determines if the surface already holds the borders as they would
+    /// be drawn around the geometry, in which case it can be reused.
+    pub fn is_drawn(&self, geometry: Geometry) -> bool {
+        self.drawn.as_ref() == Some(&self.drawn_state(geometry))
+    }
+
+    /// Notes that the surface was just drawn around the geometry.
+    pub fn set_drawn(&mut self, geometry: Geometry) {
+        self.drawn = Some(self.drawn_state(geometry));
+    }
+
+    /// Determines if both borders were drawn the same way, in which case
+    /// their surfaces hold the same pixels.
+    pub fn drawn_like(&self, other: &Borders) -> bool {
+        self.drawn.is_some() && self.drawn == other.drawn
+            && self.geometry == other.geometry && self.output == other.output
+    }
+
+    /// Copies the borders, giving the copy a surface of its own.
+    ///
+    /// A clone shares the surface, which can then not be read from to
+    /// render it. The copy has the pixels the surface has now.
+    pub fn unshared_copy(&mut self) -> Borders {
+        let (w, h, stride) = (self.surface.get_width(),
+                              self.surface.get_height(),
+                              self.surface.get_stride());
+        let data: Vec<u8> = match self.surface.get_data() {
+            Ok(data) => data.to_vec(),
+            Err(_) => iter::repeat(0).take(h as usize * stride as usize).collect()
+        };
+        let surface = ImageSurface::create_for_data(data.into_boxed_slice(),
+                                                    drop_data,
+                                                    Format::ARgb32,
+                                                    w, h, stride);
+        Borders {
+            surface: surface,
+            .. self.clone()
+        }
+    }
 }
 
 impl Debug for Borders {
@@ -327,3 +500,57 @@ unsafe impl Sync for Borders {}
 
 #[allow(dead_code)]
 fn drop_data(_: Box<[u8]>) { }
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+    use cairo::{Context, ImageSurface, Format};
+    use rustwlc::{Geometry, Point, Size, WlcView};
+    use rustwlc::render::calculate_stride;
+    use super::*;
+
+    /// Makes borders without reading their size from the registry.
+    fn test_borders() -> Borders {
+        let geometry = Geometry {
+            origin: Point { x: 0, y: 0 },
+            size: Size { w: 10, h: 10 }
+        };
+        let stride = calculate_stride(10) as i32;
+        let data: Vec<u8> = iter::repeat(0).take(10 * stride as usize).collect();
+        Borders {
+            title: "".into(),
+            title_style: TitleStyle::default(),
+            surface: ImageSurface::create_for_data(data.into_boxed_slice(), drop_data,
+                                                   Format::ARgb32, 10, 10, stride),
+            geometry: geometry,
+            output: WlcView::root().get_output(),
+            color: None,
+            title_color: None,
+            title_font_color: None,
+            show_title: false,
+            drawn: None
+        }
+    }
+
+    #[test]
+    fn rendering_a_copy_while_the_tree_has_the_borders() {
+        let mut borders = test_borders();
+        {
+            let cairo = Context::new(&borders.surface);
+            cairo.set_source_rgb(1.0, 0.0, 0.0);
+            cairo.paint();
+        }
+        let geometry = borders.geometry;
+        borders.set_drawn(geometry);
+        let mut copy = borders.unshared_copy();
+        assert!(copy.drawn_like(&borders));
+        // Rendering reads the surface, which only works if it isn't shared
+        let pixels = copy.surface.get_data().expect("Copy shared its surface").to_vec();
+        let original = borders.surface.get_data().expect("Copy took the surface").to_vec();
+        assert_eq!(pixels, original);
+        assert!(pixels.iter().any(|byte| *byte != 0));
+        // A clone shares the surface, so it can't be rendered
+        let mut clone = borders.clone();
+        assert!(clone.surface.get_data().is_err());
+    }
+}
@@ -1,7 +1,11 @@
 use std::ops::{Deref, DerefMut};
 use rustwlc::{Geometry, Size, Point};
+use ::render::Color;
+use cairo::enums::{FontSlant, FontWeight};
 use super::super::borders::Borders;
-use ::render::{BaseDraw, Drawable, DrawErr};
+use super::title::{TitleAlign, ellipsize};
+use super::buttons::{self, TitleButton};
+use ::render::{BaseDraw, Drawable, DrawErr, logical_resolution};
 
 /// Draws the borders around windows.
 /// They are all of the same size, including the top.
@@ -24,7 +28,7 @@ impl BordersDraw {
                         border_geometry: Geometry,
                         output_res: Size)
                         -> Result<Self, DrawErr<Borders>> {
-        let title_size = Borders::title_bar_size() as f64;
+        let title_size = self.base.inner().title_size() as f64;
         // yay clamping
         if x < 0.0 {
             w += x;
@@ -60,7 +64,7 @@ impl BordersDraw {
                          border_geometry: Geometry,
                          output_res: Size)
                          -> Result<Self, DrawErr<Borders>> {
-        let title_size = Borders::title_bar_size() as f64;
+        let title_size = self.base.inner().title_size() as f64;
         // yay clamping
         if border_geometry.origin.x < 0 {
             x += border_geometry.origin.x as f64;
@@ -93,15 +97,17 @@ impl BordersDraw {
                       _h: f64,
                       border_geometry: Geometry,
                       output_res: Size) -> Result<Self, DrawErr<Borders>> {
-        let title_size = Borders::title_bar_size() as f64;
+        let title_size = self.base.inner().title_size() as f64;
         let title_color = self.base.inner().title_background_color();
         let title_font_color = self.base.inner().title_font_color();
         let title: String = self.inner().title().into();
+        let style = self.inner().title_style.clone();
         if x < 0.0 {
             w += x;
         }
-        let mut title_x = Borders::thickness() as f64;
-        let mut title_y = title_size - 5.0;
+        let thickness = Borders::thickness() as f64;
+        let mut title_x = thickness;
+        let mut title_y = (title_size + style.font_size as f64 * 0.7) / 2.0;
         x = 0.0;
         let mut y = 0.0;
         if border_geometry.origin.x + border_geometry.size.w as i32 > output_res.w as i32 {
@@ -124,13 +130,75 @@ impl BordersDraw {
         self.base.fill();
         self.base = try!(self.base.check_cairo());
 
-        // Draw title text
+        // Draw title text, cut short if it doesn't fit between the borders
+        let weight = if style.bold { FontWeight::Bold } else { FontWeight::Normal };
+        self.base.select_font_face(style.font.as_str(), FontSlant::Normal, weight);
+        self.base.set_font_size(style.font_size as f64);
+        let buttons = if buttons::buttons_enabled() {
+            buttons::button_geometries(Size { w: w.max(0.0) as u32, h: title_size as u32 })
+        } else {
+            Vec::new()
+        };
+        let room = w - thickness * 2.0 - (buttons.len() as f64 * title_size);
+        let title = {
+            let base = &self.base;
+            ellipsize(title.as_str(), room, |text| base.text_extents(text).x_advance)
+        };
+        let title_w = self.base.text_extents(title.as_str()).x_advance;
+        match style.align {
+            TitleAlign::Left => {},
+            TitleAlign::Center => title_x += (room - title_w) / 2.0,
+            TitleAlign::Right => title_x += room - title_w
+        }
         self.base.move_to(title_x, title_y);
         self.base = try!(self.base.check_cairo());
         self.base.set_color_source(title_font_color);
         self.base = try!(self.base.check_cairo());
         self.base.show_text(title.as_str());
         self.base = try!(self.base.check_cairo());
+        for (button, geometry) in buttons {
+            self = try!(self.draw_button(button, x + geometry.origin.x as f64,
+                                         y + geometry.origin.y as f64,
+                                         geometry.size.w as f64, title_font_color));
+        }
+        Ok(self)
+    }
+
+    /// Draws the symbol of a title bar button in the square.
+    fn draw_button(mut self,
+                   button: TitleButton,
+                   x: f64,
+                   y: f64,
+                   side: f64,
+                   color: Color) -> Result<Self, DrawErr<Borders>> {
+        let inset = (side / 4.0).round();
+        let (x, y, side) = (x + inset, y + inset, side - inset * 2.0);
+        self.base.set_color_source(color);
+        self.base.set_line_width(1.5);
+        match button {
+            TitleButton::Close => {
+                self.base.move_to(x, y);
+                self.base.line_to(x + side, y + side);
+                self.base.move_to(x + side, y);
+                self.base.line_to(x, y + side);
+            },
+            TitleButton::Fullscreen => {
+                self.base.rectangle(x, y, side, side);
+            },
+            TitleButton::Float => {
+                // Two windows, one over the other
+                let small = (side * 2.0 / 3.0).round();
+                self.base.rectangle(x, y + side - small, small, small);
+                self.base.move_to(x + side - small, y + side - small);
+                self.base.line_to(x + side - small, y);
+                self.base.line_to(x + side, y);
+                self.base.line_to(x + side, y + small);
+                self.base.line_to(x + small, y + small);
+            }
+        }
+        self.base = try!(self.base.check_cairo());
+        self.base.stroke();
+        self.base = try!(self.base.check_cairo());
         Ok(self)
     }
 
@@ -141,7 +209,7 @@ impl BordersDraw {
                          mut h: f64,
                          border_geometry: Geometry,
                          output_res: Size) -> Result<Self, DrawErr<Borders>> {
-        let title_size = Borders::title_bar_size() as f64;
+        let title_size = self.base.inner().title_size() as f64;
         // yay clamping
         if x < 0.0 {
             w += x;
@@ -207,9 +275,9 @@ impl Drawable<Borders> for BordersDraw {
         let mut border_g = view_g;
         let thickness = Borders::thickness();
         let edge_thickness = thickness / 2;
-        let output_res = self.inner().get_output().get_resolution()
+        let output_res = logical_resolution(self.inner().get_output())
             .expect("Could not get focused output's resolution");
-        let title_size = Borders::title_bar_size();
+        let title_size = self.base.inner().title_size();
         border_g.origin.x -= edge_thickness as i32;
         border_g.origin.y -= edge_thickness as i32;
         border_g.origin.y -= title_size as i32;
@@ -234,8 +302,10 @@ impl Drawable<Borders> for BordersDraw {
         self = self.draw_left_border(x, y, edge_thickness, h, border_g, output_res)?
         .draw_right_border(w - edge_thickness, y, edge_thickness, h, border_g, output_res)?
         .draw_top_border(x, y, w, edge_thickness, border_g, output_res)?
-        .draw_bottom_border(x, h, w, -edge_thickness, border_g, output_res)?
-        .draw_title_bar(x, y, w, edge_thickness, border_g, output_res)?;
+        .draw_bottom_border(x, h, w, -edge_thickness, border_g, output_res)?;
+        if title_size > 0 {
+            self = self.draw_title_bar(x, y, w, edge_thickness, border_g, output_res)?;
+        }
 
         Ok(self.base.finish(border_g))
     }
@@ -0,0 +1,87 @@
+//! The buttons at the right end of title bars.
+//!
+//! They close the view, make it fullscreen and toggle whether it floats.
+//! They are shown unless `buttons` is false in the `title_bar` table of the
+//! windows category.
+
+use rustwlc::{Geometry, Point, Size};
+use uuid::Uuid;
+
+use ::registry;
+
+/// A button in a title bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitleButton {
+    Close,
+    Fullscreen,
+    Float
+}
+
+/// The buttons in the order they are placed, from the right end.
+pub const BUTTONS: [TitleButton; 3] = [TitleButton::Close,
+                                       TitleButton::Fullscreen,
+                                       TitleButton::Float];
+
+/// Determines if title bars have buttons. Defaults to true.
+pub fn buttons_enabled() -> bool {
+    let lock = registry::clients_read();
+    let client = lock.client(Uuid::nil()).unwrap();
+    let handle = registry::ReadHandle::new(&client);
+    handle.read("windows".into()).ok()
+        .and_then(|windows| windows.get("title_bar").cloned())
+        .and_then(|title_bar| title_bar.as_object()
+                  .and_then(|title_bar| title_bar.get("buttons"))
+                  .and_then(|buttons| buttons.as_boolean()))
+        .unwrap_or(true)
+}
+
+/// Gets where the buttons are in a title bar of the size, relative to it.
+///
+/// The buttons are squares as tall as the title bar. Those that don't fit
+/// are left out.
+pub fn button_geometries(title_bar: Size) -> Vec<(TitleButton, Geometry)> {
+    let side = title_bar.h;
+    BUTTONS.iter().enumerate()
+        .map(|(index, &button)| {
+            let x = title_bar.w as i32 - (side * (index as u32 + 1)) as i32;
+            (button, Geometry {
+                origin: Point { x: x, y: 0 },
+                size: Size { w: side, h: side }
+            })
+        })
+        .take_while(|&(_, geometry)| geometry.origin.x >= 0)
+        .collect()
+}
+
+/// Finds the button at the point in a title bar of the size,
+/// with the point relative to the title bar.
+pub fn button_at(title_bar: Size, point: Point) -> Option<TitleButton> {
+    button_geometries(title_bar).into_iter()
+        .find(|&(_, geometry)| {
+            point.x >= geometry.origin.x
+                && point.y >= geometry.origin.y
+                && point.x < geometry.origin.x + geometry.size.w as i32
+                && point.y < geometry.origin.y + geometry.size.h as i32
+        })
+        .map(|(button, _)| button)
+}
+
+#[cfg(test)]
+mod tests {
+    use rustwlc::{Point, Size};
+    use super::*;
+
+    #[test]
+    fn placing_buttons() {
+        let title_bar = Size { w: 200, h: 20 };
+        let geometries = button_geometries(title_bar);
+        assert_eq!(geometries.len(), 3);
+        assert_eq!(geometries[0].1.origin, Point { x: 180, y: 0 });
+        assert_eq!(geometries[2].1.origin, Point { x: 140, y: 0 });
+        assert_eq!(button_at(title_bar, Point { x: 199, y: 10 }), Some(TitleButton::Close));
+        assert_eq!(button_at(title_bar, Point { x: 165, y: 0 }), Some(TitleButton::Fullscreen));
+        assert_eq!(button_at(title_bar, Point { x: 139, y: 10 }), None);
+        // Buttons that don't fit are left out
+        assert_eq!(button_geometries(Size { w: 50, h: 20 }).len(), 2);
+    }
+}
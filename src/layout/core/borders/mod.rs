@@ -1,6 +1,8 @@
 mod borders;
 mod borders_draw;
+pub mod title;
+pub mod style;
+pub mod buttons;
 
 pub use self::borders::{Borders};
 pub use self::borders_draw::{BordersDraw};
-
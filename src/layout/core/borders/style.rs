@@ -0,0 +1,169 @@
+//! How the borders of a view are drawn.
+//!
+//! A view has one of these border styles:
+//!
+//! * `"none"`, no borders at all
+//! * `"pixel"`, borders without a title bar
+//! * `"normal"`, borders with a title bar, the default
+//!
+//! A view can also have its own border, title bar and title font colors,
+//! which are used instead of the inactive ones.
+//!
+//! These are set for a view over D-Bus or from Lua, or else by a rule with
+//! the `border_style`, `border_color`, `title_color` and `title_font_color`
+//! options. With `smart` set in the borders table of the windows category,
+//! the only tiled view of a workspace has no borders, as they would all
+//! touch the edges of the output.
+//!
+//! Views that draw their own decorations, like the header bars of GTK
+//! applications, have no borders unless a style is set for them.
+
+use std::collections::BTreeMap;
+use rustc_serialize::json::Json;
+use uuid::Uuid;
+
+use ::registry;
+use ::render::Color;
+
+/// Whether a view has borders, and a title bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorderStyle {
+    None,
+    Pixel,
+    Normal
+}
+
+impl BorderStyle {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(BorderStyle::None),
+            "pixel" => Some(BorderStyle::Pixel),
+            "normal" => Some(BorderStyle::Normal),
+            _ => None
+        }
+    }
+
+    /// Determines if borders in this style have a title bar.
+    pub fn has_title(self) -> bool {
+        self == BorderStyle::Normal
+    }
+
+    /// Gets the style of a view that has the style set for it, if any,
+    /// and that may draw its own decorations.
+    pub fn effective(style: Option<BorderStyle>, client_decorated: bool) -> Self {
+        match style {
+            Some(style) => style,
+            None if client_decorated => BorderStyle::None,
+            None => BorderStyle::Normal
+        }
+    }
+}
+
+/// The style and colors set for the borders of a view.
+///
+/// Whatever is unset is left to the rules, and then to the defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BorderSettings {
+    pub style: Option<BorderStyle>,
+    pub color: Option<Color>,
+    pub title_color: Option<Color>,
+    pub title_font_color: Option<Color>
+}
+
+impl BorderSettings {
+    /// Reads the settings from the options of a rule, or from a JSON object
+    /// with the same keys, e.g `{"border_style": "pixel"}`.
+    pub fn from_options(options: &BTreeMap<String, Json>) -> Result<Self, String> {
+        let color = |key: &str| match options.get(key) {
+            Some(&Json::Null) | None => Ok(None),
+            Some(color) => color.as_f64()
+                .map(|color| Some((color as u32).into()))
+                .ok_or_else(|| format!("{}: expected a number, e.g 0xff0000", key))
+        };
+        let style = match options.get("border_style") {
+            Some(&Json::Null) | None => None,
+            Some(style) => Some(try!(style.as_string()
+                                     .and_then(BorderStyle::from_name)
+                                     .ok_or_else(|| "border_style: expected \"none\", \
+                                                     \"pixel\" or \"normal\"".to_string())))
+        };
+        Ok(BorderSettings {
+            style: style,
+            color: try!(color("border_color")),
+            title_color: try!(color("title_color")),
+            title_font_color: try!(color("title_font_color"))
+        })
+    }
+
+    /// Reads the settings from a JSON object, see `from_options`.
+    pub fn from_json(json: &Json) -> Result<Self, String> {
+        json.as_object()
+            .ok_or_else(|| "Expected an object of border settings".to_string())
+            .and_then(BorderSettings::from_options)
+    }
+
+    /// Takes what is unset in these settings from the other ones.
+    pub fn or(self, other: BorderSettings) -> Self {
+        BorderSettings {
+            style: self.style.or(other.style),
+            color: self.color.or(other.color),
+            title_color: self.title_color.or(other.title_color),
+            title_font_color: self.title_font_color.or(other.title_font_color)
+        }
+    }
+}
+
+/// Determines if the only tiled view of a workspace goes without borders.
+/// Defaults to false.
+pub fn smart_borders() -> bool {
+    let lock = registry::clients_read();
+    let client = lock.client(Uuid::nil()).unwrap();
+    let handle = registry::ReadHandle::new(&client);
+    handle.read("windows".into()).ok()
+        .and_then(|windows| windows.get("borders").cloned())
+        .and_then(|borders| borders.as_object()
+                  .and_then(|borders| borders.get("smart"))
+                  .and_then(|smart| smart.as_boolean()))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use rustc_serialize::json::Json;
+    use super::*;
+
+    #[test]
+    fn reading_settings() {
+        let json = Json::from_str(r#"{"border_style": "pixel", "title_color": 16711680}"#)
+            .unwrap();
+        let settings = BorderSettings::from_json(&json).unwrap();
+        assert_eq!(settings.style, Some(BorderStyle::Pixel));
+        assert_eq!(settings.title_color, Some(0xff0000.into()));
+        assert_eq!(settings.color, None);
+        let rules = BorderSettings {
+            style: Some(BorderStyle::None),
+            color: Some(0x00ff00.into()),
+            .. BorderSettings::default()
+        };
+        let merged = settings.or(rules);
+        assert_eq!(merged.style, Some(BorderStyle::Pixel));
+        assert_eq!(merged.color, Some(0x00ff00.into()));
+        assert!(!BorderStyle::Pixel.has_title());
+
+        let bad_style = Json::from_str(r#"{"border_style": "thick"}"#).unwrap();
+        assert!(BorderSettings::from_json(&bad_style).is_err());
+        let bad_color = Json::from_str(r#"{"border_color": "red"}"#).unwrap();
+        assert!(BorderSettings::from_json(&bad_color).is_err());
+    }
+
+    #[test]
+    fn client_decorated_styles() {
+        assert_eq!(BorderStyle::effective(None, false), BorderStyle::Normal);
+        assert_eq!(BorderStyle::effective(None, true), BorderStyle::None);
+        // Rules can force borders on views that decorate themselves
+        assert_eq!(BorderStyle::effective(Some(BorderStyle::Normal), true),
+                   BorderStyle::Normal);
+        assert_eq!(BorderStyle::effective(Some(BorderStyle::Pixel), false),
+                   BorderStyle::Pixel);
+    }
+}
@@ -0,0 +1,196 @@
+//! What is written in the title bar of a view, and how.
+//!
+//! The text comes from a format, e.g `"{title} - {class}"`, in which these
+//! placeholders are replaced:
+//!
+//! * `{title}`, `{class}` and `{app_id}` of the view
+//! * `{workspace}`, the name of the workspace the view is on
+//! * `{marks}`, the marks of the view, e.g "urgent"
+//! * `{floating}`, "floating" if the view is floating
+//!
+//! The format, font and alignment are read from the `title_bar` table of the
+//! windows category, and can be set for a view by a rule with the
+//! `title_format`, `title_font`, `title_font_size`, `title_font_weight` and
+//! `title_align` options.
+
+use rustc_serialize::json::Json;
+use uuid::Uuid;
+
+use ::registry;
+use super::super::rules::RuleOptions;
+
+/// Put at the end of titles that were too long to fit.
+const ELLIPSIS: &'static str = "…";
+
+/// Where the title is in the title bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitleAlign {
+    Left,
+    Center,
+    Right
+}
+
+/// How the title is written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TitleStyle {
+    /// The family of the font, e.g "monospace".
+    pub font: String,
+    /// The size of the font, in pixels.
+    pub font_size: u32,
+    pub bold: bool,
+    pub align: TitleAlign
+}
+
+impl Default for TitleStyle {
+    fn default() -> Self {
+        TitleStyle {
+            font: "sans-serif".into(),
+            font_size: 10,
+            bold: false,
+            align: TitleAlign::Left
+        }
+    }
+}
+
+/// What the placeholders of a format are replaced with.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TitleFields {
+    pub title: String,
+    pub class: String,
+    pub app_id: String,
+    pub workspace: String,
+    pub marks: Vec<String>,
+    pub floating: bool
+}
+
+/// Reads an option from the title bar table of the windows category.
+fn title_bar_option(key: &str) -> Option<Json> {
+    let lock = registry::clients_read();
+    let client = lock.client(Uuid::nil()).unwrap();
+    let handle = registry::ReadHandle::new(&client);
+    handle.read("windows".into()).ok()
+        .and_then(|windows| windows.get("title_bar").cloned())
+        .and_then(|title_bar| title_bar.as_object()
+                  .and_then(|title_bar| title_bar.get(key).cloned()))
+}
+
+/// Reads an option set by a rule, or else from the title bar table.
+fn option(options: &RuleOptions, rule_key: &str, key: &str) -> Option<Json> {
+    options.get(rule_key).cloned().or_else(|| title_bar_option(key))
+}
+
+/// Gets the format of the title for a view with the rule options.
+/// Defaults to just the title.
+pub fn title_format(options: &RuleOptions) -> String {
+    option(options, "title_format", "format")
+        .and_then(|format| format.as_string().map(str::to_string))
+        .unwrap_or_else(|| "{title}".into())
+}
+
+impl TitleStyle {
+    /// Gets the style of the title for a view with the rule options.
+    pub fn configured(options: &RuleOptions) -> Self {
+        let default = TitleStyle::default();
+        TitleStyle {
+            font: option(options, "title_font", "font")
+                .and_then(|font| font.as_string().map(str::to_string))
+                .unwrap_or(default.font),
+            font_size: option(options, "title_font_size", "font_size")
+                .and_then(|size| size.as_f64())
+                .map(|size| size as u32)
+                .unwrap_or(default.font_size),
+            bold: option(options, "title_font_weight", "font_weight")
+                .map(|weight| weight.as_string() == Some("bold"))
+                .unwrap_or(default.bold),
+            align: match option(options, "title_align", "align").as_ref()
+                .and_then(Json::as_string) {
+                Some("center") => TitleAlign::Center,
+                Some("right") => TitleAlign::Right,
+                _ => default.align
+            }
+        }
+    }
+}
+
+/// Replaces the placeholders in the format with the fields.
+///
+/// Unknown placeholders are left as they are.
+pub fn format_title(format: &str, fields: &TitleFields) -> String {
+    let mut title = String::new();
+    let mut rest = format;
+    while let Some(start) = rest.find('{') {
+        title.push_str(&rest[..start]);
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => {
+                rest = &rest[start..];
+                break
+            }
+        };
+        match &rest[start + 1..end] {
+            "title" => title.push_str(&fields.title),
+            "class" => title.push_str(&fields.class),
+            "app_id" => title.push_str(&fields.app_id),
+            "workspace" => title.push_str(&fields.workspace),
+            "marks" => title.push_str(&fields.marks.join(" ")),
+            "floating" => if fields.floating {
+                title.push_str("floating")
+            },
+            _ => title.push_str(&rest[start..end + 1])
+        }
+        rest = &rest[end + 1..];
+    }
+    title.push_str(rest);
+    title
+}
+
+/// Shortens the text so that it's no wider than the width, putting an
+/// ellipsis at the end if anything was cut off.
+pub fn ellipsize<F>(text: &str, width: f64, width_of: F) -> String
+    where F: Fn(&str) -> f64 {
+    if width_of(text) <= width {
+        return text.into()
+    }
+    let mut chars: Vec<char> = text.chars().collect();
+    while chars.pop().is_some() {
+        // Don't leave a space before the ellipsis
+        let mut kept = chars.len();
+        while kept > 0 && chars[kept - 1].is_whitespace() {
+            kept -= 1;
+        }
+        let shortened: String = chars[..kept].iter().cloned()
+            .chain(ELLIPSIS.chars())
+            .collect();
+        if width_of(&shortened) <= width {
+            return shortened
+        }
+    }
+    String::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formatting_titles() {
+        let fields = TitleFields {
+            title: "README.md".into(),
+            class: "vim".into(),
+            app_id: "".into(),
+            workspace: "2".into(),
+            marks: vec!["urgent".into()],
+            floating: false
+        };
+        assert_eq!(format_title("{title}", &fields), "README.md");
+        assert_eq!(format_title("[{workspace}] {title} - {class} {floating}{marks}", &fields),
+                   "[2] README.md - vim urgent");
+        assert_eq!(format_title("{unknown} {title", &fields), "{unknown} {title");
+
+        let width_of = |text: &str| text.chars().count() as f64;
+        assert_eq!(ellipsize("README.md", 9.0, &width_of), "README.md");
+        assert_eq!(ellipsize("README.md", 7.0, &width_of), "README…");
+        assert_eq!(ellipsize("a b c", 3.0, &width_of), "a…");
+        assert_eq!(ellipsize("README.md", 0.0, &width_of), "");
+    }
+}
@@ -9,10 +9,13 @@ use rustwlc::{Geometry, ResizeEdge, Point, Size,
               VIEW_FULLSCREEN, VIEW_BIT_MODAL};
 
 use super::borders::{Borders, BordersDraw};
+use super::borders::style::BorderSettings;
 use super::tree::TreeError;
-use ::render::{Renderable, Drawable};
+use ::render::{Renderable, Drawable, StatusBar, BarPosition, logical_resolution};
 use ::layout::commands::CommandResult;
 use super::bar::Bar;
+use super::rules::{self, Criteria};
+use super::reservation::{self, Reservation};
 
 /// A handle to either a view or output
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -57,6 +60,78 @@ impl ContainerType {
     }
 }
 
+/// Where a minimized view was taken from, so that it can be put back there
+/// when it is restored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MinimizedFrom {
+    /// The id of the container the view was a child of
+    pub parent: Uuid,
+    /// The position of the view amongst its siblings
+    pub position: u32
+}
+
+/// The smallest and largest size that a view may be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeLimits {
+    pub min: Size,
+    pub max: Size
+}
+
+impl SizeLimits {
+    /// Makes limits that hold a view to at least `MIN_SIZE`,
+    /// without a maximum size.
+    pub fn new() -> Self {
+        SizeLimits {
+            min: MIN_SIZE,
+            max: Size { w: u32::max_value(), h: u32::max_value() }
+        }
+    }
+
+    /// Gets the size limits of the view.
+    ///
+    /// wlc does not expose the size hints of the client, so these start
+    /// as the defaults from `SizeLimits::new`, which are then overridden by
+    /// the `min_width`, `min_height`, `max_width`, and `max_height`
+    /// options of the rules that match the view.
+    pub fn for_view(view: WlcView) -> Self {
+        let mut limits = SizeLimits::new();
+        let options = rules::options_for(view);
+        let option = |name: &str| options.get(name)
+            .and_then(|value| value.as_f64())
+            .map(|value| value as u32);
+        limits.min.w = option("min_width").unwrap_or(limits.min.w);
+        limits.min.h = option("min_height").unwrap_or(limits.min.h);
+        limits.max.w = option("max_width").unwrap_or(limits.max.w);
+        limits.max.h = option("max_height").unwrap_or(limits.max.h);
+        limits
+    }
+
+    /// Grows both the minimum and maximum size by some amount.
+    /// Used to account for the space that borders and gaps take up.
+    pub fn grow(self, w: u32, h: u32) -> Self {
+        SizeLimits {
+            min: Size {
+                w: self.min.w.saturating_add(w),
+                h: self.min.h.saturating_add(h)
+            },
+            max: Size {
+                w: self.max.w.saturating_add(w),
+                h: self.max.h.saturating_add(h)
+            }
+        }
+    }
+
+    /// Clamps the size so that it is within the limits.
+    /// If the limits contradict each other, the minimum size wins.
+    pub fn clamp(&self, size: Size) -> Size {
+        use std::cmp::{min, max};
+        Size {
+            w: max(min(size.w, self.max.w), self.min.w),
+            h: max(min(size.h, self.max.h), self.min.h)
+        }
+    }
+}
+
 /// Layout mode for a container
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Layout {
@@ -77,6 +152,11 @@ pub enum Container {
         background: Option<WlcView>,
         /// Optional bar for the output
         bar: Option<Bar>,
+        /// Where the output is in the arrangement of the outputs,
+        /// in logical pixels
+        position: Point,
+        /// Space along the edges that workspaces are not laid out in
+        reserved: Vec<Reservation>,
         /// UUID associated with container, client program can use container
         id: Uuid,
     },
@@ -108,6 +188,9 @@ pub enum Container {
         id: Uuid,
         /// The border drawn to the screen
         borders: Option<Borders>,
+        /// If the container is a placeholder from a layout template,
+        /// the view it is waiting for. Placeholders can be empty.
+        placeholder: Option<Criteria>,
     },
     /// View or window
     View {
@@ -123,6 +206,16 @@ pub enum Container {
         id: Uuid,
         /// The border drawn to the screen
         borders: Option<Borders>,
+        /// How small and large the view may be made
+        size_limits: SizeLimits,
+        /// If the view is minimized, where it was minimized from.
+        /// Minimized views are hidden and not tiled, but stay in the tree.
+        minimized: Option<MinimizedFrom>,
+        /// Whether the view wants the attention of the user.
+        /// Cleared when the view is focused.
+        urgent: bool,
+        /// The style and colors of the borders, instead of what the rules say.
+        border_settings: BorderSettings,
     }
 }
 
@@ -137,6 +230,8 @@ impl Container {
             handle: handle,
             background: None,
             bar: None,
+            position: Point { x: 0, y: 0 },
+            reserved: Vec::new(),
             id: Uuid::new_v4()
         }
     }
@@ -161,7 +256,8 @@ impl Container {
             fullscreen: false,
             geometry: geometry,
             id: Uuid::new_v4(),
-            borders: None
+            borders: None,
+            placeholder: None
         }
     }
 
@@ -174,7 +270,11 @@ impl Container {
             floating: false,
             effective_geometry: geometry,
             id: Uuid::new_v4(),
-            borders: borders
+            borders: borders,
+            size_limits: SizeLimits::for_view(handle),
+            minimized: None,
+            urgent: false,
+            border_settings: BorderSettings::default()
         }
     }
 
@@ -227,8 +327,26 @@ impl Container {
     pub fn get_geometry(&self) -> Option<Geometry> {
         match *self {
             Container::Root(_)  => None,
+            Container::Output { ref reserved, .. } => {
+                self.unreserved_geometry()
+                    .map(|geometry| reservation::shrink(geometry, reserved))
+            },
+            Container::Workspace { geometry, .. } |
+            Container::Container { geometry, .. } => Some(geometry),
+            Container::View { effective_geometry, .. } => {
+                Some(effective_geometry)
+            },
+        }
+    }
+
+    /// Gets the geometry of an output, taking off its bars
+    /// but not the space reserved along its edges.
+    ///
+    /// For non-`Output` containers, this always returns `None`
+    pub fn unreserved_geometry(&self) -> Option<Geometry> {
+        match *self {
             Container::Output { ref handle, ref bar, .. } => {
-                let mut resolution = handle.get_resolution()
+                let mut resolution = logical_resolution(*handle)
                     .expect("Couldn't get output resolution");
                 let mut origin = Point { x: 0, y: 0 };
                 if let Some(handle) = bar.as_ref().map(|bar| **bar) {
@@ -241,16 +359,19 @@ impl Container {
                     origin.y += h as i32;
                     resolution.h = resolution.h.saturating_sub(h)
                 }
+                if let Some(bar_g) = StatusBar::reserved(*handle) {
+                    let h = bar_g.size.h;
+                    if StatusBar::position() == BarPosition::Top {
+                        origin.y += h as i32;
+                    }
+                    resolution.h = resolution.h.saturating_sub(h)
+                }
                 Some(Geometry {
                     origin: origin,
                     size: resolution
                 })
             },
-            Container::Workspace { geometry, .. } |
-            Container::Container { geometry, .. } => Some(geometry),
-            Container::View { effective_geometry, .. } => {
-                Some(effective_geometry)
-            },
+            _ => None
         }
     }
 
@@ -258,7 +379,7 @@ impl Container {
     ///
     /// Unlike `get_geometry`, this does not account for borders/gaps,
     /// and instead is just a thin wrapper around
-    /// `handle.get_geometry`/`handle.get_resolution`.
+    /// `handle.get_geometry`/`handle.get_virtual_resolution`.
     ///
     /// Most of the time you want `get_geometry`, as you should account for the
     /// borders, gaps, and top bar.
@@ -268,7 +389,7 @@ impl Container {
         match *self {
             Container::View { handle, .. } => handle.get_geometry(),
             Container::Output { handle, .. } => {
-                handle.get_resolution()
+                logical_resolution(handle)
                     .map(|size|
                          Geometry {
                              origin: Point { x: 0, y: 0 },
@@ -328,30 +449,114 @@ impl Container {
     }
 
 
+    /// Gets the size limits of this view.
+    ///
+    /// Other containers only have the default limits, use
+    /// `LayoutTree::size_limits_of` to get the limits of a tiled container.
+    pub fn size_limits(&self) -> SizeLimits {
+        match *self {
+            Container::View { size_limits, .. } => size_limits,
+            _ => SizeLimits::new()
+        }
+    }
+
+    /// Whether this container is a minimized view
+    pub fn minimized(&self) -> bool {
+        match *self {
+            Container::View { minimized, .. } => minimized.is_some(),
+            _ => false
+        }
+    }
+
+    /// Determines if the container is a view that wants attention
+    pub fn urgent(&self) -> bool {
+        match *self {
+            Container::View { urgent, .. } => urgent,
+            _ => false
+        }
+    }
+
+    /// Gets where the view was minimized from, if it is a minimized view
+    pub fn minimized_from(&self) -> Option<MinimizedFrom> {
+        match *self {
+            Container::View { minimized, .. } => minimized,
+            _ => None
+        }
+    }
+
+    /// Gets the view the container is waiting for, if it is a placeholder
+    pub fn placeholder(&self) -> Option<&Criteria> {
+        match *self {
+            Container::Container { ref placeholder, .. } => placeholder.as_ref(),
+            _ => None
+        }
+    }
+
     // TODO Make these set_* functions that can fail return a proper error type.
 
+    /// Makes the container a placeholder waiting for a view matching
+    /// the criteria, or makes it a normal container again.
+    ///
+    /// If not set on a container, an error is returned.
+    pub fn set_placeholder(&mut self, val: Option<Criteria>)
+                           -> Result<(), ContainerErr> {
+        match *self {
+            Container::Container { ref mut placeholder, .. } => {
+                *placeholder = val;
+                Ok(())
+            },
+            ref other => Err(ContainerErr::BadOperationOn(
+                other.get_type(), "Only containers can be placeholders"))
+        }
+    }
+
+    /// Marks the view as wanting attention, or clears the mark.
+    ///
+    /// If not set on a view, an error is returned.
+    pub fn set_urgent(&mut self, val: bool) -> Result<(), ContainerErr> {
+        match *self {
+            Container::View { ref mut urgent, .. } => {
+                *urgent = val;
+                Ok(())
+            },
+            ref other => Err(ContainerErr::BadOperationOn(
+                other.get_type(), "Only views can be urgent"))
+        }
+    }
+
+    /// Sets (or clears) where a view was minimized from.
+    /// This does not change the visibility of the view.
+    ///
+    /// If not set on a view, an error is returned.
+    pub fn set_minimized(&mut self, val: Option<MinimizedFrom>)
+                         -> Result<(), ContainerErr> {
+        match *self {
+            Container::View { ref mut minimized, .. } => {
+                *minimized = val;
+                Ok(())
+            },
+            ref other => Err(ContainerErr::BadOperationOn(
+                other.get_type(), "Only views can be minimized"))
+        }
+    }
+
     /// If not set on a view or container, error is returned telling what
     /// container type that this function was (incorrectly) called on.
     pub fn set_floating(&mut self, val: bool) -> Result<ContainerType, ContainerType> {
         let c_type = self.get_type();
         let mut v_g;
         match *self {
-            Container::View { handle, ref mut floating, .. } => {
+            Container::View { handle, ref mut floating, size_limits, .. } => {
                 *floating = val;
                 // And now we update the geometry, if necessary.
                 v_g = handle.get_geometry() .expect("View had no geometry");
-                // Make it the min size
-                if v_g.size.w < MIN_SIZE.w {
-                    v_g.size.w = MIN_SIZE.w;
-                }
-                if v_g.size.h < MIN_SIZE.h {
-                    v_g.size.h = MIN_SIZE.h;
-                }
+                // Keep it within the size it's allowed to be
+                v_g.size = size_limits.clamp(v_g.size);
                 // if modal, center it if in the top left.
                 if handle.get_type().contains(VIEW_BIT_MODAL) {
                     if v_g.origin.x == 0 && v_g.origin.y == 0 {
                         let output = handle.get_output();
-                        let res = output.get_resolution()
+                        let res = logical_resolution(output)
                             .expect("Output had no resolution");
                         v_g.origin.x = (res.w / 2 - v_g.size.w / 2) as i32;
                         v_g.origin.y = (res.h / 2 - v_g.size.h / 2) as i32;
@@ -383,7 +588,7 @@ impl Container {
                 if !val {
                     let new_geometry;
                     if floating {
-                        let output_size = handle.get_output().get_resolution()
+                        let output_size = logical_resolution(handle.get_output())
                             .expect("output had no resolution");
                         new_geometry = Geometry {
                             size: Size {
@@ -501,18 +706,6 @@ impl Container {
     }
 
 
-    pub fn render_borders(&mut self) {
-        match *self {
-            Container::View { ref mut borders, .. } |
-            Container::Container { ref mut borders, .. } => {
-                if let Some(borders) = borders.as_mut() {
-                    borders.render();
-                }
-            },
-            _ => panic!("Tried to render a non-view / non-container")
-        }
-    }
-
     pub fn draw_borders(&mut self) {
         // TODO Eventually, we should use an enum to choose which way to draw the
         // border, but for now this will do.
@@ -525,12 +718,23 @@ impl Container {
                         if let Some(new_borders) = borders_.reallocate_buffer(geometry) {
                             borders_ = new_borders;
                         } else {
+                            cache_borders(handle, None);
                             return
                         }
                     }
-                    *borders = BordersDraw::new(borders_.enable_cairo().unwrap())
-                        .draw(geometry).ok();
+                    // Nothing about them changed, so the surface is still good
+                    *borders = if borders_.is_drawn(geometry) {
+                        Some(borders_)
+                    } else {
+                        BordersDraw::new(borders_.enable_cairo().unwrap())
+                            .draw(geometry).ok()
+                            .map(|mut borders_| {
+                                borders_.set_drawn(geometry);
+                                borders_
+                            })
+                    };
                 }
+                cache_borders(handle, borders.as_mut());
             },
             Container::Container { ref mut borders, .. } => {
                 borders.take();
@@ -546,15 +750,10 @@ impl Container {
     /// Panics on non-`View`/`Container`s
     pub fn resize_borders(&mut self, geo: Geometry) {
         match *self {
-            Container::View { handle, ref mut borders, ..}  => {
+            Container::View { ref mut borders, ..}  => {
+                // Views without borders are given them by their border style
                 if let Some(borders_) = borders.take() {
                     *borders = borders_.reallocate_buffer(geo)
-                } else {
-                    let thickness = Borders::thickness();
-                    if thickness > 0 {
-                        let output = handle.get_output();
-                        *borders = Borders::new(geo, output);
-                    }
                 }
             },
             Container::Container { ref mut borders, ..} => {
@@ -594,18 +793,41 @@ impl Container {
         }
     }
 
+    /// Set the border color on a view to be the urgent color.
+    ///
+    /// If called on a non-view/container, returns an appropriate error.
+    pub fn urgent_border_color(&mut self) -> CommandResult {
+        let c_type = self.get_type();
+        match *self {
+            Container::View { ref mut borders, .. } |
+            Container::Container { ref mut borders, .. }=> {
+                if let Some(borders_) = borders.as_mut() {
+                    borders_.set_color(Borders::urgent_color());
+                    borders_.set_title_color(Borders::urgent_title_color());
+                    borders_.set_title_font_color(Borders::urgent_title_font_color());
+                }
+                Ok(())
+            },
+            _ => Err(TreeError::Container(
+                ContainerErr::BadOperationOn(c_type,
+                                             "urgent_border_color")))
+        }
+    }
+
     /// Clears the border color on a view or container.
     ///
     /// If called on a non-view/container, returns an appropriate error.
     pub fn clear_border_color(&mut self) -> CommandResult {
         let c_type = self.get_type();
+        // The colors of the view are used instead of the inactive ones
+        let settings = self.border_settings();
         match *self {
             Container::View { ref mut borders, .. } |
             Container::Container { ref mut borders, .. }=> {
                 if let Some(borders_) = borders.as_mut() {
-                    borders_.set_color(None);
-                    borders_.set_title_color(None);
-                    borders_.set_title_font_color(None);
+                    borders_.set_color(settings.color);
+                    borders_.set_title_color(settings.title_color);
+                    borders_.set_title_font_color(settings.title_font_color);
                 }
                 Ok(())
             },
@@ -615,6 +837,39 @@ impl Container {
         }
     }
 
+    /// Gets the style and colors of the borders of a view, which are the
+    /// ones set for it, or else the ones set by its rules.
+    ///
+    /// Non-views have nothing set.
+    pub fn border_settings(&self) -> BorderSettings {
+        match *self {
+            Container::View { handle, border_settings, .. } => {
+                // Bad options are warned about when the rules are loaded
+                let options = rules::options_for(handle);
+                let rule_settings = BorderSettings::from_options(&options)
+                    .unwrap_or_else(|_| BorderSettings::default());
+                border_settings.or(rule_settings)
+            },
+            _ => BorderSettings::default()
+        }
+    }
+
+    /// Sets the style and colors of the borders of a view,
+    /// instead of what its rules say.
+    ///
+    /// If called on a non-view, returns an appropriate error.
+    pub fn set_border_settings(&mut self, settings: BorderSettings)
+                               -> Result<(), ContainerErr> {
+        match *self {
+            Container::View { ref mut border_settings, .. } => {
+                *border_settings = settings;
+                Ok(())
+            },
+            ref other => Err(ContainerErr::BadOperationOn(
+                other.get_type(), "Only views have border settings"))
+        }
+    }
+
     /// Gets the title for a wlc handle.
     /// Tries to get the title, then defers to class if blank,
     /// and finally to the app_id if that is blank as well.
@@ -631,6 +886,27 @@ impl Container {
     }
 }
 
+/// Replaces the borders rendered around the view with a copy of these.
+///
+/// The copy has a surface of its own, a shared one can't be rendered.
+/// It is only copied again when the borders were drawn again.
+fn cache_borders(view: WlcView, borders: Option<&mut Borders>) {
+    if let Ok(mut rendered) = ::layout::lock_rendered_borders() {
+        match borders {
+            Some(borders) => {
+                let up_to_date = rendered.get(&view)
+                    .map_or(false, |cached| cached.drawn_like(borders));
+                if !up_to_date {
+                    rendered.insert(view, borders.unshared_copy());
+                }
+            },
+            None => {
+                rendered.remove(&view);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
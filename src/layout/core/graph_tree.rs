@@ -91,12 +91,14 @@ impl InnerTree {
     }
 
     /// Gets the active path, starting at the root node.
+    ///
+    /// The active path never leads to a minimized view.
     pub fn active_path(&self) -> Vec<(NodeIndex, &Path)> {
         let mut result = Vec::with_capacity(self.graph.edge_count());
         let mut next_ix = Some(self.root);
         while let Some(cur_ix) = next_ix {
             let maybe_edge = self.graph.edges(cur_ix)
-                .find(|e| e.weight().is_active());
+                .find(|e| e.weight().is_active() && !self[e.target()].minimized());
             if let Some(edge) = maybe_edge {
                 result.push((edge.target(), edge.weight()));
                 next_ix = Some(edge.target());
@@ -113,7 +115,7 @@ impl InnerTree {
         let mut next_ix = Some(node_ix);
         while let Some(cur_ix) = next_ix {
             let maybe_edge = self.graph.edges(cur_ix)
-                .find(|e| e.weight().is_active());
+                .find(|e| e.weight().is_active() && !self[e.target()].minimized());
             if let Some(edge) = maybe_edge {
                 next_ix = Some(edge.target());
             } else {
@@ -137,7 +139,7 @@ impl InnerTree {
                 return Ok(cur_ix);
             }
             let maybe_edge = self.graph.edges(cur_ix)
-                .find(|e| e.weight().is_active());
+                .find(|e| e.weight().is_active() && !self[e.target()].minimized());
             if let Some(edge) = maybe_edge {
                 next_ix = Some(edge.target());
             } else {
@@ -154,7 +156,9 @@ impl InnerTree {
     pub fn lowest_active_view(&self, node_ix: NodeIndex) -> Option<NodeIndex> {
         let mut cur_ix = node_ix;
         while self[cur_ix].get_type() != ContainerType::View {
-            let maybe_edge = self.graph.edges(cur_ix).min_by_key(|e| e.weight().active);
+            let maybe_edge = self.graph.edges(cur_ix)
+                .filter(|e| !self[e.target()].minimized())
+                .min_by_key(|e| e.weight().active);
             if let Some(edge) = maybe_edge {
                 cur_ix = edge.target();
             } else {
@@ -200,10 +204,16 @@ impl InnerTree {
         let edge = self.attach_child(parent_ix, child_ix);
         if active {
             self.set_ancestor_paths_active(child_ix);
-        } else {
+        } else if self.children_of(parent_ix).len() == 1 {
             let mut weight = self.graph.edge_weight_mut(edge)
                 .expect("Could not get edge weight of parent/child");
             weight.active = 1;
+        } else {
+            // Put it behind its siblings, without taking the number of one of them
+            self.graph.edge_weight_mut(edge)
+                .expect("Could not get edge weight of parent/child")
+                .active = u32::max_value();
+            self.normalize_edge_active(parent_ix);
         }
         self.id_map.insert(id, child_ix);
         if let Some(view) = maybe_view {
@@ -305,7 +315,7 @@ impl InnerTree {
             .unwrap_or(Path::zero());
         highest_weight.weight = *highest_weight + 1;
         self.graph.update_edge(target, source, highest_weight);
-        if !self[source].floating() {
+        if !self[source].floating() && !self[source].minimized() {
             self.set_ancestor_paths_active(source);
         }
         self.normalize_edge_weights(source_parent);
@@ -507,9 +517,11 @@ impl InnerTree {
 
     /// Determines if the container node can be removed because it is empty.
     /// If it is a non-root container then it can never be removed.
+    /// Placeholders are meant to be empty, so they can't be removed either.
     pub fn can_remove_empty_parent(&self, container_ix: NodeIndex) -> bool {
         if self.graph[container_ix].get_type() != ContainerType::Container
-        || self.is_root_container(container_ix) {
+        || self.is_root_container(container_ix)
+        || self.graph[container_ix].placeholder().is_some() {
             return false
         }
         if self.children_of(container_ix).len() == 0 {
@@ -564,23 +576,26 @@ impl InnerTree {
         edges.into_iter().map(|e| e.target()).collect()
     }
 
-    /// Collects all **floating** children of a node, sorted by weight
+    /// Collects all **floating** children of a node, sorted by weight.
+    /// Minimized views are not included.
     ///
     /// Will return an empty iterator if the node has no children or
     pub fn floating_children(&self, node_ix: NodeIndex) -> Vec<NodeIndex> {
         let mut edges = self.graph.edges(node_ix)
-            .filter(|e| self[e.target()].floating())
+            .filter(|e| self[e.target()].floating() && !self[e.target()].minimized())
             .collect::<Vec<_>>();
         edges.sort_by_key(|e| e.weight());
         edges.into_iter().map(|e| e.target()).collect()
     }
 
-    /// Collects all **non-floating** children of a node, sorted by weight
+    /// Collects all **non-floating** children of a node, sorted by weight.
+    /// Minimized views are not included.
     ///
     /// Will return an empty iterator if the node has no children or
     pub fn grounded_children(&self, node_ix: NodeIndex) -> Vec<NodeIndex> {
         let mut edges = self.graph.edges(node_ix)
-            .filter(|edge| !self[edge.target()].floating())
+            .filter(|edge| !self[edge.target()].floating()
+                    && !self[edge.target()].minimized())
             .collect::<Vec<_>>();
         edges.sort_by_key(|e| e.weight());
         edges.into_iter().map(|e| e.target()).collect()
@@ -660,6 +675,27 @@ impl InnerTree {
         return Err(GraphError::NotFound(container_type, node_ix))
     }
 
+    /// Like `descendant_of_type`, but skips minimized views,
+    /// which can't be focused.
+    pub fn focusable_descendant_of_type(&self, node_ix: NodeIndex,
+                                        container_type: ContainerType)
+                                        -> Result<NodeIndex, GraphError> {
+        if let Some(container) = self.get(node_ix) {
+            if container.minimized() {
+                return Err(GraphError::NotFound(container_type, node_ix))
+            }
+            if container.get_type() == container_type {
+                return Ok(node_ix)
+            }
+        }
+        for child in self.children_of(node_ix) {
+            if let Ok(desc) = self.focusable_descendant_of_type(child, container_type) {
+                return Ok(desc)
+            }
+        }
+        return Err(GraphError::NotFound(container_type, node_ix))
+    }
+
     /// Attempts to get a descendant of the matching type.
     /// Looks down the right side of the tree first
     #[allow(dead_code)]
@@ -722,7 +758,10 @@ impl InnerTree {
     /// Sets the node and its children's visibility
     pub fn set_family_visible(&mut self, node_ix: NodeIndex, visible: bool) {
         trace!("Setting {:?} to {}", node_ix, if visible {"visible"} else {"invisible"});
-        self.get_mut(node_ix).map(|c| c.set_visibility(visible));
+        // Minimized views stay hidden, no matter what their family does
+        self.get_mut(node_ix).map(|c| if !c.minimized() {
+            c.set_visibility(visible)
+        });
         for child in self.children_of(node_ix) {
             self.set_family_visible(child, visible);
         }
@@ -3,6 +3,8 @@ pub mod container;
 pub mod action;
 pub mod bar;
 pub mod borders;
+pub mod rules;
+pub mod reservation;
 mod path;
 mod graph_tree;
 
@@ -0,0 +1,153 @@
+//! Space reserved along the edges of an output, for docks and panels.
+//!
+//! Workspaces are laid out in what is left of the output after every
+//! reservation is taken off. Reservations on the same edge are stacked,
+//! the first one made being the closest to the edge.
+
+use rustwlc::{Geometry, Point, Size, WlcView};
+use uuid::Uuid;
+
+/// An edge of an output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right
+}
+
+impl Edge {
+    /// Gets the edge by its name, e.g "top".
+    pub fn from_name(name: &str) -> Option<Edge> {
+        match name.to_lowercase().as_str() {
+            "top" => Some(Edge::Top),
+            "bottom" => Some(Edge::Bottom),
+            "left" => Some(Edge::Left),
+            "right" => Some(Edge::Right),
+            _ => None
+        }
+    }
+
+    /// Gets the name of the edge.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Edge::Top => "top",
+            Edge::Bottom => "bottom",
+            Edge::Left => "left",
+            Edge::Right => "right"
+        }
+    }
+}
+
+/// Space reserved along an edge of an output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reservation {
+    /// The id used to release the reservation.
+    pub id: Uuid,
+    pub edge: Edge,
+    /// How far the space reaches into the output, in logical pixels.
+    pub size: u32,
+    /// The panel the space is reserved for, if it's a view.
+    /// The reservation is released when the view closes.
+    pub owner: Option<WlcView>
+}
+
+impl Reservation {
+    pub fn new(edge: Edge, size: u32, owner: Option<WlcView>) -> Self {
+        Reservation {
+            id: Uuid::new_v4(),
+            edge: edge,
+            size: size,
+            owner: owner
+        }
+    }
+
+    /// Splits the geometry into the space this reserves and the rest of it.
+    pub fn split(&self, geometry: Geometry) -> (Geometry, Geometry) {
+        let Geometry { origin: Point { x, y }, size: Size { w, h } } = geometry;
+        match self.edge {
+            Edge::Top | Edge::Bottom => {
+                let size = if self.size < h { self.size } else { h };
+                let (strip_y, rest_y) = if self.edge == Edge::Top {
+                    (y, y + size as i32)
+                } else {
+                    (y + (h - size) as i32, y)
+                };
+                (Geometry { origin: Point { x: x, y: strip_y }, size: Size { w: w, h: size } },
+                 Geometry { origin: Point { x: x, y: rest_y }, size: Size { w: w, h: h - size } })
+            },
+            Edge::Left | Edge::Right => {
+                let size = if self.size < w { self.size } else { w };
+                let (strip_x, rest_x) = if self.edge == Edge::Left {
+                    (x, x + size as i32)
+                } else {
+                    (x + (w - size) as i32, x)
+                };
+                (Geometry { origin: Point { x: strip_x, y: y }, size: Size { w: size, h: h } },
+                 Geometry { origin: Point { x: rest_x, y: y }, size: Size { w: w - size, h: h } })
+            }
+        }
+    }
+}
+
+/// Takes the reserved space off the geometry.
+pub fn shrink(geometry: Geometry, reservations: &[Reservation]) -> Geometry {
+    reservations.iter().fold(geometry, |rest, reservation| reservation.split(rest).1)
+}
+
+/// Gets the space taken up by the reservation with the id,
+/// if it's one of the reservations.
+pub fn reserved_space(geometry: Geometry, reservations: &[Reservation], id: Uuid)
+                      -> Option<Geometry> {
+    let mut rest = geometry;
+    for reservation in reservations {
+        let (strip, remaining) = reservation.split(rest);
+        if reservation.id == id {
+            return Some(strip)
+        }
+        rest = remaining;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use rustwlc::{Geometry, Point, Size};
+    use super::*;
+
+    #[test]
+    fn stacking_reservations() {
+        let output = Geometry {
+            origin: Point { x: 0, y: 0 },
+            size: Size { w: 800, h: 600 }
+        };
+        let reservations = vec![Reservation::new(Edge::Top, 20, None),
+                                Reservation::new(Edge::Top, 30, None),
+                                Reservation::new(Edge::Left, 50, None),
+                                Reservation::new(Edge::Bottom, 1000, None)];
+        assert_eq!(reserved_space(output, &reservations, reservations[1].id),
+                   Some(Geometry {
+                       origin: Point { x: 0, y: 20 },
+                       size: Size { w: 800, h: 30 }
+                   }));
+        assert_eq!(reserved_space(output, &reservations, reservations[2].id),
+                   Some(Geometry {
+                       origin: Point { x: 0, y: 50 },
+                       size: Size { w: 50, h: 550 }
+                   }));
+        // Too large a reservation takes what is left, instead of overflowing
+        assert_eq!(shrink(output, &reservations),
+                   Geometry {
+                       origin: Point { x: 50, y: 50 },
+                       size: Size { w: 750, h: 0 }
+                   });
+        assert_eq!(shrink(output, &reservations[..3]),
+                   Geometry {
+                       origin: Point { x: 50, y: 50 },
+                       size: Size { w: 750, h: 550 }
+                   });
+        assert_eq!(reserved_space(output, &reservations[..1], reservations[1].id), None);
+        assert_eq!(Edge::from_name("Bottom"), Some(Edge::Bottom));
+        assert_eq!(Edge::from_name("middle"), None);
+    }
+}
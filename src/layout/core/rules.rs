@@ -0,0 +1,156 @@
+//! Window rules.
+//!
+//! Rules are read from the "rules" category of the registry. Each key of the
+//! category is the name of a rule, whose value is a table of the criteria
+//! a view has to match and the options that are applied to the views that do:
+//!
+//! ```lua
+//! way_cooler.rules = {
+//!   video = { class = "mpv", min_width = 320, min_height = 240 }
+//! }
+//! ```
+//!
+//! `class` and `app_id` have to match exactly, `title` matches if it is
+//! contained in the title of the view. A rule without any criteria
+//! does not match any view.
+
+use std::collections::BTreeMap;
+
+use rustc_serialize::json::Json;
+use rustwlc::WlcView;
+use uuid::Uuid;
+
+use ::registry;
+use super::borders::style::BorderSettings;
+
+/// The keys of a rule that are used to match views, instead of being options.
+const CRITERIA: &'static [&'static str] = &["class", "title", "app_id"];
+
+/// The options set by rules, by name.
+pub type RuleOptions = BTreeMap<String, Json>;
+
+/// What a view has to look like to be matched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Criteria {
+    /// Has to be the same as the class of the view
+    pub class: Option<String>,
+    /// Has to be contained in the title of the view
+    pub title: Option<String>,
+    /// Has to be the same as the app id of the view
+    pub app_id: Option<String>
+}
+
+impl Criteria {
+    /// Reads the criteria from a table, ignoring any keys that aren't criteria.
+    pub fn from_table(table: &BTreeMap<String, Json>) -> Self {
+        let criterion = |name: &str| table.get(name)
+            .and_then(Json::as_string)
+            .map(str::to_string);
+        Criteria {
+            class: criterion("class"),
+            title: criterion("title"),
+            app_id: criterion("app_id")
+        }
+    }
+
+    /// Determines if there is nothing to match on.
+    pub fn is_empty(&self) -> bool {
+        self.class.is_none() && self.title.is_none() && self.app_id.is_none()
+    }
+
+    /// Determines if the view matches all of the criteria.
+    pub fn matches_view(&self, view: WlcView) -> bool {
+        self.matches(&view.get_class(), &view.get_title(), &view.get_app_id())
+    }
+
+    /// Determines if a view with the class, title, and app id
+    /// matches all of the criteria.
+    ///
+    /// Empty criteria don't match anything.
+    pub fn matches(&self, class: &str, title: &str, app_id: &str) -> bool {
+        !self.is_empty()
+            && self.class.as_ref().map_or(true, |value| value == class)
+            && self.title.as_ref().map_or(true, |value| title.contains(value.as_str()))
+            && self.app_id.as_ref().map_or(true, |value| value == app_id)
+    }
+}
+
+/// Gets the options of every rule that matches the view.
+///
+/// Rules are applied in the order of their names,
+/// so if two rules set the same option the last one wins.
+pub fn options_for(view: WlcView) -> RuleOptions {
+    let lock = registry::clients_read();
+    let client = lock.client(Uuid::nil()).unwrap();
+    let handle = registry::ReadHandle::new(&client);
+    let mut options = RuleOptions::new();
+    if let Ok(rules) = handle.read("rules".into()) {
+        let mut names: Vec<&String> = rules.keys().collect();
+        names.sort();
+        for name in names {
+            // Bad rules are warned about in `check_rules`
+            let rule = match rules[name].as_object() {
+                Some(rule) => rule,
+                None => continue
+            };
+            if !Criteria::from_table(rule).matches_view(view) {
+                continue
+            }
+            for (key, value) in rule.iter() {
+                if !CRITERIA.contains(&key.as_str()) {
+                    options.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+    options
+}
+
+/// Warns about the rules that can't be applied, which are ignored.
+///
+/// This should be called after the rules change, so that looking up the
+/// options of views doesn't have to warn about them each time.
+pub fn check_rules() {
+    let lock = registry::clients_read();
+    let client = lock.client(Uuid::nil()).unwrap();
+    let handle = registry::ReadHandle::new(&client);
+    if let Ok(rules) = handle.read("rules".into()) {
+        for (name, rule) in rules.iter() {
+            match rule.as_object() {
+                Some(rule) => {
+                    if let Err(err) = BorderSettings::from_options(rule) {
+                        warn!("Bad border options in rule {}: {}", name, err);
+                    }
+                },
+                None => warn!("Rule {} is not a table, ignoring it", name)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use rustc_serialize::json::Json;
+    use super::Criteria;
+
+    fn matches(rule: &BTreeMap<String, Json>, class: &str, title: &str, app_id: &str)
+               -> bool {
+        Criteria::from_table(rule).matches(class, title, app_id)
+    }
+
+    #[test]
+    fn rule_matching() {
+        let mut rule = BTreeMap::new();
+        assert!(!matches(&rule, "mpv", "video.mkv - mpv", ""));
+        rule.insert("class".to_string(), Json::String("mpv".into()));
+        assert!(matches(&rule, "mpv", "video.mkv - mpv", ""));
+        assert!(!matches(&rule, "firefox", "video.mkv - mpv", ""));
+        rule.insert("title".to_string(), Json::String(".mkv".into()));
+        assert!(matches(&rule, "mpv", "video.mkv - mpv", ""));
+        assert!(!matches(&rule, "mpv", "video.mp4 - mpv", ""));
+        // options aren't criteria
+        rule.insert("min_width".to_string(), Json::U64(320));
+        assert!(matches(&rule, "mpv", "video.mkv - mpv", ""));
+    }
+}
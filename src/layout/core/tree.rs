@@ -7,15 +7,24 @@ use uuid::Uuid;
 use rustwlc::callback::{positioner_get_anchor_rect, positioner_get_size,};
 use rustwlc::{ResizeEdge, WlcView, WlcOutput,
               RESIZE_LEFT, RESIZE_RIGHT, RESIZE_TOP, RESIZE_BOTTOM};
-use ::render::{Renderable};
 use super::super::LayoutTree;
 use super::super::ActionErr;
 use super::container::{Container, ContainerType, ContainerErr, Layout, Handle};
 use super::borders::{Borders};
+use ::layout::actions::border_style::new_view_borders;
 use ::layout::actions::focus::FocusError;
 use ::layout::actions::movement::MovementError;
 use ::layout::actions::layout::LayoutErr;
 use ::layout::actions::resize::ResizeErr;
+use ::layout::actions::minimize::MinimizeErr;
+use ::layout::actions::history::HistoryErr;
+use ::layout::actions::template::TemplateErr;
+use ::layout::actions::workspace::WorkspaceErr;
+use ::layout::actions::urgent::UrgentErr;
+use ::layout::actions::hints::HintsErr;
+use ::layout::actions::switcher::SwitcherErr;
+use ::layout::actions::output::OutputErr;
+use ::layout::actions::reservation::ReservationErr;
 
 
 use super::super::core::graph_tree::GraphError;
@@ -106,6 +115,24 @@ pub enum TreeError {
     Layout(LayoutErr),
     /// An error occurred while trying to resize the layout
     Resize(ResizeErr),
+    /// An error occurred while trying to minimize or restore a view
+    Minimize(MinimizeErr),
+    /// An error occurred while trying to undo or redo a change
+    History(HistoryErr),
+    /// An error occurred while trying to apply a layout template
+    Template(TemplateErr),
+    /// An error occurred while trying to rename, reorder or delete a workspace
+    Workspace(WorkspaceErr),
+    /// An error occurred while trying to focus on a view that wants attention
+    Urgent(UrgentErr),
+    /// An error occurred while trying to label views or act on a label in hint mode
+    Hints(HintsErr),
+    /// An error occurred while trying to open the window switcher
+    Switcher(SwitcherErr),
+    /// An error occurred while trying to change the settings of an output
+    Output(OutputErr),
+    /// An error occurred while trying to reserve space along the edge of an output
+    Reservation(ReservationErr),
     /// An error occurred while attempting to modify or use the main action
     Action(ActionErr),
     /// An error occurred while trying to do something with a container
@@ -208,8 +235,13 @@ impl LayoutTree {
         if let Some(active_ix) = self.active_container {
             let active_c = &mut self.tree[active_ix];
             if parent_node != self.active_container {
-                active_c.clear_border_color()
-                    .expect("Could not clear border color");
+                if active_c.urgent() {
+                    active_c.urgent_border_color()
+                        .expect("Could not set border color to be urgent");
+                } else {
+                    active_c.clear_border_color()
+                        .expect("Could not clear border color");
+                }
                 active_c.draw_borders();
             }
         }
@@ -330,32 +362,37 @@ impl LayoutTree {
                 active_ix = try!(self.tree.parent_of(active_ix)
                                  .map_err(|err| TreeError::PetGraph(err)));
             }
-            let geometry = view.get_geometry()
-                .expect("View had no geometry");
-            let output = view.get_output();
-            let borders = Borders::new(geometry, output)
-                .map(|mut b| {
-                    b.title = Container::get_title(view);
-                    b
-                });
-            let view_ix = self.tree.add_child(active_ix,
-                                              Container::new_view(view, borders),
-                                              true);
+            let view_ix = self.add_view_container(active_ix, view, true);
             self.tree.set_child_pos(view_ix, prev_pos);
             self.validate();
-            match self.set_active_node(view_ix) {
-                Ok(_) => {},
-                Err(TreeError::Focus(FocusError::BlockedByFullscreen(_, _))) => {
-                    debug!("Blocked focus by fullscreen");
-                },
-                Err(err) => return Err(err)
-            }
+            try!(self.focus_new_view(view_ix));
             return Ok(&self.tree[view_ix])
         }
         self.validate();
         Err(TreeError::NoActiveContainer)
     }
 
+    /// Adds a container for the view to the container, with the borders it
+    /// starts out with. Tiled views are put in the tree through this.
+    pub fn add_view_container(&mut self, parent_ix: NodeIndex, view: WlcView,
+                              active: bool) -> NodeIndex {
+        let borders = new_view_borders(view);
+        self.tree.add_child(parent_ix, Container::new_view(view, borders), active)
+    }
+
+    /// Focuses on a view that was just added, unless a fullscreen view
+    /// is in the way.
+    pub fn focus_new_view(&mut self, view_ix: NodeIndex) -> CommandResult {
+        match self.set_active_node(view_ix) {
+            Ok(_) => Ok(()),
+            Err(TreeError::Focus(FocusError::BlockedByFullscreen(_, _))) => {
+                debug!("Blocked focus by fullscreen");
+                Ok(())
+            },
+            Err(err) => Err(err)
+        }
+    }
+
     /// Adds a new view container with the given WlcView to the workspace of the active container.
     ///
     /// The view is automatically made floating, with no modifications to its geometry.
@@ -438,6 +475,7 @@ impl LayoutTree {
         let output_ix = self.tree.add_child(root_ix,
                                             Container::new_output(output),
                                             true);
+        self.place_new_output(output_ix);
         self.active_container = Some(self.init_workspace("1".to_string(), output_ix));
         self.validate();
         Ok(())
@@ -448,6 +486,11 @@ impl LayoutTree {
         if let Some(view_ix) = self.tree.descendant_with_handle(self.tree.root_ix(), view) {
             let container = self.remove_view_or_container(view_ix)
                 .expect("Could not remove node we just verified exists!");
+            if container.urgent() {
+                self.urgent_changed();
+            }
+            let id = container.get_id();
+            self.focus_order.retain(|focused_id| *focused_id != id);
             self.validate();
             Ok(container)
         } else {
@@ -744,7 +787,8 @@ impl LayoutTree {
                             let parent_ix = self.tree.parent_of(container_ix)
                                 .expect("Container had no parent");
                             if self.tree.children_of(container_ix).len() == 0
-                                && self.tree[parent_ix].get_type() != ContainerType::Workspace {
+                                && self.tree[parent_ix].get_type() != ContainerType::Workspace
+                                && self.tree[container_ix].placeholder().is_none() {
                                     error!("Tree in invalid state. {:?} is an empty non-root container\n \
                                             {:#?}", container_ix, *self);
                                     panic!();
@@ -853,6 +897,7 @@ pub mod tests {
     use super::super::super::LayoutTree;
     use super::super::super::core::container::*;
     use super::super::super::core::InnerTree;
+    use super::super::super::actions::history::History;
     use super::*;
     use rustwlc::*;
 
@@ -901,13 +946,23 @@ pub mod tests {
                                                 Container::new_view(fake_view_1.clone(), None), false);
         let mut layout_tree = LayoutTree {
             tree: tree,
-            active_container: None
+            active_container: None,
+            history: History::new(),
+            previous_workspace: None,
+            focus_order: Vec::new()
         };
         let id = layout_tree.tree[wkspc_1_view].get_id();
         layout_tree.set_active_container(id).unwrap();
         layout_tree
     }
 
+    /// Gets the id of the active container, which must be a view.
+    pub fn active_view_id(tree: &LayoutTree) -> Uuid {
+        let active_ix = tree.active_container.expect("No active container");
+        assert_eq!(tree.tree[active_ix].get_type(), ContainerType::View);
+        tree.tree[active_ix].get_id()
+    }
+
     #[test]
     fn destroy_tree_test() {
         let mut tree = basic_tree();
@@ -3,17 +3,37 @@ mod core;
 pub mod commands;
 
 pub use self::actions::movement::MovementError;
-pub use self::actions::focus::FocusError;
+pub use self::actions::focus::note_interaction;
+pub use self::actions::pointer::focus_follows_mouse;
+pub use self::actions::workspace::WorkspaceScope;
+pub use self::actions::hints::{Hints, HintAction, HintInput};
+pub use self::actions::switcher::Switcher;
+pub use self::actions::output::configured_scale;
+pub use self::actions::status_bar::{set_bar_block, remove_bar_block, bar_workspace_at,
+                                    bar_outdated, mark_bars_outdated};
+pub use self::actions::background::{set_background, clear_background, background_outdated,
+                                    mark_backgrounds_outdated};
+pub use self::actions::osd::{OsdKind, OsdMessage, show_osd, render_osd, init_osd_timer};
+pub use self::actions::title_bar::{TitleBarClick, TitleBarPart, title_bar_click};
 
 pub use self::core::action::{Action, ActionErr};
 pub use self::core::container::{Container, ContainerType, Handle, Layout};
 pub use self::core::tree::{Direction, TreeError};
 pub use self::core::bar::Bar;
+pub use self::core::reservation::Edge;
+pub use self::core::borders::style::BorderSettings;
+pub use self::core::rules::check_rules;
 use self::core::InnerTree;
+use self::core::borders::Borders;
+use self::actions::history::History;
+use ::render::{Background, StatusBar};
 
 use petgraph::graph::NodeIndex;
+use uuid::Uuid;
+use rustwlc::WlcView;
 use rustc_serialize::json::{Json, ToJson};
 
+use std::collections::HashMap;
 use std::sync::{Mutex, MutexGuard, TryLockError, PoisonError};
 
 /// A wrapper around tree, to hide its methods
@@ -27,17 +47,31 @@ pub type TreeErr = TryLockError<TreeGuard>;
 #[derive(Debug)]
 pub struct LayoutTree {
     tree: InnerTree,
-    active_container: Option<NodeIndex>
+    active_container: Option<NodeIndex>,
+    history: History,
+    previous_workspace: Option<String>,
+    /// The ids of the views that were focused, the most recent first.
+    focus_order: Vec<Uuid>
 }
 
 lazy_static! {
     static ref TREE: Mutex<LayoutTree> = {
         Mutex::new(LayoutTree {
             tree: InnerTree::new(),
-            active_container: None
+            active_container: None,
+            history: History::new(),
+            previous_workspace: None,
+            focus_order: Vec::new()
         })
     };
     static ref PREV_ACTION: Mutex<Option<Action>> = Mutex::new(None);
+    static ref HINTS: Mutex<Option<Hints>> = Mutex::new(None);
+    static ref SWITCHER: Mutex<Option<Switcher>> = Mutex::new(None);
+    static ref STATUS_BARS: Mutex<Vec<StatusBar>> = Mutex::new(Vec::new());
+    static ref BACKGROUNDS: Mutex<Vec<Background>> = Mutex::new(Vec::new());
+    /// The borders last drawn around each view, so that they can be
+    /// rendered without locking the tree.
+    static ref RENDERED_BORDERS: Mutex<HashMap<WlcView, Borders>> = Mutex::new(HashMap::new());
 }
 
 impl ToJson for LayoutTree {
@@ -97,3 +131,47 @@ pub fn try_lock_action() -> Result<MutexGuard<'static, Option<Action>>,
                                                          Option<Action>>>> {
     PREV_ACTION.try_lock()
 }
+
+/// Attempts to lock the hint mode mutex, which holds the labels while
+/// hint mode is active. If the Result is Err, then the lock could
+/// not be returned at this time, already locked.
+pub fn try_lock_hints() -> Result<MutexGuard<'static, Option<Hints>>,
+                                TryLockError<MutexGuard<'static,
+                                                        Option<Hints>>>> {
+    HINTS.try_lock()
+}
+
+/// Attempts to lock the window switcher mutex, which holds the list of views
+/// while the switcher is shown. If the Result is Err, then the lock could
+/// not be returned at this time, already locked.
+pub fn try_lock_switcher() -> Result<MutexGuard<'static, Option<Switcher>>,
+                                   TryLockError<MutexGuard<'static,
+                                                           Option<Switcher>>>> {
+    SWITCHER.try_lock()
+}
+
+/// Attempts to lock the status bar mutex, which holds the bar of each
+/// output. If the Result is Err, then the lock could not be returned
+/// at this time, already locked.
+pub fn try_lock_status_bars() -> Result<MutexGuard<'static, Vec<StatusBar>>,
+                                      TryLockError<MutexGuard<'static,
+                                                              Vec<StatusBar>>>> {
+    STATUS_BARS.try_lock()
+}
+
+/// Attempts to lock the background mutex, which holds the background
+/// painted on each output. If the Result is Err, then the lock could not
+/// be returned at this time, already locked.
+pub fn try_lock_backgrounds() -> Result<MutexGuard<'static, Vec<Background>>,
+                                      TryLockError<MutexGuard<'static,
+                                                              Vec<Background>>>> {
+    BACKGROUNDS.try_lock()
+}
+
+/// Locks the mutex of the borders drawn around views, which is only held
+/// long enough to render or replace them.
+pub fn lock_rendered_borders() -> Result<MutexGuard<'static, HashMap<WlcView, Borders>>,
+                                         PoisonError<MutexGuard<'static,
+                                                                HashMap<WlcView, Borders>>>> {
+    RENDERED_BORDERS.lock()
+}
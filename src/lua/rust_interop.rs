@@ -1,7 +1,8 @@
 //! Rust code which is called from lua in the init file
 #![deny(dead_code)]
 
-use rustc_serialize::json::ToJson;
+use std::time::Duration;
+use rustc_serialize::json::{Json, ToJson};
 use uuid::Uuid;
 use super::{send, LuaQuery, running};
 use hlua::{self, Lua, LuaTable};
@@ -10,7 +11,8 @@ use hlua::any::AnyLuaValue;
 use registry::{self};
 use commands;
 use keys::{self, KeyPress, KeyEvent};
-use convert::json::{json_to_lua};
+use convert::json::{json_to_lua, lua_to_json};
+use layout::{self, BorderSettings, Edge, OsdKind, OsdMessage, try_lock_tree};
 
 use super::thread::{update_registry_value};
 
@@ -31,6 +33,19 @@ pub fn register_libraries(lua: &mut Lua) {
         rust_table.set("ipc_run", hlua::function1(ipc_run));
         rust_table.set("ipc_get", hlua::function2(ipc_get));
         rust_table.set("ipc_set", hlua::function1(ipc_set));
+        rust_table.set("apply_layout", hlua::function2(apply_layout));
+        rust_table.set("switch_workspace", hlua::function1(switch_workspace));
+        rust_table.set("move_to_workspace", hlua::function1(move_to_workspace));
+        rust_table.set("move_workspace_to_output",
+                       hlua::function2(move_workspace_to_output));
+        rust_table.set("list_outputs", hlua::function0(list_outputs));
+        rust_table.set("configure_output", hlua::function2(configure_output));
+        rust_table.set("reserve_edge", hlua::function3(reserve_edge));
+        rust_table.set("release_edge", hlua::function1(release_edge));
+        rust_table.set("set_background", hlua::function2(set_background));
+        rust_table.set("clear_background", hlua::function1(clear_background));
+        rust_table.set("show_osd", hlua::function2(show_osd));
+        rust_table.set("set_borders", hlua::function2(set_borders));
     }
     trace!("Executing Lua init...");
     let init_code = include_str!("../../lib/lua/lua_init.lua");
@@ -71,6 +86,147 @@ fn ipc_set(category: String) -> Result<(), &'static str> {
     Ok(())
 }
 
+/// Applies a layout template to a workspace
+fn apply_layout(workspace: String, template: AnyLuaValue) -> Result<(), String> {
+    let template = try!(lua_to_json(template)
+                        .map_err(|_| "template: expected a table".to_string()));
+    let mut tree = try!(try_lock_tree()
+                        .map_err(|_| "Could not lock the tree".to_string()));
+    tree.apply_template(&workspace, &template)
+        .map_err(|err| format!("Could not apply layout to {}: {:?}", workspace, err))
+}
+
+/// Switches to the workspace with the name, creating it if it doesn't exist
+fn switch_workspace(workspace: String) -> Result<(), String> {
+    let mut tree = try!(try_lock_tree()
+                        .map_err(|_| "Could not lock the tree".to_string()));
+    tree.switch_to_workspace(&workspace)
+        .map_err(|err| format!("Could not switch to {}: {:?}", workspace, err))
+}
+
+/// Moves the active container to the workspace with the name,
+/// creating it if it doesn't exist
+fn move_to_workspace(workspace: String) -> Result<(), String> {
+    let mut tree = try!(try_lock_tree()
+                        .map_err(|_| "Could not lock the tree".to_string()));
+    tree.send_active_to_workspace(&workspace)
+        .map_err(|err| format!("Could not move to {}: {:?}", workspace, err))
+}
+
+/// Moves a workspace to the output with the name (e.g "DP-1")
+fn move_workspace_to_output(workspace: String, output: String) -> Result<(), String> {
+    let mut tree = try!(try_lock_tree()
+                        .map_err(|_| "Could not lock the tree".to_string()));
+    let output_id = try!(tree.output_by_name(&output)
+                         .ok_or(format!("There is no output named {}", output)));
+    tree.move_workspace_to_output(&workspace, output_id)
+        .map_err(|err| format!("Could not move {} to {}: {:?}", workspace, output, err))
+}
+
+/// Lists the outputs, keyed by their name
+fn list_outputs() -> Result<AnyLuaValue, String> {
+    let tree = try!(try_lock_tree()
+                    .map_err(|_| "Could not lock the tree".to_string()));
+    let outputs = match tree.outputs_info() {
+        Json::Array(outputs) => outputs,
+        _ => unreachable!()
+    };
+    let by_name = outputs.into_iter()
+        .filter_map(|output| output.find("name").and_then(Json::as_string)
+                    .map(str::to_string)
+                    .map(|name| (name, output.clone())))
+        .collect();
+    Ok(json_to_lua(Json::Object(by_name)))
+}
+
+/// Applies settings (resolution, position, scale and enabled)
+/// to the output with the name (e.g "DP-1")
+fn configure_output(output: String, settings: AnyLuaValue) -> Result<(), String> {
+    let settings = try!(lua_to_json(settings)
+                        .map_err(|_| "settings: expected a table".to_string()));
+    let mut tree = try!(try_lock_tree()
+                        .map_err(|_| "Could not lock the tree".to_string()));
+    let output_id = try!(tree.output_by_name(&output)
+                         .ok_or(format!("There is no output named {}", output)));
+    tree.configure_output(output_id, &settings)
+        .map_err(|err| format!("Could not configure {}: {:?}", output, err))
+}
+
+/// Reserves space along the edge ("top", "bottom", "left" or "right") of the
+/// output with the name, returning the id to release it with
+fn reserve_edge(output: String, edge: String, size: u32) -> Result<String, String> {
+    let edge = try!(Edge::from_name(&edge)
+                    .ok_or(format!("{} is not an edge of an output", edge)));
+    let mut tree = try!(try_lock_tree()
+                        .map_err(|_| "Could not lock the tree".to_string()));
+    let output_id = try!(tree.output_by_name(&output)
+                         .ok_or(format!("There is no output named {}", output)));
+    tree.reserve_edge(output_id, edge, size)
+        .map(|id| id.to_string())
+        .map_err(|err| format!("Could not reserve space on {}: {:?}", output, err))
+}
+
+/// Releases space reserved with `reserve_edge`
+fn release_edge(id: String) -> Result<(), String> {
+    let id = try!(Uuid::parse_str(&id)
+                  .map_err(|_| format!("{} is not a reservation id", id)));
+    let mut tree = try!(try_lock_tree()
+                        .map_err(|_| "Could not lock the tree".to_string()));
+    tree.release_reservation(id)
+        .map_err(|err| format!("Could not release {}: {:?}", id, err))
+}
+
+/// Sets the background of "default", an output (e.g "DP-1")
+/// or a workspace ("workspace:<name>")
+fn set_background(target: String, background: AnyLuaValue) -> Result<(), String> {
+    let background = try!(lua_to_json(background)
+                          .map_err(|_| "background: expected a color, path or table".to_string()));
+    layout::set_background(target, &background)
+        .map_err(|err| format!("Could not set the background: {:?}", err))
+}
+
+/// Goes back to the configured background of the target
+fn clear_background(target: String) -> Result<(), String> {
+    layout::clear_background(&target);
+    Ok(())
+}
+
+/// Shows a message on the focused output for a moment. The options are
+/// a `value` (percentage) to draw as a bar, a `tag` so that messages with
+/// the same one replace each other, a `timeout` in seconds and `error`.
+fn show_osd(text: String, options: AnyLuaValue) -> Result<(), String> {
+    let options = try!(lua_to_json(options)
+                       .map_err(|_| "options: expected a table".to_string()));
+    let mut message = OsdMessage::info(text);
+    if options.find("error").and_then(Json::as_boolean) == Some(true) {
+        message.kind = OsdKind::Error;
+    }
+    message.value = options.find("value").and_then(Json::as_f64)
+        .map(|value| value.max(0.0) as u32);
+    message.tag = options.find("tag").and_then(Json::as_string).map(str::to_string);
+    message.timeout = options.find("timeout").and_then(Json::as_f64)
+        .map(|seconds| Duration::from_millis((seconds.max(0.0) * 1000.0) as u64));
+    layout::show_osd(message);
+    Ok(())
+}
+
+/// Sets the border style and colors of a view, or of the active view
+/// if the id is empty
+fn set_borders(id: String, settings: AnyLuaValue) -> Result<(), String> {
+    let settings = try!(lua_to_json(settings)
+                        .map_err(|_| "settings: expected a table".to_string())
+                        .and_then(|settings| BorderSettings::from_json(&settings)));
+    let mut tree = try!(try_lock_tree()
+                        .map_err(|_| "Could not lock the tree".to_string()));
+    let id = if id.is_empty() {
+        try!(tree.active_id().ok_or("There is no active view".to_string()))
+    } else {
+        try!(Uuid::parse_str(&id).map_err(|_| format!("{} is not a view id", id)))
+    };
+    tree.set_border_settings(id, settings)
+        .map_err(|err| format!("Could not set the borders of {}: {:?}", id, err))
+}
+
 fn init_workspaces(_options: AnyLuaValue) -> Result<(), &'static str> {
     error!("Attempting to call `init_workspaces`, this is not implemented");
     Ok(())
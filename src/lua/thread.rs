@@ -23,7 +23,8 @@ use super::super::keys;
 
 use registry::{self};
 
-use ::layout::{lock_tree, ContainerType};
+use ::layout::{lock_tree, show_osd, mark_bars_outdated, mark_backgrounds_outdated, check_rules,
+               ContainerType, OsdMessage};
 
 lazy_static! {
     /// Sends requests to the Lua thread
@@ -118,6 +119,14 @@ pub fn send(query: LuaQuery) -> Result<Receiver<LuaResponse>, LuaSendError> {
 
 /// Initialize the Lua thread.
 pub fn init() {
+    start(false)
+}
+
+/// Starts the Lua thread, running the init file.
+///
+/// Whether the init file could be run is shown on the OSD when reloading,
+/// errors in it are shown every time.
+fn start(reloading: bool) {
     debug!("Initializing...");
     let (tx, receiver) = channel();
     *SENDER.lock().expect(ERR_LOCK_SENDER) = Some(tx);
@@ -133,9 +142,18 @@ pub fn init() {
             Ok(init_file) => {
                 let _: () = lua.execute_from_reader(init_file)
                     .map(|r| { debug!("Read init.lua successfully"); r })
+                    .map(|r| {
+                        if reloading {
+                            show_osd(OsdMessage::info("Reloaded init.lua".into()));
+                        }
+                        r
+                    })
                     .or_else(|err| {
                         error!("Lua error: {:?}", err);
                         warn!("Defaulting to pre-compiled init.lua");
+                        show_osd(OsdMessage::error(
+                            format!("Error in init.lua, using the default one instead:\n{}",
+                                    error_text(&err))));
                         lua.execute(init_path::DEFAULT_CONFIG)
                         })
                     .expect("Unable to load pre-compiled init file");
@@ -155,6 +173,8 @@ pub fn init() {
     // Call the special init hook function that we read from the init file
     if let Err(error) = lua.execute::<()>(INIT_LUA_FUNC) {
         error!("Lua init callback returned an error: {:?}", error);
+        show_osd(OsdMessage::error(
+            format!("Error in the init function:\n{}", error_text(&error))));
     }
     // Re-tile the layout tree, to make any changes appear immediantly.
     if let Ok(mut tree) = lock_tree() {
@@ -180,6 +200,16 @@ pub fn init() {
         .expect("Could not update registry from cache");
 }
 
+/// Gets the message of a Lua error, to show to the user.
+fn error_text(error: &LuaError) -> String {
+    match *error {
+        LuaError::SyntaxError(ref message) |
+        LuaError::ExecutionError(ref message) => message.clone(),
+        LuaError::ReadError(ref err) => format!("{}", err),
+        LuaError::WrongType => "Lua returned the wrong type".into()
+    }
+}
+
 /// Main loop of the Lua thread:
 ///
 /// ## Loop
@@ -234,7 +264,7 @@ fn handle_message(request: LuaMessage, lua: &mut Lua) -> bool {
             let _new_handle = thread::Builder::new()
                 .name("Lua re-init".to_string())
                 .spawn(move || {
-                    init();
+                    start(true);
                     keys::init();
                 });
 
@@ -291,6 +321,9 @@ fn handle_message(request: LuaMessage, lua: &mut Lua) -> bool {
             match lua.execute::<()>(&code) {
                 Err(error) => {
                     warn!("Error handling {}: {:?}", &press, error);
+                    show_osd(OsdMessage::error(
+                        format!("Error in the keybinding for {}:\n{}",
+                                press, error_text(&error))));
                     thread_send(request.reply, LuaResponse::Error(error));
                 }
                 Ok(_) => {
@@ -303,29 +336,69 @@ fn handle_message(request: LuaMessage, lua: &mut Lua) -> bool {
             thread_send(request.reply, LuaResponse::Pong);
         },
         LuaQuery::UpdateRegistryFromCache => {
-            let lock = registry::clients_read();
-            // Lua has access to everything
-            let client = lock.client(Uuid::nil()).unwrap();
-            let mut handle = registry::WriteHandle::new(&client);
-
-            let mut queue = REGISTRY_QUEUE.write().expect(ERR_LOCK_QUEUE);
-            for category in queue.drain(0..) {
-                let mut registry_cache = lua.get::<hlua::LuaTable<_>, _>("__registry_cache")
-                    .expect("__registry_cache wasn't defined");
-                if let Some(mut category_table) = registry_cache.get::<hlua::LuaTable<_>, _>(category.clone()) {
-                    let cat_table = match handle.write(category.clone()) {
-                        Ok(cat) => cat,
-                        Err(err) => {
-                            warn!("Could not lock {}: {:?}", category, err);
-                            break;
-                        }
-                    };
-                    update_values(&mut category_table, cat_table);
+            let mut outputs_changed = false;
+            let mut bar_changed = false;
+            let mut workspaces_changed = false;
+            let mut rules_changed = false;
+            let mut backgrounds_changed = false;
+            {
+                let lock = registry::clients_read();
+                // Lua has access to everything
+                let client = lock.client(Uuid::nil()).unwrap();
+                let mut handle = registry::WriteHandle::new(&client);
+
+                let mut queue = REGISTRY_QUEUE.write().expect(ERR_LOCK_QUEUE);
+                for category in queue.drain(0..) {
+                    outputs_changed |= category == "outputs";
+                    bar_changed |= category == "bar";
+                    workspaces_changed |= category == "workspaces";
+                    rules_changed |= category == "rules";
+                    backgrounds_changed |= category == "backgrounds";
+                    let mut registry_cache = lua.get::<hlua::LuaTable<_>, _>("__registry_cache")
+                        .expect("__registry_cache wasn't defined");
+                    if let Some(mut category_table) = registry_cache.get::<hlua::LuaTable<_>, _>(category.clone()) {
+                        let cat_table = match handle.write(category.clone()) {
+                            Ok(cat) => cat,
+                            Err(err) => {
+                                warn!("Could not lock {}: {:?}", category, err);
+                                break;
+                            }
+                        };
+                        update_values(&mut category_table, cat_table);
+                    }
+                    drop(registry_cache)
+                }
+                lua.execute::<()>("__registry_cache = {}")
+                    .expect("Could not clear __registry_cache");
+            }
+            // The registry must be unlocked, applying the settings reads it
+            if rules_changed {
+                check_rules();
+            }
+            if outputs_changed {
+                if let Ok(mut tree) = lock_tree() {
+                    tree.apply_output_settings()
+                        .unwrap_or_else(|err| {
+                            warn!("Could not apply the output settings: {:?}", err);
+                        });
                 }
-                drop(registry_cache)
             }
-            lua.execute::<()>("__registry_cache = {}")
-                .expect("Could not clear __registry_cache");
+            // The status bar takes up a different amount of space
+            if bar_changed {
+                if let Ok(mut tree) = lock_tree() {
+                    tree.layout_active_of(ContainerType::Root)
+                        .unwrap_or_else(|_| {
+                            warn!("Could not re-tile the layout tree for the bar");
+                        });
+                }
+            }
+            // Which workspaces the bars list depends on the workspace policy
+            if workspaces_changed {
+                mark_bars_outdated();
+            }
+            if backgrounds_changed {
+                mark_backgrounds_outdated();
+            }
         },
     }
     return true
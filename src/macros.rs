@@ -104,8 +104,8 @@ macro_rules! require_rustwlc {
 
 /// Create a dbus interface object.
 ///
-/// Given the path and name of a dbus interface
-/// and a series of methods with type ipc::DBusResult, this macro
+/// Given the path and name of a dbus interface, a series of read-only
+/// properties and a series of methods with type ipc::DBusResult, this macro
 /// generates a big function `setup(&mut DBusFactory) -> DBusObjPath`
 /// which will call the `Factory`'s `add_*` methods properly.
 ///
@@ -113,6 +113,7 @@ macro_rules! require_rustwlc {
 /// the name of the outputs.
 macro_rules! dbus_interface {
     ( path: $obj_path:expr; name: $obj_name:expr;
+      $(property $prop_name:ident : $prop_ty:ty { $($prop_inner:tt)* })*
       $(fn $fn_name:ident($($in_name:ident : $in_ty:ty),*)
                           -> $out_name:ident : DBusResult< $out_ty_inner:ty > { $($inner:tt)* })+ ) => {
         #[warn(dead_code)]
@@ -120,6 +121,14 @@ macro_rules! dbus_interface {
         pub fn setup(factory: &mut $crate::ipc::DBusFactory) -> $crate::ipc::DBusObjPath {
             return factory.object_path($obj_path, ()).introspectable()
                 .add(factory.interface($obj_name, ())
+                     $(
+                         .add_p(factory.property::<$prop_ty, _>(stringify!($prop_name), ())
+                                .emits_changed(::dbus::tree::EmitsChangedSignal::False)
+                                .on_get(move |iter, _| {
+                                    iter.append(try!($prop_name()));
+                                    Ok(())
+                                }))
+                     )*
                      $(
                          .add_m(factory.method(stringify!($fn_name), (),
                                 move |msg| {
@@ -146,6 +155,12 @@ macro_rules! dbus_interface {
                      )*
                 );
         }
+        $(
+            #[allow(non_snake_case)]
+            fn $prop_name() -> $crate::ipc::DBusResult<$prop_ty> {
+                $($prop_inner)*
+            }
+        )*
         $(
             #[allow(non_snake_case)]
             #[warn(dead_code)]
@@ -186,6 +186,7 @@ fn main() {
     let run_wlc = rustwlc::init()
         .expect("Unable to initialize wlc!");
     wayland::init_wayland_protocols();
+    layout::init_osd_timer();
     rustwlc::log_set_rust_handler(log_handler);
     callbacks::init();
     commands::init();
@@ -40,4 +40,22 @@ pub fn init() {
     // Construct the mouse category
     registry.add_category("mouse".into())
         .expect("Could not add mouse category");
+    // Construct the window rules category
+    registry.add_category("rules".into())
+        .expect("Could not add rules category");
+    // Construct the workspaces category
+    registry.add_category("workspaces".into())
+        .expect("Could not add workspaces category");
+    // Construct the outputs category
+    registry.add_category("outputs".into())
+        .expect("Could not add outputs category");
+    // Construct the status bar category
+    registry.add_category("bar".into())
+        .expect("Could not add bar category");
+    // Construct the backgrounds category
+    registry.add_category("backgrounds".into())
+        .expect("Could not add backgrounds category");
+    // Construct the on-screen display category
+    registry.add_category("osd".into())
+        .expect("Could not add osd category");
 }
@@ -0,0 +1,360 @@
+//! The background of an output, painted by Way Cooler beneath the views.
+//!
+//! A background is a solid color, a gradient between two colors,
+//! or a PNG image that is scaled to the output in one of several ways.
+
+use std::fmt::{self, Debug};
+use std::fs::File;
+use std::ops::{Deref, DerefMut};
+use rustc_serialize::json::Json;
+use rustwlc::{Geometry, Size, WlcOutput};
+use cairo::{Extend, Gradient, ImageSurface, LinearGradient};
+
+use super::renderable::{Renderable, blank_surface, output_scale};
+use super::draw::{BaseDraw, Drawable, DrawErr};
+use super::color::Color;
+
+/// How an image is scaled to the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageMode {
+    /// Scaled to cover the whole output, cutting off what doesn't fit.
+    Fill,
+    /// Scaled to be as large as possible while all of it is shown.
+    Fit,
+    /// Not scaled, in the middle of the output.
+    Center,
+    /// Not scaled, repeated from the top left corner.
+    Tile
+}
+
+impl ImageMode {
+    /// Gets the mode by its name, e.g "fill".
+    pub fn from_name(name: &str) -> Option<ImageMode> {
+        match name.to_lowercase().as_str() {
+            "fill" => Some(ImageMode::Fill),
+            "fit" => Some(ImageMode::Fit),
+            "center" => Some(ImageMode::Center),
+            "tile" => Some(ImageMode::Tile),
+            _ => None
+        }
+    }
+}
+
+/// What the background looks like.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackgroundStyle {
+    /// One color everywhere.
+    Color(Color),
+    /// Fades from one color to another, from the top to the bottom
+    /// or from the left to the right.
+    Gradient { from: Color, to: Color, vertical: bool },
+    /// A PNG image. The color is shown where the image doesn't cover the output.
+    Image { path: String, mode: ImageMode, color: Color }
+}
+
+impl BackgroundStyle {
+    /// Reads a background from how it is configured, one of:
+    ///
+    /// * a color, e.g `0x5E4055`
+    /// * the path to a PNG image, which fills the output
+    /// * a table with a `color`
+    /// * a table with an `image` and optionally a `mode`
+    ///   (fill, fit, center or tile) and a `color`
+    /// * a table with the `from` and `to` colors of a gradient, and
+    ///   optionally the `direction` ("vertical" or "horizontal")
+    pub fn from_json(json: &Json) -> Option<Self> {
+        let color = |value: &Json| value.as_f64().map(|num| Color::from(num as u32));
+        if let Some(background) = color(json) {
+            return Some(BackgroundStyle::Color(background))
+        }
+        if let Some(path) = json.as_string() {
+            return Some(BackgroundStyle::Image {
+                path: path.to_string(),
+                mode: ImageMode::Fill,
+                color: Color::solid_color(0, 0, 0)
+            })
+        }
+        let table = match json.as_object() {
+            Some(table) => table,
+            None => return None
+        };
+        let background = table.get("color").and_then(&color);
+        if let Some(path) = table.get("image").and_then(Json::as_string) {
+            let mode = match table.get("mode").and_then(Json::as_string) {
+                Some(name) => match ImageMode::from_name(name) {
+                    Some(mode) => mode,
+                    None => return None
+                },
+                None => ImageMode::Fill
+            };
+            return Some(BackgroundStyle::Image {
+                path: path.to_string(),
+                mode: mode,
+                color: background.unwrap_or(Color::solid_color(0, 0, 0))
+            })
+        }
+        match (table.get("from").and_then(&color), table.get("to").and_then(&color)) {
+            (Some(from), Some(to)) => {
+                let vertical = match table.get("direction").and_then(Json::as_string) {
+                    Some("horizontal") => false,
+                    Some("vertical") | None => true,
+                    Some(_) => return None
+                };
+                Some(BackgroundStyle::Gradient { from: from, to: to, vertical: vertical })
+            },
+            (None, None) => background.map(BackgroundStyle::Color),
+            _ => None
+        }
+    }
+}
+
+/// Loads a PNG image, so that it can be painted onto a background.
+fn load_image(path: &str) -> Option<ImageSurface> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) => {
+            warn!("Could not open background image {}: {:?}", path, err);
+            return None
+        }
+    };
+    let mut image = match ImageSurface::create_from_png(&mut file) {
+        Ok(image) => image,
+        Err(err) => {
+            warn!("Could not read background image {}: {:?}", path, err);
+            return None
+        }
+    };
+    // wlc reads the bytes in the opposite order to cairo, which `Color`
+    // accounts for. Swap red and blue so that the image does too.
+    match image.get_data() {
+        Ok(mut data) => {
+            for pixel in data.chunks_mut(4) {
+                pixel.swap(0, 2);
+            }
+        },
+        Err(err) => {
+            warn!("Could not read background image {}: {:?}", path, err);
+            return None
+        }
+    }
+    Some(image)
+}
+
+/// The background of an output.
+pub struct Background {
+    /// What the background looks like.
+    pub style: BackgroundStyle,
+    /// The image of the style, and the path it was loaded from.
+    image: Option<(String, ImageSurface)>,
+    /// The surface that contains the bytes we give to wlc to draw.
+    surface: ImageSurface,
+    /// Covers the whole output.
+    geometry: Geometry,
+    /// The output the background is drawn on.
+    output: WlcOutput
+}
+
+impl Renderable for Background {
+    fn new(geometry: Geometry, output: WlcOutput) -> Option<Self> {
+        let Size { w, h } = geometry.size;
+        if w == 0 || h == 0 {
+            return None
+        }
+        Some(Background {
+            style: BackgroundStyle::Color(Color::solid_color(0, 0, 0)),
+            image: None,
+            surface: blank_surface(geometry.size, output_scale(output)),
+            geometry: geometry,
+            output: output
+        })
+    }
+
+    fn get_surface(&mut self) -> &mut ImageSurface {
+        &mut self.surface
+    }
+
+    fn get_geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    fn set_geometry(&mut self, geometry: Geometry) {
+        self.geometry = geometry;
+    }
+
+    fn get_output(&self) -> WlcOutput {
+        self.output
+    }
+
+    fn reallocate_buffer(mut self, geometry: Geometry) -> Option<Self> {
+        let Size { w, h } = geometry.size;
+        if w == 0 || h == 0 {
+            return None
+        }
+        let scale = output_scale(self.output);
+        if geometry.size != self.geometry.size
+            || self.surface.get_width() != (geometry.size.w * scale) as i32 {
+            self.surface = blank_surface(geometry.size, scale);
+        }
+        self.geometry = geometry;
+        Some(self)
+    }
+}
+
+impl Background {
+    /// Changes what the background looks like, loading the image if
+    /// it's a different one. Needs to be drawn afterwards.
+    pub fn set_style(&mut self, style: BackgroundStyle) {
+        if let BackgroundStyle::Image { ref path, .. } = style {
+            let loaded = match self.image {
+                Some((ref loaded_path, _)) => loaded_path == path,
+                None => false
+            };
+            if !loaded {
+                self.image = load_image(path.as_str()).map(|image| (path.clone(), image));
+            }
+        } else {
+            self.image = None;
+        }
+        self.style = style;
+    }
+
+    /// Draws the background, so that it's ready to be rendered.
+    pub fn draw(self) -> Option<Self> {
+        let geometry = self.geometry;
+        match self.enable_cairo() {
+            Ok(base) => BackgroundDraw::new(base).draw(geometry)
+                .map_err(|err| warn!("Could not draw background: {:?}", err))
+                .ok(),
+            Err(err) => {
+                warn!("Could not draw background: {:?}", err);
+                None
+            }
+        }
+    }
+}
+
+/// Draws a background: a color, gradient or image over the whole output.
+pub struct BackgroundDraw {
+    base: BaseDraw<Background>
+}
+
+impl BackgroundDraw {
+    pub fn new(base: BaseDraw<Background>) -> Self {
+        BackgroundDraw {
+            base: base
+        }
+    }
+
+    fn fill(mut self, color: Color, w: f64, h: f64) -> Result<Self, DrawErr<Background>> {
+        self.base.set_color_source(color);
+        self.base.rectangle(0.0, 0.0, w, h);
+        self.base = try!(self.base.check_cairo());
+        self.base.fill();
+        self.base = try!(self.base.check_cairo());
+        Ok(self)
+    }
+
+    fn gradient(mut self, from: Color, to: Color, vertical: bool, w: f64, h: f64)
+                -> Result<Self, DrawErr<Background>> {
+        let gradient = if vertical {
+            LinearGradient::new(0.0, 0.0, 0.0, h)
+        } else {
+            LinearGradient::new(0.0, 0.0, w, 0.0)
+        };
+        for &(offset, color) in &[(0.0, from), (1.0, to)] {
+            let (r, g, b, a) = color.values();
+            gradient.add_color_stop_rgba(offset,
+                                         r as f64 / 255.0,
+                                         g as f64 / 255.0,
+                                         b as f64 / 255.0,
+                                         a as f64 / 255.0);
+        }
+        self.base.set_source(&gradient);
+        self.base.rectangle(0.0, 0.0, w, h);
+        self.base = try!(self.base.check_cairo());
+        self.base.fill();
+        self.base = try!(self.base.check_cairo());
+        Ok(self)
+    }
+
+    fn image(mut self, image: &ImageSurface, mode: ImageMode, w: f64, h: f64)
+             -> Result<Self, DrawErr<Background>> {
+        let (image_w, image_h) = (image.get_width() as f64, image.get_height() as f64);
+        if image_w == 0.0 || image_h == 0.0 {
+            return Ok(self)
+        }
+        let scale = match mode {
+            ImageMode::Fill => (w / image_w).max(h / image_h),
+            ImageMode::Fit => (w / image_w).min(h / image_h),
+            ImageMode::Center | ImageMode::Tile => 1.0
+        };
+        self.base.save();
+        if mode == ImageMode::Tile {
+            self.base.set_source_surface(image, 0.0, 0.0);
+            self.base.get_source().set_extend(Extend::Repeat);
+            self.base.rectangle(0.0, 0.0, w, h);
+        } else {
+            self.base.translate((w - image_w * scale) / 2.0, (h - image_h * scale) / 2.0);
+            self.base.scale(scale, scale);
+            self.base.set_source_surface(image, 0.0, 0.0);
+            self.base.rectangle(0.0, 0.0, image_w, image_h);
+        }
+        self.base = try!(self.base.check_cairo());
+        self.base.fill();
+        self.base.restore();
+        self.base = try!(self.base.check_cairo());
+        Ok(self)
+    }
+}
+
+impl Drawable<Background> for BackgroundDraw {
+    fn draw(mut self, background_g: Geometry) -> Result<Background, DrawErr<Background>> {
+        let (style, image) = {
+            let background = self.base.inner();
+            (background.style.clone(),
+             background.image.as_ref().map(|&(_, ref image)| image.clone()))
+        };
+        let Size { w, h } = background_g.size;
+        let (w, h) = (w as f64, h as f64);
+        self = match style {
+            BackgroundStyle::Color(color) => try!(self.fill(color, w, h)),
+            BackgroundStyle::Gradient { from, to, vertical } => {
+                try!(self.gradient(from, to, vertical, w, h))
+            },
+            BackgroundStyle::Image { mode, color, .. } => {
+                self = try!(self.fill(color, w, h));
+                match image {
+                    Some(image) => try!(self.image(&image, mode, w, h)),
+                    None => self
+                }
+            }
+        };
+        Ok(self.base.finish(background_g))
+    }
+}
+
+impl Deref for BackgroundDraw {
+    type Target = BaseDraw<Background>;
+
+    fn deref(&self) -> &BaseDraw<Background> {
+        &self.base
+    }
+}
+
+impl DerefMut for BackgroundDraw {
+    fn deref_mut(&mut self) -> &mut BaseDraw<Background> {
+        &mut self.base
+    }
+}
+
+impl Debug for Background {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Background")
+            .field("style", &self.style)
+            .field("geometry", &self.geometry as &Debug)
+            .finish()
+    }
+}
+
+unsafe impl Send for Background {}
+unsafe impl Sync for Background {}
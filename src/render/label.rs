@@ -0,0 +1,188 @@
+//! A box with a line of text in it, drawn on top of the views of an output.
+
+use std::fmt::{self, Debug};
+use std::ops::{Deref, DerefMut};
+use rustwlc::{Geometry, Point, Size, WlcOutput};
+use cairo::ImageSurface;
+
+use super::renderable::{Renderable, blank_surface, output_scale};
+use super::draw::{BaseDraw, Drawable, DrawErr};
+use super::color::Color;
+
+/// Space between the text and the edge of the label, in pixels.
+const PADDING: u32 = 6;
+
+/// A line of text, with a background behind it.
+pub struct Label {
+    /// The text shown on the label.
+    pub text: String,
+    /// The surface that contains the bytes we give to wlc to draw.
+    surface: ImageSurface,
+    /// Where the label is drawn on the output.
+    geometry: Geometry,
+    /// The output the label is drawn on.
+    output: WlcOutput,
+    /// The color behind the text.
+    pub background_color: Color,
+    /// The color of the text.
+    pub font_color: Color,
+    /// The size of the font, in pixels.
+    pub font_size: u32
+}
+
+impl Renderable for Label {
+    fn new(geometry: Geometry, output: WlcOutput) -> Option<Self> {
+        let Size { w, h } = geometry.size;
+        if w == 0 || h == 0 {
+            return None
+        }
+        Some(Label {
+            text: "".into(),
+            surface: blank_surface(geometry.size, output_scale(output)),
+            geometry: geometry,
+            output: output,
+            background_color: Color::solid_color(0, 0, 0),
+            font_color: Color::solid_color(255, 255, 255),
+            font_size: 12
+        })
+    }
+
+    fn get_surface(&mut self) -> &mut ImageSurface {
+        &mut self.surface
+    }
+
+    fn get_geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    fn set_geometry(&mut self, geometry: Geometry) {
+        self.geometry = geometry;
+    }
+
+    fn get_output(&self) -> WlcOutput {
+        self.output
+    }
+
+    fn reallocate_buffer(mut self, geometry: Geometry) -> Option<Self> {
+        let Size { w, h } = geometry.size;
+        if w == 0 || h == 0 {
+            return None
+        }
+        let scale = output_scale(self.output);
+        if geometry.size != self.geometry.size
+            || self.surface.get_width() != (geometry.size.w * scale) as i32 {
+            self.surface = blank_surface(geometry.size, scale);
+        }
+        self.geometry = geometry;
+        Some(self)
+    }
+}
+
+impl Label {
+    /// Makes a label big enough for the text, centered on the point.
+    ///
+    /// The size is estimated from the number of characters,
+    /// which is good enough for the short texts labels are used for.
+    pub fn centered(text: &str, center: Point, font_size: u32, output: WlcOutput)
+                    -> Option<Self> {
+        let size = Label::size_for(text, font_size);
+        let geometry = Geometry {
+            origin: Point {
+                x: center.x - size.w as i32 / 2,
+                y: center.y - size.h as i32 / 2
+            },
+            size: size
+        };
+        Label::new(geometry, output).map(|mut label| {
+            label.text = text.into();
+            label.font_size = font_size;
+            label
+        })
+    }
+
+    /// Gets the size a label needs to fit the text.
+    pub fn size_for(text: &str, font_size: u32) -> Size {
+        let chars = text.chars().count() as u32;
+        Size {
+            w: chars * font_size * 2 / 3 + PADDING * 2,
+            h: font_size + PADDING * 2
+        }
+    }
+
+    /// Draws the label, so that it's ready to be rendered.
+    pub fn draw(self) -> Option<Self> {
+        let geometry = self.geometry;
+        match self.enable_cairo() {
+            Ok(base) => LabelDraw::new(base).draw(geometry)
+                .map_err(|err| warn!("Could not draw label: {:?}", err))
+                .ok(),
+            Err(err) => {
+                warn!("Could not draw label: {:?}", err);
+                None
+            }
+        }
+    }
+}
+
+/// Draws a label: fills the background, then writes the text on top.
+pub struct LabelDraw {
+    base: BaseDraw<Label>
+}
+
+impl LabelDraw {
+    pub fn new(base: BaseDraw<Label>) -> Self {
+        LabelDraw {
+            base: base
+        }
+    }
+}
+
+impl Drawable<Label> for LabelDraw {
+    fn draw(mut self, label_g: Geometry) -> Result<Label, DrawErr<Label>> {
+        let (background_color, font_color, font_size, text) = {
+            let label = self.base.inner();
+            (label.background_color, label.font_color,
+             label.font_size as f64, label.text.clone())
+        };
+        let Size { w, h } = label_g.size;
+        self.base.set_color_source(background_color);
+        self.base.rectangle(0.0, 0.0, w as f64, h as f64);
+        self.base = try!(self.base.check_cairo());
+        self.base.fill();
+        self.base = try!(self.base.check_cairo());
+
+        self.base.set_font_size(font_size);
+        self.base.move_to(PADDING as f64, PADDING as f64 + font_size * 0.8);
+        self.base = try!(self.base.check_cairo());
+        self.base.set_color_source(font_color);
+        self.base.show_text(text.as_str());
+        self.base = try!(self.base.check_cairo());
+        Ok(self.base.finish(label_g))
+    }
+}
+
+impl Deref for LabelDraw {
+    type Target = BaseDraw<Label>;
+
+    fn deref(&self) -> &BaseDraw<Label> {
+        &self.base
+    }
+}
+
+impl DerefMut for LabelDraw {
+    fn deref_mut(&mut self) -> &mut BaseDraw<Label> {
+        &mut self.base
+    }
+}
+
+impl Debug for Label {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Label")
+            .field("text", &self.text)
+            .field("geometry", &self.geometry as &Debug)
+            .finish()
+    }
+}
+
+unsafe impl Send for Label {}
+unsafe impl Sync for Label {}
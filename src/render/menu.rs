@@ -0,0 +1,247 @@
+//! A list of lines of text under a heading, one of which can be highlighted,
+//! drawn on top of the views of an output.
+
+use std::cmp;
+use std::fmt::{self, Debug};
+use std::ops::{Deref, DerefMut};
+use rustwlc::{Geometry, Point, Size, WlcOutput};
+use cairo::ImageSurface;
+
+use super::renderable::{Renderable, blank_surface, output_scale, logical_resolution};
+use super::draw::{BaseDraw, Drawable, DrawErr};
+use super::color::Color;
+
+/// Space between the text and the edge of the menu, in pixels.
+const PADDING: u32 = 8;
+
+/// The most lines that are shown at once. When there are more,
+/// the ones around the highlighted line are shown.
+const MAX_LINES: usize = 15;
+
+/// A heading with lines of text beneath it.
+pub struct Menu {
+    /// The text shown above the lines.
+    pub heading: String,
+    /// The lines of text.
+    pub lines: Vec<String>,
+    /// The index of the line that is highlighted, if any.
+    pub selected: Option<usize>,
+    /// The surface that contains the bytes we give to wlc to draw.
+    surface: ImageSurface,
+    /// Where the menu is drawn on the output.
+    geometry: Geometry,
+    /// The output the menu is drawn on.
+    output: WlcOutput,
+    /// The color behind the text.
+    pub background_color: Color,
+    /// The color behind the highlighted line.
+    pub selected_color: Color,
+    /// The color of the text.
+    pub font_color: Color,
+    /// The size of the font, in pixels.
+    pub font_size: u32
+}
+
+impl Renderable for Menu {
+    fn new(geometry: Geometry, output: WlcOutput) -> Option<Self> {
+        let Size { w, h } = geometry.size;
+        if w == 0 || h == 0 {
+            return None
+        }
+        Some(Menu {
+            heading: "".into(),
+            lines: vec![],
+            selected: None,
+            surface: blank_surface(geometry.size, output_scale(output)),
+            geometry: geometry,
+            output: output,
+            background_color: Color::solid_color(0x22, 0x22, 0x22),
+            selected_color: Color::solid_color(0x38, 0x66, 0x90),
+            font_color: Color::solid_color(0xff, 0xff, 0xff),
+            font_size: 16
+        })
+    }
+
+    fn get_surface(&mut self) -> &mut ImageSurface {
+        &mut self.surface
+    }
+
+    fn get_geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    fn set_geometry(&mut self, geometry: Geometry) {
+        self.geometry = geometry;
+    }
+
+    fn get_output(&self) -> WlcOutput {
+        self.output
+    }
+
+    fn reallocate_buffer(mut self, geometry: Geometry) -> Option<Self> {
+        let Size { w, h } = geometry.size;
+        if w == 0 || h == 0 {
+            return None
+        }
+        let scale = output_scale(self.output);
+        if geometry.size != self.geometry.size
+            || self.surface.get_width() != (geometry.size.w * scale) as i32 {
+            self.surface = blank_surface(geometry.size, scale);
+        }
+        self.geometry = geometry;
+        Some(self)
+    }
+}
+
+impl Menu {
+    /// Makes a menu big enough for the heading and lines,
+    /// centered on the output.
+    pub fn centered(heading: &str, lines: Vec<String>, selected: Option<usize>,
+                    font_size: u32, output: WlcOutput) -> Option<Self> {
+        let resolution = match logical_resolution(output) {
+            Some(resolution) => resolution,
+            None => return None
+        };
+        let longest = lines.iter().map(|line| line.chars().count())
+            .chain(Some(heading.chars().count()))
+            .max().unwrap_or(0) as u32;
+        let shown = cmp::min(lines.len(), MAX_LINES) as u32;
+        let line_height = Menu::line_height(font_size);
+        let size = Size {
+            w: cmp::min(longest * font_size * 2 / 3 + PADDING * 2, resolution.w),
+            h: cmp::min((shown + 1) * line_height + PADDING * 2, resolution.h)
+        };
+        let geometry = Geometry {
+            origin: Point {
+                x: (resolution.w as i32 - size.w as i32) / 2,
+                y: (resolution.h as i32 - size.h as i32) / 2
+            },
+            size: size
+        };
+        Menu::new(geometry, output).map(|mut menu| {
+            menu.heading = heading.into();
+            menu.lines = lines;
+            menu.selected = selected;
+            menu.font_size = font_size;
+            menu
+        })
+    }
+
+    /// Gets the height of each line, in pixels.
+    fn line_height(font_size: u32) -> u32 {
+        font_size + font_size / 2
+    }
+
+    /// Gets the index of the first line that is shown,
+    /// so that the highlighted line is always shown.
+    pub fn first_shown(&self) -> usize {
+        match self.selected {
+            Some(selected) if selected >= MAX_LINES => selected + 1 - MAX_LINES,
+            _ => 0
+        }
+    }
+
+    /// Draws the menu, so that it's ready to be rendered.
+    pub fn draw(self) -> Option<Self> {
+        let geometry = self.geometry;
+        match self.enable_cairo() {
+            Ok(base) => MenuDraw::new(base).draw(geometry)
+                .map_err(|err| warn!("Could not draw menu: {:?}", err))
+                .ok(),
+            Err(err) => {
+                warn!("Could not draw menu: {:?}", err);
+                None
+            }
+        }
+    }
+}
+
+/// Draws a menu: fills the background, highlights the selected line,
+/// then writes the heading and the lines on top.
+pub struct MenuDraw {
+    base: BaseDraw<Menu>
+}
+
+impl MenuDraw {
+    pub fn new(base: BaseDraw<Menu>) -> Self {
+        MenuDraw {
+            base: base
+        }
+    }
+
+    fn draw_line(mut self, text: &str, row: u32, highlighted: bool, width: f64)
+                 -> Result<Self, DrawErr<Menu>> {
+        let (selected_color, font_color, font_size) = {
+            let menu = self.base.inner();
+            (menu.selected_color, menu.font_color, menu.font_size)
+        };
+        let line_height = Menu::line_height(font_size) as f64;
+        let top = PADDING as f64 + row as f64 * line_height;
+        if highlighted {
+            self.base.set_color_source(selected_color);
+            self.base.rectangle(0.0, top, width, line_height);
+            self.base = try!(self.base.check_cairo());
+            self.base.fill();
+            self.base = try!(self.base.check_cairo());
+        }
+        self.base.set_font_size(font_size as f64);
+        self.base.move_to(PADDING as f64, top + font_size as f64);
+        self.base = try!(self.base.check_cairo());
+        self.base.set_color_source(font_color);
+        self.base.show_text(text);
+        self.base = try!(self.base.check_cairo());
+        Ok(self)
+    }
+}
+
+impl Drawable<Menu> for MenuDraw {
+    fn draw(mut self, menu_g: Geometry) -> Result<Menu, DrawErr<Menu>> {
+        let (background_color, heading, lines, selected, first) = {
+            let menu = self.base.inner();
+            (menu.background_color, menu.heading.clone(), menu.lines.clone(),
+             menu.selected, menu.first_shown())
+        };
+        let Size { w, h } = menu_g.size;
+        self.base.set_color_source(background_color);
+        self.base.rectangle(0.0, 0.0, w as f64, h as f64);
+        self.base = try!(self.base.check_cairo());
+        self.base.fill();
+        self.base = try!(self.base.check_cairo());
+
+        self = try!(self.draw_line(heading.as_str(), 0, false, w as f64));
+        for (row, (index, line)) in lines.iter().enumerate()
+            .skip(first).take(MAX_LINES).enumerate() {
+            self = try!(self.draw_line(line.as_str(), row as u32 + 1,
+                                       selected == Some(index), w as f64));
+        }
+        Ok(self.base.finish(menu_g))
+    }
+}
+
+impl Deref for MenuDraw {
+    type Target = BaseDraw<Menu>;
+
+    fn deref(&self) -> &BaseDraw<Menu> {
+        &self.base
+    }
+}
+
+impl DerefMut for MenuDraw {
+    fn deref_mut(&mut self) -> &mut BaseDraw<Menu> {
+        &mut self.base
+    }
+}
+
+impl Debug for Menu {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Menu")
+            .field("heading", &self.heading)
+            .field("lines", &self.lines)
+            .field("selected", &self.selected)
+            .field("geometry", &self.geometry as &Debug)
+            .finish()
+    }
+}
+
+unsafe impl Send for Menu {}
+unsafe impl Sync for Menu {}
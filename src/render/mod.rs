@@ -1,8 +1,18 @@
 mod renderable;
 mod draw;
 mod color;
+mod label;
+mod menu;
+mod status_bar;
+mod background;
+mod osd;
 pub mod screen_scrape;
 
-pub use self::renderable::Renderable;
+pub use self::renderable::{Renderable, output_scale, logical_resolution};
 pub use self::draw::{Drawable, DrawErr, BaseDraw};
 pub use self::color::Color;
+pub use self::label::Label;
+pub use self::menu::Menu;
+pub use self::status_bar::{StatusBar, BarContent, BarPosition, WorkspaceTag};
+pub use self::background::{Background, BackgroundStyle};
+pub use self::osd::Osd;
@@ -0,0 +1,223 @@
+//! A box with a short message in it, shown on top of the views for a moment.
+//!
+//! The message can be several lines long, and can have a bar under it
+//! that is filled by a percentage, e.g for the volume.
+
+use std::cmp;
+use std::fmt::{self, Debug};
+use std::ops::{Deref, DerefMut};
+use rustwlc::{Geometry, Size, WlcOutput};
+use cairo::ImageSurface;
+
+use super::renderable::{Renderable, blank_surface, output_scale};
+use super::draw::{BaseDraw, Drawable, DrawErr};
+use super::color::Color;
+
+/// Space between the text and the edge of the box, in pixels.
+const PADDING: u32 = 12;
+
+/// How wide the bar of a percentage is at least, in pixels.
+const BAR_WIDTH: u32 = 200;
+
+/// How tall the bar of a percentage is, in pixels.
+const BAR_HEIGHT: u32 = 8;
+
+/// A message, and optionally a percentage, drawn on top of an output.
+pub struct Osd {
+    /// The text shown, one line after another.
+    pub text: String,
+    /// How much of the bar under the text is filled, out of 100.
+    pub value: Option<u32>,
+    /// The surface that contains the bytes we give to wlc to draw.
+    surface: ImageSurface,
+    /// Where the box is drawn on the output.
+    geometry: Geometry,
+    /// The output the box is drawn on.
+    output: WlcOutput,
+    /// The color behind the text.
+    pub background_color: Color,
+    /// The color of the text and of the filled part of the bar.
+    pub font_color: Color,
+    /// The size of the font, in pixels.
+    pub font_size: u32
+}
+
+impl Renderable for Osd {
+    fn new(geometry: Geometry, output: WlcOutput) -> Option<Self> {
+        let Size { w, h } = geometry.size;
+        if w == 0 || h == 0 {
+            return None
+        }
+        Some(Osd {
+            text: "".into(),
+            value: None,
+            surface: blank_surface(geometry.size, output_scale(output)),
+            geometry: geometry,
+            output: output,
+            background_color: Color::solid_color(0, 0, 0),
+            font_color: Color::solid_color(255, 255, 255),
+            font_size: 16
+        })
+    }
+
+    fn get_surface(&mut self) -> &mut ImageSurface {
+        &mut self.surface
+    }
+
+    fn get_geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    fn set_geometry(&mut self, geometry: Geometry) {
+        self.geometry = geometry;
+    }
+
+    fn get_output(&self) -> WlcOutput {
+        self.output
+    }
+
+    fn reallocate_buffer(mut self, geometry: Geometry) -> Option<Self> {
+        let Size { w, h } = geometry.size;
+        if w == 0 || h == 0 {
+            return None
+        }
+        let scale = output_scale(self.output);
+        if geometry.size != self.geometry.size
+            || self.surface.get_width() != (geometry.size.w * scale) as i32 {
+            self.surface = blank_surface(geometry.size, scale);
+        }
+        self.geometry = geometry;
+        Some(self)
+    }
+}
+
+impl Osd {
+    /// Gets the size a box needs to fit the text, and the bar if there is one.
+    ///
+    /// Like labels, the width of the text is estimated from the number
+    /// of characters.
+    pub fn size_for(text: &str, value: Option<u32>, font_size: u32) -> Size {
+        let lines = text.lines().count() as u32;
+        let longest = text.lines()
+            .map(|line| line.chars().count() as u32)
+            .max()
+            .unwrap_or(0);
+        let mut w = longest * font_size * 2 / 3;
+        let mut h = lines * line_height(font_size);
+        if value.is_some() {
+            w = cmp::max(w, BAR_WIDTH);
+            h += BAR_HEIGHT + if lines > 0 { PADDING } else { 0 };
+        }
+        Size { w: w + PADDING * 2, h: h + PADDING * 2 }
+    }
+
+    /// Draws the box, so that it's ready to be rendered.
+    pub fn draw(self) -> Option<Self> {
+        let geometry = self.geometry;
+        match self.enable_cairo() {
+            Ok(base) => OsdDraw::new(base).draw(geometry)
+                .map_err(|err| warn!("Could not draw OSD: {:?}", err))
+                .ok(),
+            Err(err) => {
+                warn!("Could not draw OSD: {:?}", err);
+                None
+            }
+        }
+    }
+}
+
+/// How far apart the lines of text are, in pixels.
+fn line_height(font_size: u32) -> u32 {
+    font_size * 5 / 4
+}
+
+/// Draws an OSD: fills the background, writes each line of the text,
+/// then draws the bar under it.
+pub struct OsdDraw {
+    base: BaseDraw<Osd>
+}
+
+impl OsdDraw {
+    pub fn new(base: BaseDraw<Osd>) -> Self {
+        OsdDraw {
+            base: base
+        }
+    }
+
+    fn fill(mut self, color: Color, x: f64, y: f64, w: f64, h: f64)
+            -> Result<Self, DrawErr<Osd>> {
+        self.base.set_color_source(color);
+        self.base.rectangle(x, y, w, h);
+        self.base = try!(self.base.check_cairo());
+        self.base.fill();
+        self.base = try!(self.base.check_cairo());
+        Ok(self)
+    }
+}
+
+impl Drawable<Osd> for OsdDraw {
+    fn draw(mut self, osd_g: Geometry) -> Result<Osd, DrawErr<Osd>> {
+        let (background_color, font_color, font_size, text, value) = {
+            let osd = self.base.inner();
+            (osd.background_color, osd.font_color,
+             osd.font_size, osd.text.clone(), osd.value)
+        };
+        let Size { w, h } = osd_g.size;
+        let (w, h) = (w as f64, h as f64);
+        let padding = PADDING as f64;
+        self = try!(self.fill(background_color, 0.0, 0.0, w, h));
+
+        self.base.set_font_size(font_size as f64);
+        let mut y = padding;
+        for line in text.lines() {
+            self.base.move_to(padding, y + font_size as f64 * 0.9);
+            self.base = try!(self.base.check_cairo());
+            self.base.set_color_source(font_color);
+            self.base.show_text(line);
+            self.base = try!(self.base.check_cairo());
+            y += line_height(font_size) as f64;
+        }
+
+        if let Some(value) = value {
+            let bar_y = h - padding - BAR_HEIGHT as f64;
+            let bar_w = w - padding * 2.0;
+            let filled = bar_w * cmp::min(value, 100) as f64 / 100.0;
+            // The empty part of the bar is halfway between the two colors
+            let ((r1, g1, b1, _), (r2, g2, b2, _)) = (font_color.values(),
+                                                      background_color.values());
+            let empty = Color::solid_color(((r1 as u16 + r2 as u16) / 2) as u8,
+                                           ((g1 as u16 + g2 as u16) / 2) as u8,
+                                           ((b1 as u16 + b2 as u16) / 2) as u8);
+            self = try!(self.fill(empty, padding, bar_y, bar_w, BAR_HEIGHT as f64));
+            self = try!(self.fill(font_color, padding, bar_y, filled, BAR_HEIGHT as f64));
+        }
+        Ok(self.base.finish(osd_g))
+    }
+}
+
+impl Deref for OsdDraw {
+    type Target = BaseDraw<Osd>;
+
+    fn deref(&self) -> &BaseDraw<Osd> {
+        &self.base
+    }
+}
+
+impl DerefMut for OsdDraw {
+    fn deref_mut(&mut self) -> &mut BaseDraw<Osd> {
+        &mut self.base
+    }
+}
+
+impl Debug for Osd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Osd")
+            .field("text", &self.text)
+            .field("value", &self.value)
+            .field("geometry", &self.geometry as &Debug)
+            .finish()
+    }
+}
+
+unsafe impl Send for Osd {}
+unsafe impl Sync for Osd {}
@@ -1,10 +1,15 @@
 //! Defines the renderable trait.
 //! A renderable object holds the buffer that is written to wlc_pixel_write.
 //! The buffer can only be modified by casting the type to a `Drawable`.
+//!
+//! The geometry of a `Renderable` is in the same (logical) coordinates as the
+//! layout. On outputs with a scale factor the buffer holds that many pixels
+//! for each logical one, and is drawn to as if it were logical.
 
-use rustwlc::{Geometry, WlcOutput};
+use std::iter;
+use rustwlc::{Geometry, Point, Size, WlcOutput};
 use rustwlc::render::{write_pixels, wlc_pixel_format, calculate_stride};
-use cairo::{self, Context, ImageSurface, Operator};
+use cairo::{self, Context, Format, ImageSurface, Operator};
 use super::draw::BaseDraw;
 
 /// Something that can be rendered by wlc.
@@ -54,6 +59,12 @@ pub trait Renderable {
             cairo::Status::Success => {},
             err => return Err(err)
         }
+        let scale = output_scale(self.get_output()) as f64;
+        cairo.scale(scale, scale);
+        match cairo.status() {
+            cairo::Status::Success => {},
+            err => return Err(err)
+        }
         Ok(BaseDraw::new(self, cairo))
     }
 
@@ -61,8 +72,10 @@ pub trait Renderable {
     ///
     /// Automatically ensures that the buffer does not clip the sides
     fn render(&mut self) {
-        let output_res = self.get_output().get_resolution()
+        let output = self.get_output();
+        let output_res = logical_resolution(output)
             .expect("Output had no resolution");
+        let scale = output_scale(output);
         let mut geometry = self.get_geometry();
         let surface = self.get_surface();
         let buffer = surface.get_data()
@@ -85,6 +98,7 @@ pub trait Renderable {
         if geometry.origin.y < 0 {
             geometry.origin.y = 0;
         }
+        let geometry = scale_geometry(geometry, scale);
         let stride = calculate_stride(geometry.size.w);
         if stride * geometry.size.h > buffer.len() as u32 {
             warn!("Buffer to big to draw! Not drawing");
@@ -93,3 +107,65 @@ pub trait Renderable {
         write_pixels(wlc_pixel_format::WLC_RGBA8888, geometry, &buffer);
     }
 }
+
+/// Gets the scale factor of the output, which is never less than 1.
+#[cfg(not(test))]
+#[inline]
+pub fn output_scale(output: WlcOutput) -> u32 {
+    ::std::cmp::max(output.get_scale(), 1)
+}
+
+#[cfg(test)]
+#[inline]
+pub fn output_scale(_output: WlcOutput) -> u32 {
+    1
+}
+
+/// Gets the size of the output in logical coordinates, which is its
+/// resolution divided by its scale factor.
+#[cfg(not(test))]
+#[inline]
+pub fn logical_resolution(output: WlcOutput) -> Option<Size> {
+    output.get_virtual_resolution()
+}
+
+#[cfg(test)]
+#[inline]
+pub fn logical_resolution(output: WlcOutput) -> Option<Size> {
+    output.get_resolution()
+}
+
+/// Scales a logical geometry to the pixels of an output with the scale factor.
+pub fn scale_geometry(geometry: Geometry, scale: u32) -> Geometry {
+    Geometry {
+        origin: Point {
+            x: geometry.origin.x * scale as i32,
+            y: geometry.origin.y * scale as i32
+        },
+        size: scale_size(geometry.size, scale)
+    }
+}
+
+/// Scales a logical size to the pixels of an output with the scale factor.
+pub fn scale_size(size: Size, scale: u32) -> Size {
+    Size {
+        w: size.w * scale,
+        h: size.h * scale
+    }
+}
+
+/// Makes a blank surface for a `Renderable` of the (logical) size to draw on,
+/// with enough pixels for an output with the scale factor.
+pub fn blank_surface(size: Size, scale: u32) -> ImageSurface {
+    let Size { w, h } = scale_size(size, scale);
+    let stride = calculate_stride(w) as i32;
+    let data: Vec<u8> = iter::repeat(0).take(h as usize * stride as usize).collect();
+    ImageSurface::create_for_data(data.into_boxed_slice(),
+                                  drop_data,
+                                  Format::ARgb32,
+                                  w as i32,
+                                  h as i32,
+                                  stride)
+}
+
+fn drop_data(_: Box<[u8]>) { }
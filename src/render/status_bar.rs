@@ -0,0 +1,374 @@
+//! A bar along the top or bottom of an output, drawn by Way Cooler.
+//!
+//! It shows the workspaces, the title of the focused view and text blocks
+//! set by the user. Its options are read from the bar category of the
+//! registry.
+
+use std::cmp;
+use std::fmt::{self, Debug};
+use std::ops::{Deref, DerefMut};
+use rustc_serialize::json::Json;
+use rustwlc::{Geometry, Point, Size, WlcOutput};
+use cairo::ImageSurface;
+use uuid::Uuid;
+
+use super::renderable::{Renderable, blank_surface, output_scale, logical_resolution};
+use super::draw::{BaseDraw, Drawable, DrawErr};
+use super::color::Color;
+use ::registry;
+
+/// Space around the text of each part of the bar, in pixels.
+const PADDING: u32 = 6;
+
+/// Put between the blocks set by the user.
+const BLOCK_SEPARATOR: &'static str = "  |  ";
+
+/// Which edge of the output the bar is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarPosition {
+    Top,
+    Bottom
+}
+
+/// The state of a workspace, as shown on the bar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceTag {
+    pub name: String,
+    /// Whether it's the workspace the user is on.
+    pub focused: bool,
+    /// Whether it's shown on its output.
+    pub visible: bool,
+    /// Whether a view on it wants attention.
+    pub urgent: bool
+}
+
+/// Everything the bar shows.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BarContent {
+    pub workspaces: Vec<WorkspaceTag>,
+    /// The title of the focused view, if it's on this output.
+    pub title: String,
+    /// The text blocks, shown on the right in this order.
+    pub blocks: Vec<String>
+}
+
+/// The colors of the bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarColors {
+    pub background: Color,
+    pub font: Color,
+    /// Behind the name of the focused workspace.
+    pub focused: Color,
+    /// Behind the names of workspaces shown on other outputs.
+    pub visible: Color,
+    /// Behind the names of workspaces that want attention.
+    pub urgent: Color
+}
+
+/// A bar drawn along an edge of an output.
+pub struct StatusBar {
+    /// What is drawn on the bar.
+    pub content: BarContent,
+    /// The colors it is drawn with.
+    pub colors: BarColors,
+    /// The surface that contains the bytes we give to wlc to draw.
+    surface: ImageSurface,
+    /// Where the bar is drawn on the output.
+    geometry: Geometry,
+    /// The output the bar is drawn on.
+    output: WlcOutput
+}
+
+/// Reads an option from the bar category of the registry.
+fn bar_option(key: &str) -> Option<Json> {
+    let lock = registry::clients_read();
+    let client = lock.client(Uuid::nil()).unwrap();
+    let handle = registry::ReadHandle::new(&client);
+    handle.read("bar".into()).ok()
+        .and_then(|bar| bar.get(key).cloned())
+}
+
+/// Reads a color option from the bar category, or the default if unset.
+fn color_option(key: &str, default: u32) -> Color {
+    bar_option(key)
+        .and_then(|color| color.as_f64())
+        .map(|num| num as u32)
+        .unwrap_or(default).into()
+}
+
+/// Estimates how wide the text is, in pixels.
+fn text_width(text: &str, font_size: u32) -> u32 {
+    text.chars().count() as u32 * font_size * 2 / 3
+}
+
+impl Renderable for StatusBar {
+    fn new(geometry: Geometry, output: WlcOutput) -> Option<Self> {
+        let Size { w, h } = geometry.size;
+        if w == 0 || h == 0 {
+            return None
+        }
+        Some(StatusBar {
+            content: BarContent::default(),
+            colors: StatusBar::configured_colors(),
+            surface: blank_surface(geometry.size, output_scale(output)),
+            geometry: geometry,
+            output: output
+        })
+    }
+
+    fn get_surface(&mut self) -> &mut ImageSurface {
+        &mut self.surface
+    }
+
+    fn get_geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    fn set_geometry(&mut self, geometry: Geometry) {
+        self.geometry = geometry;
+    }
+
+    fn get_output(&self) -> WlcOutput {
+        self.output
+    }
+
+    fn reallocate_buffer(mut self, geometry: Geometry) -> Option<Self> {
+        let Size { w, h } = geometry.size;
+        if w == 0 || h == 0 {
+            return None
+        }
+        let scale = output_scale(self.output);
+        if geometry.size != self.geometry.size
+            || self.surface.get_width() != (geometry.size.w * scale) as i32 {
+            self.surface = blank_surface(geometry.size, scale);
+        }
+        self.geometry = geometry;
+        Some(self)
+    }
+}
+
+impl StatusBar {
+    /// Determines if the bar is turned on in the registry.
+    pub fn enabled() -> bool {
+        bar_option("enabled")
+            .and_then(|enabled| enabled.as_boolean())
+            .unwrap_or(false)
+    }
+
+    /// Gets which edge of the output the bar is on. Defaults to the top.
+    pub fn position() -> BarPosition {
+        match bar_option("position").as_ref().and_then(Json::as_string) {
+            Some("bottom") => BarPosition::Bottom,
+            _ => BarPosition::Top
+        }
+    }
+
+    /// Gets the height of the bar. Defaults to 20.
+    pub fn height() -> u32 {
+        bar_option("height")
+            .and_then(|height| height.as_f64())
+            .map(|num| num as u32)
+            .unwrap_or(20)
+    }
+
+    /// Gets the colors of the bar from the registry.
+    pub fn configured_colors() -> BarColors {
+        BarColors {
+            background: color_option("background_color", 0x222222),
+            font: color_option("font_color", 0xffffff),
+            focused: color_option("focused_color", 0x57beb9),
+            visible: color_option("visible_color", 0x386890),
+            urgent: color_option("urgent_color", 0xd64937)
+        }
+    }
+
+    /// Gets the text blocks set in the registry, by their names.
+    pub fn configured_blocks() -> Vec<(String, String)> {
+        bar_option("blocks")
+            .and_then(|blocks| blocks.as_object().map(|blocks| {
+                blocks.iter()
+                    .filter_map(|(name, text)| {
+                        text.as_string().map(|text| (name.clone(), text.to_string()))
+                    })
+                    .collect()
+            }))
+            .unwrap_or_default()
+    }
+
+    /// Gets the part of the output the bar takes up, if the bar is enabled.
+    pub fn reserved(output: WlcOutput) -> Option<Geometry> {
+        if !StatusBar::enabled() {
+            return None
+        }
+        let resolution = match logical_resolution(output) {
+            Some(resolution) => resolution,
+            None => return None
+        };
+        let height = cmp::min(StatusBar::height(), resolution.h);
+        let y = match StatusBar::position() {
+            BarPosition::Top => 0,
+            BarPosition::Bottom => (resolution.h - height) as i32
+        };
+        Some(Geometry {
+            origin: Point { x: 0, y: y },
+            size: Size { w: resolution.w, h: height }
+        })
+    }
+
+    /// The size of the font, in pixels.
+    fn font_size(&self) -> u32 {
+        self.geometry.size.h * 3 / 5
+    }
+
+    /// Gets where the name of each workspace is drawn,
+    /// as the left and right edge relative to the bar.
+    pub fn workspace_spans(&self) -> Vec<(String, i32, i32)> {
+        let font_size = self.font_size();
+        let mut left = 0;
+        self.content.workspaces.iter().map(|workspace| {
+            let right = left + (text_width(workspace.name.as_str(), font_size)
+                                + PADDING * 2) as i32;
+            let span = (workspace.name.clone(), left, right);
+            left = right;
+            span
+        }).collect()
+    }
+
+    /// Gets the name of the workspace drawn under the point,
+    /// which is relative to the output.
+    pub fn workspace_at(&self, point: Point) -> Option<String> {
+        let Geometry { origin, size } = self.geometry;
+        if point.y < origin.y || point.y >= origin.y + size.h as i32 {
+            return None
+        }
+        let x = point.x - origin.x;
+        self.workspace_spans().into_iter()
+            .find(|&(_, left, right)| x >= left && x < right)
+            .map(|(name, _, _)| name)
+    }
+
+    /// Draws the bar, so that it's ready to be rendered.
+    pub fn draw(self) -> Option<Self> {
+        let geometry = self.geometry;
+        match self.enable_cairo() {
+            Ok(base) => StatusBarDraw::new(base).draw(geometry)
+                .map_err(|err| warn!("Could not draw status bar: {:?}", err))
+                .ok(),
+            Err(err) => {
+                warn!("Could not draw status bar: {:?}", err);
+                None
+            }
+        }
+    }
+}
+
+/// Draws a status bar: the workspaces on the left, followed by the title,
+/// and the blocks on the right.
+pub struct StatusBarDraw {
+    base: BaseDraw<StatusBar>
+}
+
+impl StatusBarDraw {
+    pub fn new(base: BaseDraw<StatusBar>) -> Self {
+        StatusBarDraw {
+            base: base
+        }
+    }
+
+    fn fill(mut self, color: Color, x: f64, w: f64, h: f64)
+            -> Result<Self, DrawErr<StatusBar>> {
+        self.base.set_color_source(color);
+        self.base.rectangle(x, 0.0, w, h);
+        self.base = try!(self.base.check_cairo());
+        self.base.fill();
+        self.base = try!(self.base.check_cairo());
+        Ok(self)
+    }
+
+    fn text(mut self, text: &str, x: f64, h: f64, font_size: f64, color: Color)
+            -> Result<Self, DrawErr<StatusBar>> {
+        self.base.set_font_size(font_size);
+        self.base.move_to(x, (h + font_size * 0.7) / 2.0);
+        self.base = try!(self.base.check_cairo());
+        self.base.set_color_source(color);
+        self.base.show_text(text);
+        self.base = try!(self.base.check_cairo());
+        Ok(self)
+    }
+}
+
+impl Drawable<StatusBar> for StatusBarDraw {
+    fn draw(mut self, bar_g: Geometry) -> Result<StatusBar, DrawErr<StatusBar>> {
+        let (colors, content, spans, font_size) = {
+            let bar = self.base.inner();
+            (bar.colors, bar.content.clone(), bar.workspace_spans(), bar.font_size())
+        };
+        let Size { w, h } = bar_g.size;
+        let (w, h, font_size) = (w as f64, h as f64, font_size as f64);
+        self = try!(self.fill(colors.background, 0.0, w, h));
+
+        let mut right_of_workspaces = 0.0;
+        for (workspace, (_, left, right)) in content.workspaces.iter().zip(spans) {
+            let (left, right) = (left as f64, right as f64);
+            let background = if workspace.urgent {
+                Some(colors.urgent)
+            } else if workspace.focused {
+                Some(colors.focused)
+            } else if workspace.visible {
+                Some(colors.visible)
+            } else {
+                None
+            };
+            if let Some(background) = background {
+                self = try!(self.fill(background, left, right - left, h));
+            }
+            self = try!(self.text(workspace.name.as_str(), left + PADDING as f64,
+                                  h, font_size, colors.font));
+            right_of_workspaces = right;
+        }
+
+        if !content.title.is_empty() {
+            self = try!(self.text(content.title.as_str(),
+                                  right_of_workspaces + PADDING as f64 * 2.0,
+                                  h, font_size, colors.font));
+        }
+        if !content.blocks.is_empty() {
+            // Drawn over the end of a long title
+            let blocks = content.blocks.join(BLOCK_SEPARATOR);
+            self.base.set_font_size(font_size);
+            let width = self.base.text_extents(blocks.as_str()).x_advance
+                + PADDING as f64 * 2.0;
+            self = try!(self.fill(colors.background, w - width, width, h));
+            self = try!(self.text(blocks.as_str(), w - width + PADDING as f64,
+                                  h, font_size, colors.font));
+        }
+        Ok(self.base.finish(bar_g))
+    }
+}
+
+impl Deref for StatusBarDraw {
+    type Target = BaseDraw<StatusBar>;
+
+    fn deref(&self) -> &BaseDraw<StatusBar> {
+        &self.base
+    }
+}
+
+impl DerefMut for StatusBarDraw {
+    fn deref_mut(&mut self) -> &mut BaseDraw<StatusBar> {
+        &mut self.base
+    }
+}
+
+impl Debug for StatusBar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StatusBar")
+            .field("content", &self.content)
+            .field("colors", &self.colors)
+            .field("geometry", &self.geometry as &Debug)
+            .finish()
+    }
+}
+
+unsafe impl Send for StatusBar {}
+unsafe impl Sync for StatusBar {}
@@ -1,8 +1,11 @@
 pub mod gamma_control;
+pub mod server_decoration;
+pub mod timer;
 
 /// Initializes the appropriate handlers for each wayland protocol
 /// that Way Cooler supports.
 pub fn init_wayland_protocols() {
     debug!("Initializing wayland protocols");
     gamma_control::init();
+    server_decoration::init();
 }
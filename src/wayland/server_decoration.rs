@@ -0,0 +1,275 @@
+//! Module that defines the bindings for KDE's server decoration protocol.
+//! See https://github.com/KDE/kwayland/blob/master/src/client/protocols/server-decoration.xml
+//!
+//! Clients use it to tell us whether they draw their own decorations,
+//! like the header bars of GTK applications. Those views are not given
+//! borders unless their rules ask for them, see `BorderStyle`.
+use wayland::server_decoration::generated
+    ::server::org_kde_kwin_server_decoration::OrgKdeKwinServerDecoration;
+use wayland::server_decoration::generated
+    ::server::org_kde_kwin_server_decoration_manager::OrgKdeKwinServerDecorationManager;
+use rustwlc::{WlcOutput, WlcView};
+use rustwlc::wayland::{self, wlc_resource_from_wl_surface_resource};
+use wayland_server::Resource;
+use wayland_sys::server::{WAYLAND_SERVER_HANDLE, wl_client, wl_resource};
+use std::collections::HashMap;
+use std::os::raw::c_void;
+use std::sync::Mutex;
+use nix::libc::{c_int, c_uint, uint32_t, uintptr_t};
+
+use ::layout::try_lock_tree;
+
+/// The view draws its own decorations.
+const MODE_CLIENT: u32 = 1;
+/// We draw the decorations of the view.
+const MODE_SERVER: u32 = 2;
+
+lazy_static! {
+    /// The decoration modes that surfaces asked for, by their wlc handles.
+    static ref MODES: Mutex<HashMap<uintptr_t, u32>> = Mutex::new(HashMap::new());
+}
+
+static mut DECORATION_MANAGER: DecorationManagerInterface =
+    DecorationManagerInterface {
+        create: create
+    };
+
+static mut DECORATION: DecorationInterface =
+    DecorationInterface {
+        release: release,
+        request_mode: request_mode
+    };
+
+/// Generated modules from the XML protocol spec.
+mod generated {
+    // Generated code generally doesn't follow standards
+    #![allow(dead_code,non_camel_case_types,unused_unsafe,unused_variables)]
+    #![allow(non_upper_case_globals,non_snake_case,unused_imports)]
+
+    pub mod interfaces {
+        #[doc(hidden)]
+        pub use wayland_server::protocol_interfaces::{wl_surface_interface};
+        include!(concat!(env!("OUT_DIR"), "/server-decoration_interface.rs"));
+    }
+
+    pub mod server {
+        #[doc(hidden)]
+        pub use wayland_server::{Resource, Handler,
+                                 Client, Liveness,
+                                 EventLoopHandle, EventResult};
+        #[doc(hidden)]
+        pub use wayland_server::protocol::{wl_surface};
+        #[doc(hidden)]
+        pub use super::interfaces;
+        include!(concat!(env!("OUT_DIR"), "/server-decoration_api.rs"));
+    }
+}
+
+#[repr(C)]
+/// Lets clients make a decoration object for each of their surfaces.
+struct DecorationManagerInterface {
+    create: unsafe extern "C" fn (client: *mut wl_client,
+                                  resource: *mut wl_resource,
+                                  id: u32,
+                                  surface: *mut wl_resource)
+}
+
+#[repr(C)]
+/// Lets a client say who decorates one of its surfaces.
+struct DecorationInterface {
+    release: unsafe extern "C" fn (client: *mut wl_client,
+                                   resource: *mut wl_resource),
+    request_mode: unsafe extern "C" fn (client: *mut wl_client,
+                                        resource: *mut wl_resource,
+                                        mode: u32)
+}
+
+/// Determines if the view said it draws its own decorations.
+pub fn client_decorated(view: WlcView) -> bool {
+    let modes = match MODES.lock() {
+        Ok(modes) => modes,
+        Err(_) => return false
+    };
+    // Saves asking wlc for the surface when no one uses the protocol
+    if modes.is_empty() {
+        return false
+    }
+    modes.get(&surface_of(view)) == Some(&MODE_CLIENT)
+}
+
+/// Gets the wlc handle of the surface of the view.
+#[cfg(not(test))]
+#[inline]
+fn surface_of(view: WlcView) -> uintptr_t {
+    let surface: wayland::WlcResource = view.into();
+    surface.get_raw()
+}
+
+#[cfg(test)]
+#[inline]
+fn surface_of(_view: WlcView) -> uintptr_t {
+    0
+}
+
+/// Finds the view that the surface, by its wlc handle, belongs to.
+fn view_of_surface(surface: uintptr_t) -> Option<WlcView> {
+    WlcOutput::list().into_iter()
+        .flat_map(|output| output.get_views())
+        .find(|view| surface_of(*view) == surface)
+}
+
+/// Records the mode the surface asked for, and tells the client that it has
+/// it. If the surface already is a view, its borders are updated.
+unsafe extern "C" fn request_mode(_client: *mut wl_client,
+                                  resource: *mut wl_resource,
+                                  mode: u32) {
+    let surface = ffi_dispatch!(
+        WAYLAND_SERVER_HANDLE,
+        wl_resource_get_user_data,
+        resource) as uintptr_t;
+    debug!("Surface {} requested decoration mode {}", surface, mode);
+    if let Ok(mut modes) = MODES.lock() {
+        modes.insert(surface, mode);
+    }
+    decoration_send_mode(resource, mode);
+    if let Some(view) = view_of_surface(surface) {
+        match try_lock_tree() {
+            Ok(mut tree) => {
+                if let Err(err) = tree.update_border_style(view) {
+                    warn!("Could not update borders of {:?}: {:?}", view, err);
+                }
+            },
+            Err(_) => error!("Could not lock tree")
+        }
+    }
+}
+
+/// Destroys the wl_resource.
+unsafe extern "C" fn release(_client: *mut wl_client,
+                             resource: *mut wl_resource) {
+    ffi_dispatch!(
+        WAYLAND_SERVER_HANDLE,
+        wl_resource_destroy,
+        resource
+    );
+}
+
+/// Forgets the mode of the surface once its decoration object is gone.
+unsafe extern "C" fn decoration_destroyed(resource: *mut wl_resource) {
+    let surface = ffi_dispatch!(
+        WAYLAND_SERVER_HANDLE,
+        wl_resource_get_user_data,
+        resource) as uintptr_t;
+    if let Ok(mut modes) = MODES.lock() {
+        modes.remove(&surface);
+    }
+}
+
+/// Makes a decoration object for the surface. Until the client asks for
+/// something else, we decorate the surface ourselves.
+unsafe extern "C" fn create(client: *mut wl_client,
+                            _resource: *mut wl_resource,
+                            id: uint32_t,
+                            surface: *mut wl_resource) {
+    let decoration_resource = ffi_dispatch!(
+        WAYLAND_SERVER_HANDLE,
+        wl_resource_create,
+        client,
+        OrgKdeKwinServerDecoration::interface_ptr(),
+        OrgKdeKwinServerDecoration::supported_version() as i32,
+        id);
+    if decoration_resource.is_null() {
+        warn!("Out of memory, could not make a new wl_resource \
+               for a server decoration");
+        ffi_dispatch!(
+            WAYLAND_SERVER_HANDLE,
+            wl_client_post_no_memory,
+            client
+        );
+        return
+    }
+    let wlc_surface = wlc_resource_from_wl_surface_resource(surface as *const _);
+    debug!("Client made a server decoration for surface {}", wlc_surface);
+    let decoration_ptr = &mut DECORATION as *mut _ as *mut c_void;
+    ffi_dispatch!(
+        WAYLAND_SERVER_HANDLE,
+        wl_resource_set_implementation,
+        decoration_resource,
+        decoration_ptr,
+        wlc_surface as *mut c_void,
+        Some(decoration_destroyed)
+    );
+    decoration_send_mode(decoration_resource, MODE_SERVER);
+}
+
+/// Binds the handler to a new Wayland resource, created by the client,
+/// and tells it that we decorate surfaces by default.
+unsafe extern "C" fn bind(client: *mut wl_client,
+                          _data: *mut c_void,
+                          version: u32,
+                          id: u32) {
+    debug!("Binding server decoration manager resource");
+    let cur_version = OrgKdeKwinServerDecorationManager::supported_version();
+    if version > cur_version {
+        warn!("Unsupported server decoration protocol version {}!", version);
+        warn!("We only support version {}", cur_version);
+        return
+    }
+    let resource = ffi_dispatch!(
+        WAYLAND_SERVER_HANDLE,
+        wl_resource_create,
+        client,
+        OrgKdeKwinServerDecorationManager::interface_ptr(),
+        version as c_int,
+        id
+    );
+    if resource.is_null() {
+        warn!("Out of memory, could not make a new wl_resource \
+               for the server decoration manager");
+        ffi_dispatch!(
+            WAYLAND_SERVER_HANDLE,
+            wl_client_post_no_memory,
+            client
+        );
+        return
+    }
+    let global_manager_ptr = &mut DECORATION_MANAGER as *mut _ as *mut c_void;
+    ffi_dispatch!(
+        WAYLAND_SERVER_HANDLE,
+        wl_resource_set_implementation,
+        resource,
+        global_manager_ptr,
+        ::std::ptr::null_mut(),
+        None
+    );
+    ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                  wl_resource_post_event,
+                  resource,
+                  0,
+                  MODE_SERVER as c_uint);
+}
+
+/// Sends the decoration mode of the surface to the client.
+unsafe fn decoration_send_mode(resource: *mut wl_resource, mode: u32) {
+    ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                  wl_resource_post_event,
+                  resource,
+                  0,
+                  mode as c_uint);
+}
+
+/// Sets up Way Cooler to announce it uses the server decoration interface.
+pub fn init() {
+    let w_display = wayland::get_display();
+    unsafe {
+        debug!("Initializing server decoration manager");
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                      wl_global_create,
+                      w_display as *mut _,
+                      OrgKdeKwinServerDecorationManager::interface_ptr(),
+                      OrgKdeKwinServerDecorationManager::supported_version() as i32,
+                      ::std::ptr::null_mut(),
+                      bind
+        );
+    }
+}
@@ -0,0 +1,75 @@
+//! Timers on the event loop of the compositor, for things that have to
+//! happen later even when no input or client request wakes us up.
+use rustwlc::wayland;
+use wayland_sys::server::{WAYLAND_SERVER_HANDLE, wl_event_source};
+use std::mem;
+use std::os::raw::c_void;
+use nix::libc::c_int;
+
+/// A timer that calls a function when it fires.
+///
+/// It does nothing until it is armed with `arm`, and fires at most once
+/// for each time it is armed.
+#[derive(Debug)]
+pub struct Timer {
+    source: *mut wl_event_source
+}
+
+// Timers must only be made, armed and fired on the main thread,
+// they are only sent to be kept in the statics.
+unsafe impl Send for Timer {}
+
+impl Timer {
+    /// Adds a timer for the callback to the event loop.
+    pub fn new(callback: fn()) -> Option<Timer> {
+        unsafe {
+            let event_loop = ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                                           wl_display_get_event_loop,
+                                           wayland::get_display() as *mut _);
+            if event_loop.is_null() {
+                warn!("Could not get the event loop to add a timer to");
+                return None
+            }
+            let source = ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                                       wl_event_loop_add_timer,
+                                       event_loop,
+                                       fire,
+                                       callback as *mut c_void);
+            if source.is_null() {
+                warn!("Could not add a timer to the event loop");
+                return None
+            }
+            Some(Timer { source: source })
+        }
+    }
+
+    /// Makes the timer fire in `delay` milliseconds, replacing the
+    /// time it was set to fire before.
+    pub fn arm(&self, delay: u32) {
+        // A delay of zero would disarm the timer instead
+        let delay = ::std::cmp::max(delay, 1);
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_event_source_timer_update,
+                          self.source,
+                          delay as c_int);
+        }
+    }
+
+    /// Stops the timer from firing, if it was armed.
+    pub fn disarm(&self) {
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_event_source_timer_update,
+                          self.source,
+                          0);
+        }
+    }
+}
+
+/// Calls the callback the timer was made with.
+unsafe extern "C" fn fire(data: *mut c_void) -> c_int {
+    let callback: fn() = mem::transmute(data);
+    callback();
+    0
+}